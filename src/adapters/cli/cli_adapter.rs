@@ -1,41 +1,622 @@
+use crate::adapters::cli::commands::{AddItem, Cli, Command, ImportSource};
+use crate::adapters::cli::diagnostics::{render_diagnostics, render_parse_error};
+use crate::adapters::cli::init_wizard::run_wizard;
 use crate::application::services::project_service::ProjectService;
-use crate::domain::models::config::Config;
+use crate::domain::models::api_key::resolve_api_keys;
+use crate::domain::models::config::{Config, ConflictPolicy};
+use crate::domain::models::database_import::{database_type_of, import_tables, project_name};
+use crate::domain::models::ddl::{ddl_database_type, parse_ddl};
+use crate::domain::models::manifest::{FileOutcome, GenerationReport};
+use crate::domain::models::openapi_import::import_openapi;
+use crate::domain::models::relation::resolve_relations;
+use crate::domain::models::scaffold::{new_endpoint, new_model, Addition};
 use crate::domain::ports::file_system::FileSystem;
 use crate::domain::ports::project_generator::ProjectGenerator;
+use crate::domain::ports::schema_introspector::SchemaIntrospector;
+use crate::domain::validation::config_validator::validate_config;
+use crate::infrastructure::file_system::recording_file_system::{
+    FileChange, RecordedWrite, RecordingFileSystem,
+};
+use crate::output::{
+    config_toml_from, config_toml_with_endpoint, config_toml_with_model, init_config_toml,
+    openapi_yaml_content,
+};
+use similar::TextDiff;
+use std::error::Error;
+use std::io::Write;
+use std::path::Path;
 
-pub struct CliAdapter<F: FileSystem> {
+pub struct CliAdapter<F: FileSystem, S: SchemaIntrospector> {
     project_service: ProjectService<F>,
+    introspector: S,
 }
 
-impl<F: FileSystem> CliAdapter<F> {
-    pub fn new(project_service: ProjectService<F>) -> Self {
-        Self { project_service }
+impl<F: FileSystem, S: SchemaIntrospector> CliAdapter<F, S> {
+    pub fn new(project_service: ProjectService<F>, introspector: S) -> Self {
+        Self {
+            project_service,
+            introspector,
+        }
+    }
+
+    /// Runs the parsed command line, returning an error for the caller to report.
+    pub async fn run(&self, cli: Cli) -> Result<(), Box<dyn Error>> {
+        let command = cli.command.as_ref().unwrap_or(&Command::Generate);
+        if cli.dry_run
+            && !matches!(
+                command,
+                Command::New { .. } | Command::Generate | Command::Add { .. }
+            )
+        {
+            return Err("--dry-run only applies to `new`, `generate` and `add`".into());
+        }
+        match command {
+            Command::New { name } => {
+                let mut config = self.load_config(&cli.config).await?;
+                if let Some(name) = name {
+                    config.project_name = name.clone();
+                }
+                let out_dir = Self::out_dir(&cli, &config);
+                if !cli.force && self.project_service.file_system.exists(&out_dir).await {
+                    return Err(format!(
+                        "'{}' already exists; use `prkserver generate` or pass --force",
+                        out_dir
+                    )
+                    .into());
+                }
+                self.generate(&cli, config, &out_dir).await
+            }
+            Command::Generate => {
+                let config = self.load_config(&cli.config).await?;
+                let out_dir = Self::out_dir(&cli, &config);
+                self.generate(&cli, config, &out_dir).await
+            }
+            Command::Diff => {
+                let mut config = self.load_config(&cli.config).await?;
+                Self::apply_overrides(&cli, &mut config);
+                let out_dir = Self::out_dir(&cli, &config);
+                self.diff(&cli, config, &out_dir).await
+            }
+            Command::Validate => {
+                let config = self.load_config(&cli.config).await?;
+                if !cli.quiet {
+                    println!(
+                        "'{}' is valid ({} models).",
+                        cli.config,
+                        config.models.len()
+                    );
+                }
+                Ok(())
+            }
+            Command::Init => {
+                let file_system = &self.project_service.file_system;
+                if !cli.force && file_system.exists(&cli.config).await {
+                    return Err(format!(
+                        "'{}' already exists; pass --force to overwrite",
+                        cli.config
+                    )
+                    .into());
+                }
+                // `--yes` takes every default instead of asking, e.g. without a terminal
+                let config = if cli.yes {
+                    run_wizard(std::io::empty(), std::io::sink())?
+                } else {
+                    run_wizard(std::io::stdin().lock(), std::io::stderr())?
+                };
+                let content = init_config_toml(&config);
+                file_system
+                    .write_file(&cli.config, &content)
+                    .await
+                    .map_err(|e| format!("Failed to write {}: {}", cli.config, e))?;
+                if !cli.quiet {
+                    println!("Wrote {}.", cli.config);
+                }
+                Ok(())
+            }
+            Command::Openapi => {
+                let config = self.load_config(&cli.config).await?;
+                print!("{}", openapi_yaml_content(&config));
+                Ok(())
+            }
+            Command::Import {
+                source: ImportSource::Openapi { file },
+            } => self.import_openapi(&cli, file).await,
+            Command::Import {
+                source: ImportSource::Database { url },
+            } => self.import_database(&cli, url).await,
+            Command::Import {
+                source:
+                    ImportSource::Sql {
+                        file,
+                        database_type,
+                    },
+            } => self.import_sql(&cli, file, database_type.as_deref()).await,
+            Command::Add { item } => self.add(&cli, item).await,
+        }
     }
 
-    pub async fn run(&self, config_path: &str) {
+    /// Reads, parses and validates the config, reporting every problem at once.
+    async fn load_config(&self, config_path: &str) -> Result<Config, Box<dyn Error>> {
         let config_content = self
             .project_service
             .file_system
             .read_to_string(config_path)
             .await
-            .expect("Failed to read config.toml");
-        let config: Config = toml::from_str(&config_content).expect("Failed to parse config.toml");
+            .map_err(|e| format!("Failed to read {}: {}", config_path, e))?;
+        Self::parse_config(config_path, &config_content)
+    }
 
-        match self.project_service.generate_project(config).await {
-            Ok(()) => println!(
-                "Project '{}' created successfully.\n\ncd {}\ngit init\ncargo fmt",
-                self.project_service
-                    .file_system
-                    .read_to_string(config_path)
-                    .await
-                    .unwrap(), // Simplified for demo
-                self.project_service
-                    .file_system
-                    .read_to_string(config_path)
-                    .await
-                    .unwrap()
+    fn parse_config(config_path: &str, config_content: &str) -> Result<Config, Box<dyn Error>> {
+        let config: Config = toml::from_str(config_content)
+            .map_err(|e| render_parse_error(config_path, config_content, &e))?;
+        let diagnostics = validate_config(&config);
+        if !diagnostics.is_empty() {
+            return Err(render_diagnostics(config_path, config_content, &diagnostics).into());
+        }
+        Ok(resolve_api_keys(&resolve_relations(&config)))
+    }
+
+    /// Writes a config mapped from an OpenAPI document.
+    async fn import_openapi(&self, cli: &Cli, file: &str) -> Result<(), Box<dyn Error>> {
+        let file_system = &self.project_service.file_system;
+        self.check_import_target(cli).await?;
+        let content = file_system
+            .read_to_string(file)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", file, e))?;
+        // JSON documents are valid YAML too
+        let document: serde_json::Value = serde_yaml::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", file, e))?;
+        let import = import_openapi(&document).map_err(|e| format!("{}: {}", file, e))?;
+        self.write_import(cli, file, &import.config, &import.skipped)
+            .await
+    }
+
+    /// Writes a config mapped from the tables of a live database.
+    async fn import_database(&self, cli: &Cli, url: &str) -> Result<(), Box<dyn Error>> {
+        self.check_import_target(cli).await?;
+        // A bare path is a SQLite file
+        let url = match database_type_of(url) {
+            Some(_) => url.to_string(),
+            None if self.project_service.file_system.exists(url).await => {
+                format!("sqlite://{}", url)
+            }
+            None => {
+                return Err(format!(
+                    "'{}' is neither a database URL (sqlite://, postgres://, mysql://) nor a file",
+                    url
+                )
+                .into())
+            }
+        };
+        let database_type = database_type_of(&url).unwrap_or("sqlite");
+        let tables = self
+            .introspector
+            .tables(&url)
+            .await
+            .map_err(|e| format!("Failed to read the schema of {}: {}", url, e))?;
+        let import = import_tables(&tables, database_type, &url, &project_name(&url));
+        self.write_import(cli, &url, &import.config, &import.skipped)
+            .await
+    }
+
+    /// Writes a config mapped from the `CREATE TABLE` statements of an SQL dump.
+    async fn import_sql(
+        &self,
+        cli: &Cli,
+        file: &str,
+        database_type: Option<&str>,
+    ) -> Result<(), Box<dyn Error>> {
+        let file_system = &self.project_service.file_system;
+        self.check_import_target(cli).await?;
+        let content = file_system
+            .read_to_string(file)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", file, e))?;
+        let schema = parse_ddl(&content).map_err(|e| format!("{}: {}", file, e))?;
+        let database_type = database_type
+            .unwrap_or_else(|| ddl_database_type(&content))
+            .to_lowercase();
+        let database_url = match database_type.as_str() {
+            "sqlite" => "sqlite://database.db".to_string(),
+            _ => format!("{}://user:password@localhost/database_name", database_type),
+        };
+        let mut import = import_tables(
+            &schema.tables,
+            &database_type,
+            &database_url,
+            &project_name(file),
+        );
+        import.skipped.splice(0..0, schema.skipped);
+        self.write_import(cli, file, &import.config, &import.skipped)
+            .await
+    }
+
+    /// Adds a model or endpoint to the config in place, then generates the
+    /// files it needs if the project has been generated already.
+    async fn add(&self, cli: &Cli, item: &AddItem) -> Result<(), Box<dyn Error>> {
+        let file_system = &self.project_service.file_system;
+        let content = file_system
+            .read_to_string(&cli.config)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", cli.config, e))?;
+        let config = Self::parse_config(&cli.config, &content)?;
+        let (content, added, addition) = match item {
+            AddItem::Model {
+                name,
+                fields,
+                table,
+                crud,
+            } => {
+                if config.models.iter().any(|m| m.name == *name) {
+                    return Err(
+                        format!("'{}' already has a model named '{}'", cli.config, name).into(),
+                    );
+                }
+                let model = new_model(name, table.as_deref(), fields, *crud);
+                (
+                    config_toml_with_model(&content, &model),
+                    format!("model '{}'", name),
+                    Addition {
+                        model: name.clone(),
+                        new_table: true,
+                    },
+                )
+            }
+            AddItem::Endpoint {
+                model,
+                method,
+                path,
+            } => {
+                let Some(found) = config.models.iter().find(|m| m.name == *model) else {
+                    return Err(format!("'{}' has no model named '{}'", cli.config, model).into());
+                };
+                let endpoint = new_endpoint(found, method, path);
+                (
+                    config_toml_with_endpoint(&content, model, &endpoint)
+                        .map_err(|e| format!("Failed to update {}: {}", cli.config, e))?,
+                    format!("endpoint {} {}", endpoint.method, path),
+                    Addition {
+                        model: model.clone(),
+                        new_table: false,
+                    },
+                )
+            }
+        };
+        // Validated before anything is written, so a clash leaves the config as it was
+        let config = Self::parse_config(&cli.config, &content)?;
+        if !cli.dry_run {
+            file_system
+                .write_file(&cli.config, &content)
+                .await
+                .map_err(|e| format!("Failed to write {}: {}", cli.config, e))?;
+            if !cli.quiet {
+                println!("Added {} to {}.", added, cli.config);
+            }
+        }
+
+        let out_dir = Self::out_dir(cli, &config);
+        if !file_system.exists(&out_dir).await {
+            if !cli.quiet {
+                println!(
+                    "'{}' has not been generated yet; run `prkserver new` to generate it.",
+                    out_dir
+                );
+            }
+            return Ok(());
+        }
+        let Some(report) = self
+            .write_addition(cli, config, &out_dir, &addition)
+            .await?
+        else {
+            return Ok(());
+        };
+        if !cli.quiet {
+            for (path, outcome) in &report.files {
+                if matches!(outcome, FileOutcome::Written) {
+                    println!("  wrote {}/{}", out_dir, path);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn check_import_target(&self, cli: &Cli) -> Result<(), Box<dyn Error>> {
+        if !cli.force && self.project_service.file_system.exists(&cli.config).await {
+            return Err(
+                format!("'{}' already exists; pass --force to overwrite", cli.config).into(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Lists what couldn't be mapped from `source`, then writes the imported
+    /// config. A config that doesn't validate is only written with `--force`.
+    async fn write_import(
+        &self,
+        cli: &Cli,
+        source: &str,
+        config: &Config,
+        skipped: &[String],
+    ) -> Result<(), Box<dyn Error>> {
+        if !skipped.is_empty() {
+            eprintln!("Not imported from {}:", source);
+            for skipped in skipped {
+                eprintln!("  - {}", skipped);
+            }
+        }
+        let config_content = config_toml_from(config);
+        let config: Config = toml::from_str(&config_content)?;
+        let diagnostics = validate_config(&config);
+        if !diagnostics.is_empty() {
+            eprintln!(
+                "{}",
+                render_diagnostics(&cli.config, &config_content, &diagnostics)
+            );
+            if !cli.force {
+                return Err(format!(
+                    "{} was not written because the imported config is invalid; \
+                     pass --force to write it anyway and fix it by hand",
+                    cli.config
+                )
+                .into());
+            }
+        }
+        self.project_service
+            .file_system
+            .write_file(&cli.config, &config_content)
+            .await
+            .map_err(|e| format!("Failed to write {}: {}", cli.config, e))?;
+
+        if !cli.quiet {
+            let endpoints = config
+                .models
+                .iter()
+                .map(|m| m.endpoints.iter().flatten().count())
+                .sum::<usize>();
+            println!(
+                "Wrote {} with {} models and {} endpoints.",
+                cli.config,
+                config.models.len(),
+                endpoints
+            );
+        }
+        Ok(())
+    }
+
+    /// Applies the flags overriding config settings. `--templates` wins over
+    /// `templates_dir`, which is relative to the config file.
+    fn apply_overrides(cli: &Cli, config: &mut Config) {
+        config.templates_dir = match (&cli.templates, &config.templates_dir) {
+            (Some(dir), _) => Some(dir.clone()),
+            // Relative to the config file, so generating from elsewhere finds the same templates
+            (None, Some(dir)) => Some(
+                Path::new(&cli.config)
+                    .parent()
+                    .unwrap_or(Path::new(""))
+                    .join(dir)
+                    .to_string_lossy()
+                    .into_owned(),
             ),
-            Err(e) => eprintln!("Error creating project: {}", e),
+            (None, None) => None,
+        };
+        if let Some(policy) = cli.on_conflict {
+            config.on_conflict = Some(policy);
+        } else if cli.force {
+            config.on_conflict = Some(ConflictPolicy::Overwrite);
+        }
+    }
+
+    fn out_dir(cli: &Cli, config: &Config) -> String {
+        cli.out_dir
+            .clone()
+            .unwrap_or_else(|| config.project_name.clone())
+    }
+
+    async fn generate(
+        &self,
+        cli: &Cli,
+        config: Config,
+        out_dir: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let project_name = config.project_name.clone();
+        if self.write_project(cli, config, out_dir).await?.is_some() && !cli.quiet {
+            println!(
+                "Project '{}' created successfully.\n\ncd {}\ngit init\ncargo fmt",
+                project_name, out_dir
+            );
         }
+        Ok(())
     }
+
+    /// Generates the project after confirming destructive schema changes, and
+    /// returns what was written; with `--dry-run`, lists the files instead and
+    /// returns `None`.
+    async fn write_project(
+        &self,
+        cli: &Cli,
+        mut config: Config,
+        out_dir: &str,
+    ) -> Result<Option<GenerationReport>, Box<dyn Error>> {
+        Self::apply_overrides(cli, &mut config);
+        let changes = self
+            .project_service
+            .pending_schema_changes(&config, out_dir)
+            .await?;
+        if !cli.quiet && !changes.is_empty() {
+            println!("Schema changes:");
+            for change in &changes {
+                println!("  - {}", change);
+            }
+        }
+        if cli.dry_run {
+            self.dry_run(config, out_dir, None).await?;
+            return Ok(None);
+        }
+        let destructive = changes
+            .iter()
+            .filter(|change| change.is_destructive())
+            .collect::<Vec<_>>();
+        if !destructive.is_empty() && !cli.yes {
+            eprintln!("The following schema changes can lose data:");
+            for change in &destructive {
+                eprintln!("  - {}", change);
+            }
+            if !confirm("Generate a migration applying them?")? {
+                return Err("Aborted: destructive schema changes were not confirmed (pass --yes to apply them)".into());
+            }
+        }
+        let report = self
+            .project_service
+            .generate_project(config, out_dir)
+            .await
+            .map_err(|e| format!("Error creating project: {}", e))?;
+        Self::print_report(cli, out_dir, &report);
+        Ok(Some(report))
+    }
+
+    /// Generates the files `add` touched, or previews them with `--dry-run`.
+    async fn write_addition(
+        &self,
+        cli: &Cli,
+        mut config: Config,
+        out_dir: &str,
+        addition: &Addition,
+    ) -> Result<Option<GenerationReport>, Box<dyn Error>> {
+        Self::apply_overrides(cli, &mut config);
+        if cli.dry_run {
+            self.dry_run(config, out_dir, Some(addition)).await?;
+            return Ok(None);
+        }
+        let report = self
+            .project_service
+            .generate_addition(config, out_dir, addition)
+            .await
+            .map_err(|e| format!("Error creating project: {}", e))?;
+        Self::print_report(cli, out_dir, &report);
+        Ok(Some(report))
+    }
+
+    /// Lists the files that had been edited since they were generated.
+    fn print_report(cli: &Cli, out_dir: &str, report: &GenerationReport) {
+        for (path, outcome) in &report.files {
+            let path = format!("{}/{}", out_dir, path);
+            match outcome {
+                FileOutcome::Merged { conflicts: 0 } if !cli.quiet => {
+                    println!("Merged your changes to {} with the new output.", path)
+                }
+                FileOutcome::Merged { conflicts } if *conflicts > 0 => eprintln!(
+                    "{}: {} conflict(s) between your changes and the new output; resolve the <<<<<<< markers.",
+                    path, conflicts
+                ),
+                FileOutcome::WroteNew => eprintln!(
+                    "{} has local changes; wrote the new output to {}.new.",
+                    path, path
+                ),
+                FileOutcome::Skipped if !cli.quiet => {
+                    println!("{} has local changes; left it as it is.", path)
+                }
+                FileOutcome::Overwritten if !cli.quiet => {
+                    println!("Overwrote your changes to {}.", path)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Generates `config`, or only `addition`, into a `RecordingFileSystem`
+    /// over the real one, so nothing is written, and returns what would be
+    /// written under `out_dir`.
+    async fn preview(
+        &self,
+        config: Config,
+        out_dir: &str,
+        addition: Option<&Addition>,
+    ) -> Result<Vec<RecordedWrite>, Box<dyn Error>> {
+        let service =
+            ProjectService::new(RecordingFileSystem::new(&self.project_service.file_system));
+        match addition {
+            Some(addition) => service.generate_addition(config, out_dir, addition).await,
+            None => service.generate_project(config, out_dir).await,
+        }
+        .map_err(|e| format!("Error creating project: {}", e))?;
+        let writes = service.file_system.changes(out_dir).await;
+        // The manifest and merge bases under `.prkserver` are bookkeeping
+        Ok(writes
+            .into_iter()
+            .filter(|write| !write.path.starts_with(".prkserver/"))
+            .collect())
+    }
+
+    /// Lists every file generation would create, modify or leave unchanged.
+    async fn dry_run(
+        &self,
+        config: Config,
+        out_dir: &str,
+        addition: Option<&Addition>,
+    ) -> Result<(), Box<dyn Error>> {
+        let writes = self.preview(config, out_dir, addition).await?;
+        let (mut created, mut modified, mut unchanged) = (0, 0, 0);
+        println!(
+            "Dry run, nothing was written. Generating into '{}' would:",
+            out_dir
+        );
+        for write in &writes {
+            let action = match write.change {
+                FileChange::Created => {
+                    created += 1;
+                    "create"
+                }
+                FileChange::Modified { .. } => {
+                    modified += 1;
+                    "modify"
+                }
+                FileChange::Unchanged => {
+                    unchanged += 1;
+                    "keep"
+                }
+            };
+            println!("  {:<6} {}", action, write.path);
+        }
+        println!(
+            "{} created, {} modified, {} unchanged.",
+            created, modified, unchanged
+        );
+        Ok(())
+    }
+
+    /// Prints a unified diff for every file generation would create or modify.
+    async fn diff(&self, cli: &Cli, config: Config, out_dir: &str) -> Result<(), Box<dyn Error>> {
+        let mut changed = false;
+        for write in self.preview(config, out_dir, None).await? {
+            let (before, old_header) = match &write.change {
+                FileChange::Created => ("", "/dev/null".to_string()),
+                FileChange::Modified { before } => (before.as_str(), format!("a/{}", write.path)),
+                FileChange::Unchanged => continue,
+            };
+            changed = true;
+            print!(
+                "{}",
+                TextDiff::from_lines(before, &write.content)
+                    .unified_diff()
+                    .header(&old_header, &format!("b/{}", write.path))
+            );
+        }
+        if !changed && !cli.quiet {
+            println!("No changes.");
+        }
+        Ok(())
+    }
+}
+
+/// Asks a yes/no question on stdin; anything but `y`/`yes` (including EOF) is a no.
+fn confirm(question: &str) -> std::io::Result<bool> {
+    eprint!("{} [y/N] ", question);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
 }
+
+#[cfg(test)]
+mod tests;
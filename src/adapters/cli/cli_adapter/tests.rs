@@ -0,0 +1,123 @@
+//! Commands run against an in-memory file system, parsed from argument lists
+//! as the binary parses them.
+
+use super::CliAdapter;
+use crate::adapters::cli::commands::Cli;
+use crate::adapters::cli::init_wizard::run_wizard;
+use crate::application::services::project_service::ProjectService;
+use crate::domain::models::config::Config;
+use crate::domain::models::database_import::TableSchema;
+use crate::domain::ports::file_system::FileSystem;
+use crate::domain::ports::schema_introspector::SchemaIntrospector;
+use crate::domain::validation::config_validator::validate_config;
+use crate::infrastructure::file_system::in_memory_file_system::InMemoryFileSystem;
+use crate::output::init_config_toml;
+use clap::Parser;
+use std::error::Error;
+
+struct NoDatabase;
+
+impl SchemaIntrospector for NoDatabase {
+    async fn tables(&self, _database_url: &str) -> Result<Vec<TableSchema>, Box<dyn Error>> {
+        Err("no database in tests".into())
+    }
+}
+
+fn adapter() -> CliAdapter<InMemoryFileSystem, NoDatabase> {
+    CliAdapter::new(ProjectService::new(InMemoryFileSystem::new()), NoDatabase)
+}
+
+async fn run(
+    adapter: &CliAdapter<InMemoryFileSystem, NoDatabase>,
+    args: &[&str],
+) -> Result<(), Box<dyn Error>> {
+    let args = ["prkserver", "--quiet", "--config", "config.toml"]
+        .iter()
+        .chain(args);
+    adapter.run(Cli::parse_from(args)).await
+}
+
+/// `author_id` is text while `authors.id` is an integer.
+const MISMATCHED_FOREIGN_KEY: &str = "\
+CREATE TABLE authors (id INTEGER PRIMARY KEY, name TEXT NOT NULL);
+CREATE TABLE books (id INTEGER PRIMARY KEY, author_id TEXT REFERENCES authors(id));
+";
+
+#[tokio::test]
+async fn importing_an_invalid_config_fails_without_writing_it() {
+    let adapter = adapter();
+    let file_system = &adapter.project_service.file_system;
+    file_system
+        .write_file("schema.sql", MISMATCHED_FOREIGN_KEY)
+        .await
+        .unwrap();
+
+    let error = run(&adapter, &["import", "sql", "schema.sql"])
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("--force"), "{}", error);
+    assert!(!file_system.exists("config.toml").await);
+
+    run(&adapter, &["--force", "import", "sql", "schema.sql"])
+        .await
+        .unwrap();
+    let written = file_system.read_to_string("config.toml").await.unwrap();
+    assert!(written.contains("name = \"Book\""), "{}", written);
+}
+
+#[tokio::test]
+async fn init_with_yes_writes_the_wizard_defaults() {
+    let adapter = adapter();
+    run(&adapter, &["--yes", "init"]).await.unwrap();
+
+    let file_system = &adapter.project_service.file_system;
+    let written = file_system.read_to_string("config.toml").await.unwrap();
+    let defaults = run_wizard(std::io::empty(), std::io::sink()).unwrap();
+    assert_eq!(written, init_config_toml(&defaults));
+    let config: Config = toml::from_str(&written).unwrap();
+    assert!(validate_config(&config).is_empty(), "{}", written);
+
+    let error = run(&adapter, &["--yes", "init"]).await.unwrap_err();
+    assert!(error.to_string().contains("already exists"), "{}", error);
+}
+
+#[tokio::test]
+async fn adding_a_model_with_dry_run_writes_nothing() {
+    let adapter = adapter();
+    run(&adapter, &["--yes", "init"]).await.unwrap();
+    run(&adapter, &["generate"]).await.unwrap();
+    let file_system = &adapter.project_service.file_system;
+    let generated = file_system.files();
+    assert!(generated.keys().any(|path| path.ends_with("src/main.rs")));
+
+    run(
+        &adapter,
+        &[
+            "--dry-run",
+            "add",
+            "model",
+            "Publisher",
+            "--field",
+            "name:String",
+            "--crud",
+        ],
+    )
+    .await
+    .unwrap();
+    assert_eq!(file_system.files(), generated);
+
+    run(
+        &adapter,
+        &[
+            "add",
+            "model",
+            "Publisher",
+            "--field",
+            "name:String",
+            "--crud",
+        ],
+    )
+    .await
+    .unwrap();
+    assert_ne!(file_system.files(), generated);
+}
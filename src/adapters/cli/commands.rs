@@ -0,0 +1,122 @@
+use crate::domain::models::config::{ConflictPolicy, Field};
+use crate::domain::models::scaffold::parse_field;
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+#[command(name = "prkserver", version, about)]
+pub struct Cli {
+    /// Path to the project configuration file
+    #[arg(short, long, global = true, default_value = "./config.toml")]
+    pub config: String,
+
+    /// Directory to write the generated project into (defaults to `project_name`)
+    #[arg(short, long, global = true)]
+    pub out_dir: Option<String>,
+
+    /// Overwrite existing files and directories, and write imported configs that don't validate
+    #[arg(short, long, global = true)]
+    pub force: bool,
+
+    /// Answer yes to every confirmation prompt, including destructive schema changes
+    #[arg(short, long, global = true)]
+    pub yes: bool,
+
+    /// Directory of `*.tmpl` files overriding the built-in templates (overrides `templates_dir`)
+    #[arg(long, global = true)]
+    pub templates: Option<String>,
+
+    /// What to do with generated files edited since: merge, new, skip or overwrite
+    /// (overrides `on_conflict`; `--force` means overwrite)
+    #[arg(long, global = true, value_parser = str::parse::<ConflictPolicy>)]
+    pub on_conflict: Option<ConflictPolicy>,
+
+    /// List the files `new`, `generate` or `add` would create or modify, without writing anything
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Only print errors
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Create a new project, refusing to touch an existing directory without `--force`
+    New {
+        /// Project name, overriding `project_name` from the config
+        name: Option<String>,
+    },
+    /// Generate (or regenerate) the project described by the config
+    Generate,
+    /// Print unified diffs between the generated project on disk and what `generate` would write
+    Diff,
+    /// Check the config without writing any files
+    Validate,
+    /// Write a config file, asking for the project settings, models and fields
+    /// (`--yes` takes every default without asking)
+    Init,
+    /// Print the OpenAPI document for the config without generating anything
+    Openapi,
+    /// Write a config file describing an existing API or database
+    Import {
+        #[command(subcommand)]
+        source: ImportSource,
+    },
+    /// Add a model or an endpoint to the config and generate the files it needs
+    Add {
+        #[command(subcommand)]
+        item: AddItem,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AddItem {
+    /// Add a model, e.g. `add model Order --field total:f64 --field user_id:i32 --crud`
+    Model {
+        /// Model name, in PascalCase
+        name: String,
+        /// A field as `name:type`, repeated for each field; `id: i32` is added unless there is an `id`
+        #[arg(long = "field", value_parser = parse_field)]
+        fields: Vec<Field>,
+        /// Table name (defaults to the snake case plural of the model name)
+        #[arg(long)]
+        table: Option<String>,
+        /// Add list, create, get, update and delete endpoints
+        #[arg(long)]
+        crud: bool,
+    },
+    /// Add an endpoint to a model, e.g. `add endpoint Order GET /orders/{id}`
+    Endpoint {
+        /// Name of the model the endpoint serves
+        model: String,
+        /// HTTP method
+        method: String,
+        /// Path, with `{name}` placeholders for path parameters
+        path: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ImportSource {
+    /// Map the schemas and paths of an OpenAPI 3 document (YAML or JSON) to models and endpoints
+    Openapi {
+        /// Path of the OpenAPI document
+        file: String,
+    },
+    /// Map the tables of a live database to models with CRUD endpoints
+    Database {
+        /// Database URL (`sqlite://`, `postgres://` or `mysql://`), or the path of a SQLite file
+        url: String,
+    },
+    /// Map the `CREATE TABLE` statements of an SQL dump to models with CRUD endpoints
+    Sql {
+        /// Path of the SQL file
+        file: String,
+        /// Database the dump comes from (postgres, mysql, sqlite); guessed from its syntax by default
+        #[arg(long)]
+        database_type: Option<String>,
+    },
+}
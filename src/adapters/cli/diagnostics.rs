@@ -0,0 +1,68 @@
+use toml_edit::{ImDocument, Item};
+
+use crate::domain::validation::diagnostic::{Diagnostic, PathSegment};
+
+/// Formats a TOML syntax or deserialization error as `file:line:column: error: message`.
+pub fn render_parse_error(config_path: &str, source: &str, error: &toml::de::Error) -> String {
+    let location = error
+        .span()
+        .map(|span| line_col(source, span.start))
+        .map(|(line, column)| format!("{}:{}:{}", config_path, line, column))
+        .unwrap_or_else(|| config_path.to_string());
+    format!("{}: error: {}", location, error.message().trim_end())
+}
+
+/// Formats every diagnostic with the TOML location of the value it points at.
+pub fn render_diagnostics(config_path: &str, source: &str, diagnostics: &[Diagnostic]) -> String {
+    let document = ImDocument::parse(source).ok();
+    let mut lines = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let location = document
+                .as_ref()
+                .and_then(|document| locate(document.as_item(), &diagnostic.path))
+                .map(|offset| line_col(source, offset))
+                .map(|(line, column)| format!("{}:{}:{}", config_path, line, column))
+                .unwrap_or_else(|| config_path.to_string());
+            format!("{}: error: {}", location, diagnostic)
+        })
+        .collect::<Vec<_>>();
+    lines.push(format!(
+        "{} problem(s) found in {}",
+        diagnostics.len(),
+        config_path
+    ));
+    lines.join("\n")
+}
+
+/// Walks `path` as far as it exists in the document and returns the start of
+/// the deepest item that has a span.
+fn locate(root: &Item, path: &[PathSegment]) -> Option<usize> {
+    let mut item = root;
+    let mut offset = None;
+    for segment in path {
+        let next = match segment {
+            PathSegment::Key(key) => item.get(key.as_str()),
+            PathSegment::Index(index) => item.get(*index),
+        };
+        match next {
+            Some(next) => {
+                item = next;
+                offset = item.span().map(|span| span.start).or(offset);
+            }
+            None => break,
+        }
+    }
+    offset
+}
+
+/// Converts a byte offset into a 1-based line and column.
+fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map_or(before.len(), |newline| before.len() - newline - 1)
+        + 1;
+    (line, column)
+}
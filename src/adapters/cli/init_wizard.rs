@@ -0,0 +1,154 @@
+use crate::domain::models::config::{Config, Framework};
+use crate::domain::models::scaffold::{new_model, parse_field, table_name_for};
+use crate::domain::validation::config_validator::{
+    crate_name_error, validate_config, SUPPORTED_DATABASE_TYPES,
+};
+use convert_case::{Case, Casing};
+use std::io::{self, BufRead, Write};
+
+/// Asks questions on `output` and reads the answers from `input`, one per line.
+/// An empty answer, or the end of the input, takes the default.
+struct Prompter<R, W> {
+    input: R,
+    output: W,
+    at_end: bool,
+}
+
+impl<R: BufRead, W: Write> Prompter<R, W> {
+    fn ask(&mut self, question: &str, default: &str) -> io::Result<String> {
+        match default {
+            "" => write!(self.output, "{}: ", question)?,
+            default => write!(self.output, "{} [{}]: ", question, default)?,
+        }
+        self.output.flush()?;
+        let mut answer = String::new();
+        if self.input.read_line(&mut answer)? == 0 {
+            self.at_end = true;
+        }
+        Ok(match answer.trim() {
+            "" => default.to_string(),
+            answer => answer.to_string(),
+        })
+    }
+
+    /// Asks until the answer is one of `choices`, ignoring case.
+    fn choose(&mut self, question: &str, choices: &[&str], default: &str) -> io::Result<String> {
+        let question = format!("{} ({})", question, choices.join(", "));
+        loop {
+            let answer = self.ask(&question, default)?.to_lowercase();
+            if choices.contains(&answer.as_str()) {
+                return Ok(answer);
+            }
+            writeln!(self.output, "Expected one of: {}", choices.join(", "))?;
+        }
+    }
+
+    fn confirm(&mut self, question: &str, default: bool) -> io::Result<bool> {
+        let hint = if default { "Y/n" } else { "y/N" };
+        Ok(match self.ask(question, hint)?.to_lowercase().as_str() {
+            "y" | "yes" => true,
+            "n" | "no" => false,
+            _ => default,
+        })
+    }
+}
+
+/// Walks through the project settings and then its models and fields, and
+/// returns the config they describe. Each model is validated as it is added.
+pub fn run_wizard<R: BufRead, W: Write>(input: R, output: W) -> io::Result<Config> {
+    let mut prompter = Prompter {
+        input,
+        output,
+        at_end: false,
+    };
+    // Asked again rather than mangled into something the user didn't type
+    let project_name = loop {
+        let name = prompter.ask("Project name", "backend_project")?;
+        match crate_name_error(&name) {
+            Some(error) => writeln!(prompter.output, "{}", error)?,
+            None => break name,
+        }
+    };
+    let framework = match prompter
+        .choose("Framework", &["axum", "actix"], "axum")?
+        .as_str()
+    {
+        "actix" => Framework::ActixWeb,
+        _ => Framework::Axum,
+    };
+    let database_type = prompter.choose("Database type", SUPPORTED_DATABASE_TYPES, "postgres")?;
+    let default_url = match database_type.as_str() {
+        "sqlite" => format!("sqlite://{}.db", project_name),
+        _ => format!(
+            "{}://user:password@localhost/{}",
+            database_type, project_name
+        ),
+    };
+    let database_url = prompter.ask("Database URL", &default_url)?;
+    let mut config = Config {
+        project_name,
+        database_url,
+        database_type,
+        models: Vec::new(),
+        middlewares: None,
+        framework,
+        auth: None,
+        api_keys: None,
+        openapi: None,
+        templates_dir: None,
+        on_conflict: None,
+    };
+
+    loop {
+        // The project needs one model at least, so the first question has a default
+        let name = if config.models.is_empty() {
+            prompter.ask("Model name", "User")?
+        } else {
+            prompter.ask("Another model name (empty to finish)", "")?
+        };
+        if name.is_empty() {
+            return Ok(config);
+        }
+        let name = name.to_case(Case::Pascal);
+        let table_name = prompter.ask("Table name", &table_name_for(&name))?;
+        writeln!(
+            prompter.output,
+            "Fields of {} as name:type, e.g. title:String; `id: i32` is added unless there is an id (empty to finish)",
+            name
+        )?;
+        let mut fields = Vec::new();
+        loop {
+            let spec = prompter.ask("Field", "")?;
+            if spec.is_empty() {
+                break;
+            }
+            match parse_field(&spec) {
+                Ok(field) => fields.push(field),
+                Err(e) => writeln!(prompter.output, "{}", e)?,
+            }
+        }
+        let crud = prompter.confirm("Add CRUD endpoints?", true)?;
+
+        config
+            .models
+            .push(new_model(&name, Some(&table_name), &fields, crud));
+        let diagnostics = validate_config(&config);
+        if !diagnostics.is_empty() {
+            config.models.pop();
+            writeln!(prompter.output, "{} was not added:", name)?;
+            for diagnostic in diagnostics {
+                writeln!(prompter.output, "  - {}", diagnostic.message)?;
+            }
+            // Asking again would only get the same defaults
+            if prompter.at_end {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} is not a valid model", name),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -1 +1,4 @@
 pub mod cli_adapter;
+pub mod commands;
+pub mod diagnostics;
+pub mod init_wizard;
@@ -1,13 +1,45 @@
-use crate::domain::models::config::Config;
+use crate::domain::models::config::{
+    resolve_primary_key, Config, ConflictPolicy, Field, Middleware, Model,
+};
+use crate::domain::models::manifest::{
+    carry_user_regions, content_hash, FileOutcome, GenerationReport, Manifest,
+};
+use crate::domain::models::merge::merge3;
+use crate::domain::models::scaffold::Addition;
+use crate::domain::models::schema::{SchemaChange, SchemaSnapshot};
 use crate::domain::models::template::Template;
 use crate::domain::ports::file_system::FileSystem;
 use crate::domain::ports::project_generator::ProjectGenerator;
 use crate::output::{
-    cargo_toml_content, compose_yaml_content, database_connection_content, docker_ignore_content,
-    dockerfile_content, env_content, git_ignore_content, main_content,
+    auth_context, auth_handlers_context, create_table_context, existing_jwt_secret, jwt_secret,
+    openapi_context, schema_migration_context, ColumnView, TemplateEngine,
 };
+use chrono::{Duration, NaiveDateTime, Timelike, Utc};
+use minijinja::{context, Value};
 use std::error::Error;
 
+/// Where the schema of the last generation is recorded, relative to the project root.
+const SCHEMA_SNAPSHOT_PATH: &str = ".prkserver/schema.json";
+
+/// Version prefix of `migrations/{version}_{name}.sql`.
+const MIGRATION_VERSION_FORMAT: &str = "%Y%m%d%H%M%S";
+
+/// Where the hashes of the generated files are recorded, relative to the project root.
+const MANIFEST_PATH: &str = ".prkserver/manifest.json";
+
+/// Where a copy of each generated file is kept as the base for three-way merges.
+const GENERATED_COPIES_DIR: &str = ".prkserver/generated";
+
+/// State shared by the writes of one generation.
+struct Generation {
+    engine: TemplateEngine,
+    on_conflict: ConflictPolicy,
+    /// The manifest of the previous generation.
+    previous: Manifest,
+    manifest: Manifest,
+    report: GenerationReport,
+}
+
 pub struct ProjectService<F: FileSystem> {
     pub file_system: F,
 }
@@ -20,12 +52,12 @@ impl<F: FileSystem> ProjectService<F> {
     /// Creates a file at the specified path within the project directory.
     async fn create_file(
         &self,
-        project_name: &str,
+        out_dir: &str,
         file_path: &str,
         content: &str,
     ) -> Result<(), Box<dyn Error>> {
         // Construct the full path (e.g., "my_project/src/domain/mod.rs")
-        let path = format!("{}/{}", project_name, file_path);
+        let path = format!("{}/{}", out_dir, file_path);
 
         // Get the parent directory (e.g., "my_project/src/domain")
         if let Some(parent) = std::path::Path::new(&path).parent() {
@@ -40,133 +72,340 @@ impl<F: FileSystem> ProjectService<F> {
         Ok(())
     }
 
-    /// Generates the content for a `mod.rs` file by declaring public modules.
-    fn generate_mod_rs_content(&self, file_names: &[String]) -> String {
-        file_names
-            .iter()
-            .map(|name| format!("pub mod {};\npub use {}::*;\n\n", name, name))
-            .collect()
+    /// Renders `template` with `context` into a file of the project directory.
+    async fn render_file(
+        &self,
+        generation: &mut Generation,
+        out_dir: &str,
+        file_path: &str,
+        template: &str,
+        context: Value,
+    ) -> Result<(), Box<dyn Error>> {
+        let content = generation
+            .engine
+            .render(template, context)
+            .map_err(|e| format!("{} ({}): {}", template, file_path, e))?;
+        self.write_generated(generation, out_dir, file_path, &content)
+            .await
     }
 
-    /// Writes a `mod.rs` file to the specified directory with dynamic module declarations.
-    async fn generate_mod_rs(
+    /// Writes a generated file unless it was edited since the last generation.
+    /// Protected regions always keep their current content; other edits are
+    /// handled as `generation.on_conflict` says.
+    async fn write_generated(
         &self,
-        project_name: &str,
-        dir: &str,
-        file_names: &[String],
+        generation: &mut Generation,
+        out_dir: &str,
+        file_path: &str,
+        content: &str,
     ) -> Result<(), Box<dyn Error>> {
-        let relative_path = format!("src/{}/mod.rs", dir);
-        let content = self.generate_mod_rs_content(file_names);
-        self.create_file(project_name, &relative_path, &content)
-            .await?;
+        let file_path = file_path
+            .split('/')
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("/");
+        let path = format!("{}/{}", out_dir, file_path);
+        let existing = if self.file_system.exists(&path).await {
+            Some(self.file_system.read_to_string(&path).await?)
+        } else {
+            None
+        };
+        let content = match &existing {
+            Some(existing) => carry_user_regions(content, existing),
+            None => content.to_string(),
+        };
+        // Without a recorded hash there is no telling whether a differing file was edited
+        let previous_hash = generation.previous.files.get(&file_path);
+        let edited =
+            |existing: &str| previous_hash.is_none_or(|hash| content_hash(existing) != *hash);
+
+        let outcome = match &existing {
+            None => FileOutcome::Written,
+            Some(existing) if *existing == content => FileOutcome::Unchanged,
+            Some(existing) if !edited(existing) => FileOutcome::Written,
+            Some(existing) => match generation.on_conflict {
+                ConflictPolicy::Overwrite => FileOutcome::Overwritten,
+                ConflictPolicy::Skip => FileOutcome::Skipped,
+                ConflictPolicy::New => FileOutcome::WroteNew,
+                ConflictPolicy::Merge => {
+                    let base_path = format!("{}/{}/{}", out_dir, GENERATED_COPIES_DIR, file_path);
+                    if self.file_system.exists(&base_path).await {
+                        let base = self.file_system.read_to_string(&base_path).await?;
+                        let merge = merge3(&base, existing, &content);
+                        if merge.content == *existing {
+                            // Edits merged earlier, with nothing new to add
+                            FileOutcome::Unchanged
+                        } else {
+                            self.create_file(out_dir, &file_path, &merge.content)
+                                .await?;
+                            FileOutcome::Merged {
+                                conflicts: merge.conflicts,
+                            }
+                        }
+                    } else {
+                        FileOutcome::WroteNew
+                    }
+                }
+            },
+        };
+
+        match outcome {
+            FileOutcome::Written | FileOutcome::Overwritten => {
+                self.create_file(out_dir, &file_path, &content).await?;
+            }
+            FileOutcome::WroteNew => {
+                self.create_file(out_dir, &format!("{}.new", file_path), &content)
+                    .await?;
+            }
+            // Written too, so a recording lists it
+            FileOutcome::Unchanged => {
+                self.create_file(out_dir, &file_path, existing.as_deref().unwrap())
+                    .await?;
+            }
+            FileOutcome::Merged { .. } | FileOutcome::Skipped => {}
+        }
+        match outcome {
+            // The edited file stays as it was, so it still counts as edited next time
+            FileOutcome::WroteNew | FileOutcome::Skipped => {
+                if let Some(hash) = previous_hash {
+                    generation
+                        .manifest
+                        .files
+                        .insert(file_path.clone(), hash.clone());
+                }
+            }
+            _ => {
+                generation
+                    .manifest
+                    .files
+                    .insert(file_path.clone(), content_hash(&content));
+                self.create_file(
+                    out_dir,
+                    &format!("{}/{}", GENERATED_COPIES_DIR, file_path),
+                    &content,
+                )
+                .await?;
+            }
+        }
+        generation.report.files.push((file_path, outcome));
         Ok(())
     }
-}
 
-impl<F: FileSystem> ProjectGenerator for ProjectService<F> {
-    /// Generates a complete Rust project based on the provided configuration.
-    async fn generate_project(&self, config: Config) -> Result<(), Box<dyn Error>> {
-        let template = Template::new(config.clone());
+    /// Reads the manifest of the last generation into `out_dir`, if any.
+    async fn read_manifest(&self, out_dir: &str) -> Result<Manifest, Box<dyn Error>> {
+        let path = format!("{}/{}", out_dir, MANIFEST_PATH);
+        if !self.file_system.exists(&path).await {
+            return Ok(Manifest::default());
+        }
+        let content = self.file_system.read_to_string(&path).await?;
+        Ok(serde_json::from_str(&content)?)
+    }
 
-        // ### Create Directory Structure
-        self.file_system
-            .create_dir_all(&config.project_name)
-            .await?;
-        self.file_system
-            .create_dir_all(&format!("{}/src", config.project_name))
-            .await?;
-        self.file_system
-            .create_dir_all(&format!("{}/src/domain", config.project_name))
-            .await?;
-        self.file_system
-            .create_dir_all(&format!("{}/src/domain/models", config.project_name))
-            .await?;
-        self.file_system
-            .create_dir_all(&format!("{}/src/domain/ports", config.project_name))
-            .await?;
-        self.file_system
-            .create_dir_all(&format!("{}/src/application", config.project_name))
-            .await?;
-        self.file_system
-            .create_dir_all(&format!("{}/src/application/services", config.project_name))
-            .await?;
-        self.file_system
-            .create_dir_all(&format!("{}/src/infrastructure", config.project_name))
-            .await?;
-        self.file_system
-            .create_dir_all(&format!(
-                "{}/src/infrastructure/repositories",
-                config.project_name
-            ))
-            .await?;
-        self.file_system
-            .create_dir_all(&format!("{}/src/adapters", config.project_name))
-            .await?;
-        self.file_system
-            .create_dir_all(&format!("{}/src/adapters/http", config.project_name))
-            .await?;
+    /// The template engine for `config`, with the `*.tmpl` files of its
+    /// `templates_dir` replacing the built-in templates of the same name.
+    async fn load_templates(&self, config: &Config) -> Result<TemplateEngine, Box<dyn Error>> {
+        let mut engine = TemplateEngine::new(config);
+        let Some(dir) = &config.templates_dir else {
+            return Ok(engine);
+        };
+        if !self.file_system.exists(dir).await {
+            return Err(format!("templates directory '{}' does not exist", dir).into());
+        }
+        let names = self
+            .file_system
+            .read_dir(dir)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", dir, e))?;
+        for name in names.into_iter().filter(|name| name.ends_with(".tmpl")) {
+            let path = format!("{}/{}", dir, name);
+            let source = self
+                .file_system
+                .read_to_string(&path)
+                .await
+                .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+            engine
+                .override_template(&name, source)
+                .map_err(|e| format!("{}: {}", path, e))?;
+        }
+        Ok(engine)
+    }
+
+    /// Reads the schema recorded by the last generation into `out_dir`, if any.
+    async fn read_schema_snapshot(
+        &self,
+        out_dir: &str,
+    ) -> Result<Option<SchemaSnapshot>, Box<dyn Error>> {
+        let path = format!("{}/{}", out_dir, SCHEMA_SNAPSHOT_PATH);
+        if !self.file_system.exists(&path).await {
+            return Ok(None);
+        }
+        let content = self.file_system.read_to_string(&path).await?;
+        let mut snapshot: SchemaSnapshot = serde_json::from_str(&content)?;
+        // Snapshots from before `primary_key` existed rely on the implicit `id` key
+        for table in &mut snapshot.tables {
+            table.fields = resolve_primary_key(&table.fields);
+        }
+        Ok(Some(snapshot))
+    }
+
+    /// Schema changes between the last generation into `out_dir` and `config`.
+    /// Empty when the project has not been generated with a schema snapshot yet.
+    pub async fn pending_schema_changes(
+        &self,
+        config: &Config,
+        out_dir: &str,
+    ) -> Result<Vec<SchemaChange>, Box<dyn Error>> {
+        let database_type = &config.database_type;
+        Ok(match self.read_schema_snapshot(out_dir).await? {
+            Some(snapshot) => snapshot.diff(config, |old, new| {
+                // Compare definitions under the new name so a rename alone isn't an alter.
+                let old = Field {
+                    name: new.name.clone(),
+                    column: new.column.clone(),
+                    ..old.clone()
+                };
+                ColumnView::new(&old, database_type) != ColumnView::new(new, database_type)
+                    || old.is_indexed() != new.is_indexed()
+                    || old.references != new.references
+            }),
+            None => Vec::new(),
+        })
+    }
+
+    /// Writes `CREATE TABLE` migrations on first generation and incremental
+    /// migrations afterwards, then records the new schema snapshot.
+    async fn generate_migrations(
+        &self,
+        generation: &mut Generation,
+        config: &Config,
+        out_dir: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let target = SchemaSnapshot::from_config(config);
+        let first_version = self.next_migration_version(out_dir).await?;
+        let version = first_version.format(MIGRATION_VERSION_FORMAT);
+        let has_snapshot = self.read_schema_snapshot(out_dir).await?.is_some();
+
+        if has_snapshot {
+            let changes = self.pending_schema_changes(config, out_dir).await?;
+            if !changes.is_empty() {
+                let name = match changes.as_slice() {
+                    [change] => change.slug(),
+                    _ => "update_schema".to_string(),
+                };
+                self.render_file(
+                    generation,
+                    out_dir,
+                    &format!("migrations/{}_{}.sql", version, name),
+                    "migration.sql.tmpl",
+                    schema_migration_context(&changes, &target, &config.database_type),
+                )
+                .await?;
+            }
+        } else if !self
+            .file_system
+            .exists(&format!("{}/migrations", out_dir))
+            .await
+        {
+            for (index, table) in target.tables.iter().enumerate() {
+                let version = first_version + Duration::seconds(index as i64);
+                let migration_path = format!(
+                    "migrations/{}_create_{}.sql",
+                    version.format(MIGRATION_VERSION_FORMAT),
+                    table.table_name
+                );
+                self.render_file(
+                    generation,
+                    out_dir,
+                    &migration_path,
+                    "create_table.sql.tmpl",
+                    create_table_context(&table.table_name, &table.fields, &config.database_type),
+                )
+                .await?;
+            }
+        }
+        // A project generated before snapshots existed keeps its migrations;
+        // the current config becomes the baseline for future diffs.
 
-        // ### Generate Static Files
-        self.create_file(
-            &config.project_name,
-            "Cargo.toml",
-            &cargo_toml_content(
-                &config.project_name,
-                &config.database_type,
-                false,
-                &config.framework,
-            ),
-        )
-        .await?;
-        self.create_file(&config.project_name, "src/main.rs", &main_content(&config))
-            .await?;
-        self.create_file(
-            &config.project_name,
-            "src/database_connection.rs",
-            &database_connection_content(&config.database_type),
-        )
-        .await?;
-        self.create_file(
-            &config.project_name,
-            ".env",
-            &env_content(&config.database_url),
-        )
-        .await?;
-        self.create_file(
-            &config.project_name,
-            "Dockerfile",
-            &dockerfile_content(&config.project_name),
-        )
-        .await?;
-        self.create_file(
-            &config.project_name,
-            ".dockerignore",
-            &docker_ignore_content(),
-        )
-        .await?;
-        self.create_file(&config.project_name, ".gitignore", &git_ignore_content())
-            .await?;
         self.create_file(
-            &config.project_name,
-            "compose.yaml",
-            &compose_yaml_content(&config.database_type),
+            out_dir,
+            SCHEMA_SNAPSHOT_PATH,
+            &serde_json::to_string_pretty(&target)?,
         )
         .await?;
+        Ok(())
+    }
 
-        // ### Generate `mod.rs` Files
-        // **Adapters**
-        self.generate_mod_rs(
-            &config.project_name,
-            "adapters/http",
-            &vec!["http".to_string()],
+    /// The version for a new migration: now, or one second after the latest
+    /// migration in `migrations/` when the clock isn't ahead of it, since sqlx
+    /// needs every version to be unique and increasing.
+    async fn next_migration_version(&self, out_dir: &str) -> Result<NaiveDateTime, Box<dyn Error>> {
+        let now = Utc::now()
+            .naive_utc()
+            .with_nanosecond(0)
+            .unwrap_or_default();
+        let migrations_dir = format!("{}/migrations", out_dir);
+        if !self.file_system.exists(&migrations_dir).await {
+            return Ok(now);
+        }
+        let latest = self
+            .file_system
+            .read_dir(&migrations_dir)
+            .await?
+            .iter()
+            .filter_map(|name| name.split_once('_'))
+            .filter_map(|(version, _)| {
+                NaiveDateTime::parse_from_str(version, MIGRATION_VERSION_FORMAT).ok()
+            })
+            .max();
+        Ok(match latest {
+            Some(latest) if latest >= now => latest + Duration::seconds(1),
+            _ => now,
+        })
+    }
+
+    /// Starts a generation into `out_dir` with an empty manifest.
+    async fn start_generation(
+        &self,
+        config: &Config,
+        out_dir: &str,
+    ) -> Result<Generation, Box<dyn Error>> {
+        Ok(Generation {
+            engine: self.load_templates(config).await?,
+            on_conflict: config.on_conflict.unwrap_or_default(),
+            previous: self.read_manifest(out_dir).await?,
+            manifest: Manifest::default(),
+            report: GenerationReport::default(),
+        })
+    }
+
+    /// Records the manifest of `generation` and returns its report.
+    async fn finish_generation(
+        &self,
+        generation: Generation,
+        out_dir: &str,
+    ) -> Result<GenerationReport, Box<dyn Error>> {
+        self.create_file(
+            out_dir,
+            MANIFEST_PATH,
+            &serde_json::to_string_pretty(&generation.manifest)?,
         )
         .await?;
+        Ok(generation.report)
+    }
 
+    /// Writes the `mod.rs` file of every generated directory.
+    async fn generate_mod_files(
+        &self,
+        generation: &mut Generation,
+        config: &Config,
+        out_dir: &str,
+    ) -> Result<(), Box<dyn Error>> {
         // **Domain**
         self.generate_mod_rs(
-            &config.project_name,
+            generation,
+            out_dir,
             "domain",
-            &vec![
+            &[
                 "models".to_string(),
                 "ports".to_string(),
                 "error".to_string(),
@@ -179,8 +418,9 @@ impl<F: FileSystem> ProjectGenerator for ProjectService<F> {
             .models
             .iter()
             .map(|m| m.name.to_lowercase())
+            .chain(["page".to_string()])
             .collect();
-        self.generate_mod_rs(&config.project_name, "domain/models", &model_files)
+        self.generate_mod_rs(generation, out_dir, "domain/models", &model_files)
             .await?;
 
         // **Domain/Ports**
@@ -189,16 +429,18 @@ impl<F: FileSystem> ProjectGenerator for ProjectService<F> {
             .iter()
             .map(|m| format!("{}_repository", m.name.to_lowercase()))
             .collect();
-        self.generate_mod_rs(&config.project_name, "domain/ports", &port_files)
+        self.generate_mod_rs(generation, out_dir, "domain/ports", &port_files)
             .await?;
 
         // **Application**
-        self.generate_mod_rs(
-            &config.project_name,
-            "application",
-            &vec!["services".to_string()],
-        )
-        .await?;
+        let application_files: Vec<String> = ["services"]
+            .into_iter()
+            .chain(config.auth.as_ref().map(|_| "auth"))
+            .chain(config.api_keys.as_ref().map(|_| "api_keys"))
+            .map(str::to_string)
+            .collect();
+        self.generate_mod_rs(generation, out_dir, "application", &application_files)
+            .await?;
 
         // **Application/Services**
         let service_files: Vec<String> = config
@@ -206,14 +448,15 @@ impl<F: FileSystem> ProjectGenerator for ProjectService<F> {
             .iter()
             .map(|m| format!("{}_service", m.name.to_lowercase()))
             .collect();
-        self.generate_mod_rs(&config.project_name, "application/services", &service_files)
+        self.generate_mod_rs(generation, out_dir, "application/services", &service_files)
             .await?;
 
         // **Infrastructure**
         self.generate_mod_rs(
-            &config.project_name,
+            generation,
+            out_dir,
             "infrastructure",
-            &vec!["repositories".to_string()],
+            &["repositories".to_string()],
         )
         .await?;
 
@@ -224,7 +467,8 @@ impl<F: FileSystem> ProjectGenerator for ProjectService<F> {
             .map(|m| format!("sqlx_{}_repository", m.name.to_lowercase()))
             .collect();
         self.generate_mod_rs(
-            &config.project_name,
+            generation,
+            out_dir,
             "infrastructure/repositories",
             &repo_files,
         )
@@ -235,98 +479,397 @@ impl<F: FileSystem> ProjectGenerator for ProjectService<F> {
         let middles_files: Vec<String> = config
             .middlewares
             .iter()
-            .map(|m| {
-                m.iter()
-                    .map(|m| format!("{}_middleware", m.model.to_lowercase()))
-            })
-            .flatten()
+            .flat_map(|m| m.iter().map(Middleware::function_name))
             .collect();
-        let http_files = [&http_files[..], &middles_files[..]].concat();
-        self.generate_mod_rs(&config.project_name, "adapters/", &vec!["http".to_string()])
+        let auth_files: Vec<String> = config
+            .auth
+            .iter()
+            .map(|_| "auth".to_string())
+            .chain(config.api_keys.iter().map(|_| "api_keys".to_string()))
+            .chain(config.serves_openapi().then(|| "openapi".to_string()))
+            .collect();
+        let http_files = [&http_files[..], &middles_files[..], &auth_files[..]].concat();
+        self.generate_mod_rs(generation, out_dir, "adapters/", &["http".to_string()])
             .await?;
-        self.generate_mod_rs(&config.project_name, "adapters/http", &http_files)
+        self.generate_mod_rs(generation, out_dir, "adapters/http", &http_files)
             .await?;
+        Ok(())
+    }
 
-        // ### Generate Dynamic Files
-        for model in &config.models {
-            // **Model**
-            let model_path = format!("src/domain/models/{}.rs", model.name.to_lowercase());
-            self.create_file(
-                &config.project_name,
-                &model_path,
-                &template.generate_model_content(model),
+    /// Writes the model, repository port, repository and service of `model`.
+    async fn generate_model_files(
+        &self,
+        generation: &mut Generation,
+        template: &Template,
+        model: &Model,
+        out_dir: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        // **Model**
+        let model_path = format!("src/domain/models/{}.rs", model.name.to_lowercase());
+        self.render_file(
+            generation,
+            out_dir,
+            &model_path,
+            "model.rs.tmpl",
+            template.model_context(model),
+        )
+        .await?;
+
+        // **Repository Trait**
+        let repo_trait_path = format!(
+            "src/domain/ports/{}_repository.rs",
+            model.name.to_lowercase()
+        );
+        self.render_file(
+            generation,
+            out_dir,
+            &repo_trait_path,
+            "repository_trait.rs.tmpl",
+            template.repository_trait_context(model),
+        )
+        .await?;
+
+        // **Repository Implementation**
+        let repo_impl_path = format!(
+            "src/infrastructure/repositories/sqlx_{}_repository.rs",
+            model.name.to_lowercase()
+        );
+        self.render_file(
+            generation,
+            out_dir,
+            &repo_impl_path,
+            "repository_impl.rs.tmpl",
+            template.repository_impl_context(model, &template.config.database_type),
+        )
+        .await?;
+
+        // **Service**
+        let service_path = format!(
+            "src/application/services/{}_service.rs",
+            model.name.to_lowercase()
+        );
+        self.render_file(
+            generation,
+            out_dir,
+            &service_path,
+            "service.rs.tmpl",
+            template.service_context(model),
+        )
+        .await
+    }
+
+    /// Writes `openapi.yaml`, and the handler serving it when enabled.
+    async fn generate_openapi(
+        &self,
+        generation: &mut Generation,
+        config: &Config,
+        out_dir: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        self.render_file(
+            generation,
+            out_dir,
+            "openapi.yaml",
+            "openapi.yaml.tmpl",
+            openapi_context(config),
+        )
+        .await?;
+        if config.serves_openapi() {
+            self.render_file(
+                generation,
+                out_dir,
+                "src/adapters/http/openapi.rs",
+                "openapi.rs.tmpl",
+                openapi_context(config),
             )
             .await?;
+        }
+        Ok(())
+    }
 
-            // **Repository Trait**
-            let repo_trait_path = format!(
-                "src/domain/ports/{}_repository.rs",
-                model.name.to_lowercase()
-            );
+    /// Writes a `CREATE TABLE` migration for `table_name` alone and adds the
+    /// table to the schema snapshot, leaving other pending schema changes to
+    /// the next generation.
+    async fn generate_table_migration(
+        &self,
+        generation: &mut Generation,
+        config: &Config,
+        table_name: &str,
+        out_dir: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let target = SchemaSnapshot::from_config(config);
+        let table = target
+            .table(table_name)
+            .ok_or_else(|| format!("table '{}' not found", table_name))?;
+        let version = self.next_migration_version(out_dir).await?;
+        self.render_file(
+            generation,
+            out_dir,
+            &format!(
+                "migrations/{}_create_{}.sql",
+                version.format(MIGRATION_VERSION_FORMAT),
+                table_name
+            ),
+            "create_table.sql.tmpl",
+            create_table_context(table_name, &table.fields, &config.database_type),
+        )
+        .await?;
+        // Without a snapshot the next generation takes the whole config as its baseline
+        if let Some(mut snapshot) = self.read_schema_snapshot(out_dir).await? {
+            snapshot.tables.retain(|t| t.table_name != table_name);
+            snapshot.tables.push(table.clone());
             self.create_file(
-                &config.project_name,
-                &repo_trait_path,
-                &template.generate_repository_trait(model),
+                out_dir,
+                SCHEMA_SNAPSHOT_PATH,
+                &serde_json::to_string_pretty(&snapshot)?,
             )
             .await?;
+        }
+        Ok(())
+    }
 
-            // **Repository Implementation**
-            let repo_impl_path = format!(
-                "src/infrastructure/repositories/sqlx_{}_repository.rs",
-                model.name.to_lowercase()
-            );
-            self.create_file(
-                &config.project_name,
-                &repo_impl_path,
-                &template.generate_repository_impl(model, &config.database_type),
-            )
+    /// Generates only what `add` changed: the files of `addition.model`, the
+    /// `mod.rs` files, `main.rs` and `http.rs` wiring its service, and the
+    /// OpenAPI document, plus the table of a new model. Everything else, including the `.env` and other pending
+    /// schema changes, is left for `generate`.
+    pub async fn generate_addition(
+        &self,
+        config: Config,
+        out_dir: &str,
+        addition: &Addition,
+    ) -> Result<GenerationReport, Box<dyn Error>> {
+        let model = config
+            .models
+            .iter()
+            .find(|m| m.name == addition.model)
+            .ok_or_else(|| format!("model '{}' not found", addition.model))?;
+        let template = Template::new(config.clone());
+        let mut generation = self.start_generation(&config, out_dir).await?;
+        // Files left alone keep their recorded hashes
+        generation.manifest = self.read_manifest(out_dir).await?;
+
+        if addition.new_table {
+            self.generate_table_migration(&mut generation, &config, &model.table_name, out_dir)
+                .await?;
+        }
+        self.generate_mod_files(&mut generation, &config, out_dir)
+            .await?;
+        self.generate_model_files(&mut generation, &template, model, out_dir)
+            .await?;
+        self.render_file(
+            &mut generation,
+            out_dir,
+            "src/main.rs",
+            "main.rs.tmpl",
+            context! {},
+        )
+        .await?;
+        self.render_file(
+            &mut generation,
+            out_dir,
+            "src/adapters/http/http.rs",
+            "http.rs.tmpl",
+            template.http_context(),
+        )
+        .await?;
+        self.generate_openapi(&mut generation, &config, out_dir)
             .await?;
+        self.finish_generation(generation, out_dir).await
+    }
 
-            // **Service**
-            let service_path = format!(
-                "src/application/services/{}_service.rs",
-                model.name.to_lowercase()
-            );
-            self.create_file(
-                &config.project_name,
-                &service_path,
-                &template.generate_service(model),
+    /// Writes a `mod.rs` file to the specified directory with dynamic module declarations.
+    async fn generate_mod_rs(
+        &self,
+        generation: &mut Generation,
+        out_dir: &str,
+        dir: &str,
+        file_names: &[String],
+    ) -> Result<(), Box<dyn Error>> {
+        let relative_path = format!("src/{}/mod.rs", dir);
+        self.render_file(
+            generation,
+            out_dir,
+            &relative_path,
+            "mod.rs.tmpl",
+            context! { modules => file_names },
+        )
+        .await
+    }
+}
+
+impl<F: FileSystem> ProjectGenerator for ProjectService<F> {
+    /// Generates a complete Rust project based on the provided configuration.
+    async fn generate_project(
+        &self,
+        config: Config,
+        out_dir: &str,
+    ) -> Result<GenerationReport, Box<dyn Error>> {
+        let template = Template::new(config.clone());
+        let mut generation = self.start_generation(&config, out_dir).await?;
+
+        // ### Create Directory Structure
+        self.file_system.create_dir_all(out_dir).await?;
+        self.file_system
+            .create_dir_all(&format!("{}/src", out_dir))
+            .await?;
+        self.file_system
+            .create_dir_all(&format!("{}/src/domain", out_dir))
+            .await?;
+        self.file_system
+            .create_dir_all(&format!("{}/src/domain/models", out_dir))
+            .await?;
+        self.file_system
+            .create_dir_all(&format!("{}/src/domain/ports", out_dir))
+            .await?;
+        self.file_system
+            .create_dir_all(&format!("{}/src/application", out_dir))
+            .await?;
+        self.file_system
+            .create_dir_all(&format!("{}/src/application/services", out_dir))
+            .await?;
+        self.file_system
+            .create_dir_all(&format!("{}/src/infrastructure", out_dir))
+            .await?;
+        self.file_system
+            .create_dir_all(&format!("{}/src/infrastructure/repositories", out_dir))
+            .await?;
+        self.file_system
+            .create_dir_all(&format!("{}/src/adapters", out_dir))
+            .await?;
+        self.file_system
+            .create_dir_all(&format!("{}/src/adapters/http", out_dir))
+            .await?;
+
+        // ### Generate Static Files
+        let static_files = [
+            ("Cargo.toml", "Cargo.toml.tmpl"),
+            ("src/main.rs", "main.rs.tmpl"),
+            ("src/database_connection.rs", "database_connection.rs.tmpl"),
+            ("Dockerfile", "Dockerfile.tmpl"),
+            (".dockerignore", "dockerignore.tmpl"),
+            (".gitignore", "gitignore.tmpl"),
+            ("compose.yaml", "compose.yaml.tmpl"),
+            ("src/domain/models/page.rs", "page.rs.tmpl"),
+            ("src/domain/error.rs", "error.rs.tmpl"),
+        ];
+        for (file_path, template_name) in static_files {
+            self.render_file(
+                &mut generation,
+                out_dir,
+                file_path,
+                template_name,
+                context! {},
             )
             .await?;
         }
+        let env_path = format!("{}/.env", out_dir);
+        // Reused, so regenerating doesn't invalidate tokens already issued
+        let existing = if self.file_system.exists(&env_path).await {
+            existing_jwt_secret(&self.file_system.read_to_string(&env_path).await?)
+        } else {
+            None
+        };
+        let jwt_secret = existing.unwrap_or_else(jwt_secret);
+        self.render_file(
+            &mut generation,
+            out_dir,
+            ".env",
+            "env.tmpl",
+            context! { jwt_secret },
+        )
+        .await?;
+
+        // ### Generate Migrations
+        self.generate_migrations(&mut generation, &config, out_dir)
+            .await?;
+
+        // ### Generate `mod.rs` Files
+        self.generate_mod_files(&mut generation, &config, out_dir)
+            .await?;
+
+        // ### Generate Dynamic Files
+        for model in &config.models {
+            self.generate_model_files(&mut generation, &template, model, out_dir)
+                .await?;
+        }
 
         // **HTTP Server File**
-        self.create_file(
-            &config.project_name,
+        self.render_file(
+            &mut generation,
+            out_dir,
             "src/adapters/http/http.rs",
-            &template.generate_http_content(),
+            "http.rs.tmpl",
+            template.http_context(),
         )
         .await?;
 
         // ### Generate Middleware (if present)
         if let Some(middlewares) = &config.middlewares {
             for middleware in middlewares {
-                let middleware_path = format!(
-                    "src/adapters/http/{}_middleware.rs",
-                    middleware.model.to_lowercase()
-                );
-                self.create_file(
-                    &config.project_name,
+                let middleware_path =
+                    format!("src/adapters/http/{}.rs", middleware.function_name());
+                self.render_file(
+                    &mut generation,
+                    out_dir,
                     &middleware_path,
-                    &template.generate_middleware_content(middleware, &config.database_type),
+                    "middleware.rs.tmpl",
+                    template.middleware_context(middleware),
                 )
                 .await?;
             }
         }
 
-        // ### Generate Error Handling
-        self.create_file(
-            &config.project_name,
-            "src/domain/error.rs",
-            &template.generate_error_content(),
-        )
-        .await?;
+        // ### Generate Authentication (if present)
+        if let Some(auth) = &config.auth {
+            let user_model = config
+                .models
+                .iter()
+                .find(|m| m.name == auth.user_model)
+                .ok_or_else(|| format!("auth user model '{}' not found", auth.user_model))?;
+            self.render_file(
+                &mut generation,
+                out_dir,
+                "src/application/auth.rs",
+                "auth.rs.tmpl",
+                auth_context(auth),
+            )
+            .await?;
+            self.render_file(
+                &mut generation,
+                out_dir,
+                "src/adapters/http/auth.rs",
+                "auth_handlers.rs.tmpl",
+                auth_handlers_context(auth, user_model),
+            )
+            .await?;
+        }
 
-        Ok(())
+        // ### Generate API Keys (if present)
+        if config.api_keys.is_some() {
+            self.render_file(
+                &mut generation,
+                out_dir,
+                "src/application/api_keys.rs",
+                "api_keys.rs.tmpl",
+                context! {},
+            )
+            .await?;
+            self.render_file(
+                &mut generation,
+                out_dir,
+                "src/adapters/http/api_keys.rs",
+                "api_key_handlers.rs.tmpl",
+                context! {},
+            )
+            .await?;
+        }
+
+        // ### Generate OpenAPI Document
+        self.generate_openapi(&mut generation, &config, out_dir)
+            .await?;
+
+        self.finish_generation(generation, out_dir).await
     }
 }
+
+#[cfg(test)]
+mod tests;
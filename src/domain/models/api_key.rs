@@ -0,0 +1,75 @@
+use crate::domain::models::config::{Config, Endpoint, Field, HeaderCheck, Middleware, Model};
+
+/// Model `[api_keys]` adds; its middleware is `ApiKeyMiddleware`.
+pub const API_KEY_MODEL: &str = "ApiKey";
+pub const API_KEY_TABLE: &str = "api_keys";
+/// Admin routes: `POST` issues a key, `GET` lists keys and `DELETE /{id}` revokes one.
+pub const API_KEY_PATH: &str = "/api_keys";
+
+/// Copies `config` with the `ApiKey` model, its admin endpoints and the
+/// middleware checking the key header. Running it on an already resolved
+/// config changes nothing.
+pub fn resolve_api_keys(config: &Config) -> Config {
+    let mut resolved = config.clone();
+    let Some(api_keys) = &config.api_keys else {
+        return resolved;
+    };
+    if config.models.iter().any(|m| m.name == API_KEY_MODEL) {
+        return resolved;
+    }
+
+    let field = |name: &str, field_type: &str| Field {
+        name: name.to_string(),
+        field_type: field_type.to_string(),
+        ..Field::default()
+    };
+    // Only the hash is stored; the prefix tells keys apart in listings
+    let fields = vec![
+        field("id", "i64"),
+        field("name", "String"),
+        field("prefix", "String"),
+        Field {
+            hidden: Some(true),
+            unique: Some(true),
+            ..field("key_hash", "String")
+        },
+    ];
+    let admin = |method: &str, path: String, path_params: Option<Vec<Field>>| Endpoint {
+        method: method.to_string(),
+        path,
+        auth: Some(true),
+        roles: api_keys.admin_roles.clone(),
+        path_params,
+        ..Endpoint::default()
+    };
+    resolved.models.push(Model {
+        name: API_KEY_MODEL.to_string(),
+        table_name: API_KEY_TABLE.to_string(),
+        fields,
+        endpoints: Some(vec![
+            admin("GET", API_KEY_PATH.to_string(), None),
+            admin(
+                "DELETE",
+                format!("{}/{{id}}", API_KEY_PATH),
+                Some(vec![field("id", "i64")]),
+            ),
+        ]),
+        ..Model::default()
+    });
+    resolved
+        .middlewares
+        .get_or_insert_with(Vec::new)
+        .push(Middleware {
+            model: API_KEY_MODEL.to_string(),
+            select_from_model: None,
+            validate_header: Some(vec![HeaderCheck {
+                model_field: "key_hash".to_string(),
+                header_key: api_keys.header().to_string(),
+                hashed: true,
+            }]),
+        });
+    resolved
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,78 @@
+use super::{resolve_api_keys, API_KEY_MODEL};
+use crate::domain::models::config::Config;
+
+fn config(api_keys: &str) -> Config {
+    toml::from_str(&format!(
+        r#"
+project_name = "billing"
+database_url = "postgres://localhost/billing"
+database_type = "postgres"
+framework = "Axum"
+{api_keys}
+
+[[models]]
+name = "Invoice"
+table_name = "invoices"
+fields = [{{ name = "id", type = "i32" }}]
+endpoints = [{{ method = "GET", path = "/invoices", middlewares = ["ApiKeyMiddleware"] }}]
+"#
+    ))
+    .unwrap()
+}
+
+#[test]
+fn configs_without_api_keys_are_left_alone() {
+    let resolved = resolve_api_keys(&config(""));
+    assert_eq!(resolved.models.len(), 1);
+    assert!(resolved.middlewares.is_none());
+}
+
+#[test]
+fn api_keys_add_a_model_with_admin_endpoints() {
+    let resolved = resolve_api_keys(&config("[api_keys]\nadmin_roles = [\"admin\"]"));
+    let model = resolved
+        .models
+        .iter()
+        .find(|m| m.name == API_KEY_MODEL)
+        .unwrap();
+    assert_eq!(model.table_name, "api_keys");
+    let key_hash = model.fields.iter().find(|f| f.name == "key_hash").unwrap();
+    assert!(key_hash.is_hidden() && key_hash.is_unique());
+
+    let endpoints = model.endpoints.as_ref().unwrap();
+    let routes: Vec<_> = endpoints
+        .iter()
+        .map(|e| (e.method.as_str(), e.path.as_str()))
+        .collect();
+    assert_eq!(routes, [("GET", "/api_keys"), ("DELETE", "/api_keys/{id}")]);
+    for endpoint in endpoints {
+        assert!(endpoint.requires_auth());
+        assert_eq!(endpoint.roles, Some(vec!["admin".to_string()]));
+    }
+}
+
+#[test]
+fn the_middleware_checks_the_hash_of_the_key_header() {
+    for (api_keys, header) in [
+        ("[api_keys]", "X-API-Key"),
+        ("[api_keys]\nheader = \"X-Service-Key\"", "X-Service-Key"),
+    ] {
+        let resolved = resolve_api_keys(&config(api_keys));
+        let middlewares = resolved.middlewares.as_ref().unwrap();
+        assert_eq!(middlewares.len(), 1);
+        assert_eq!(middlewares[0].function_name(), "apikey_middleware");
+        let checks = middlewares[0].header_checks();
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].model_field, "key_hash");
+        assert_eq!(checks[0].header_key, header);
+        assert!(checks[0].hashed);
+    }
+}
+
+#[test]
+fn resolving_twice_changes_nothing() {
+    let resolved = resolve_api_keys(&config("[api_keys]"));
+    let again = resolve_api_keys(&resolved);
+    assert_eq!(again.models.len(), resolved.models.len());
+    assert_eq!(again.middlewares.as_ref().unwrap().len(), 1);
+}
@@ -1,6 +1,8 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Clone)]
+use crate::domain::models::relation::Relation;
+
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Config {
     pub project_name: String,
     pub database_url: String,
@@ -8,41 +10,342 @@ pub struct Config {
     pub models: Vec<Model>,
     pub middlewares: Option<Vec<Middleware>>,
     pub framework: Framework,
+    pub auth: Option<Auth>,
+    pub api_keys: Option<ApiKeys>,
+    pub openapi: Option<OpenApi>,
+    /// Directory of templates overriding the built-in ones, see `output::template_engine`.
+    pub templates_dir: Option<String>,
+    /// What regeneration does with files changed since they were generated.
+    pub on_conflict: Option<ConflictPolicy>,
+}
+
+/// `on_conflict`: how regeneration treats a file edited outside its
+/// `prkserver:begin-user` / `prkserver:end-user` regions.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    /// Three-way merge the edits with the new output, marking conflicting lines.
+    #[default]
+    Merge,
+    /// Leave the file alone and write the new output next to it as `{file}.new`.
+    New,
+    /// Leave the file alone.
+    Skip,
+    /// Replace the file, dropping the edits.
+    Overwrite,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+impl std::str::FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "merge" => Ok(Self::Merge),
+            "new" => Ok(Self::New),
+            "skip" => Ok(Self::Skip),
+            "overwrite" => Ok(Self::Overwrite),
+            _ => Err(format!(
+                "unknown conflict policy '{}', expected merge, new, skip or overwrite",
+                value
+            )),
+        }
+    }
+}
+
+/// `[auth]`: JWT login against a user model.
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Auth {
+    /// Model users log in as.
+    pub user_model: String,
+    /// Field matched against the login name; defaults to `email`.
+    pub username_field: Option<String>,
+    /// Field holding the argon2 password hash; defaults to `password_hash`.
+    pub password_field: Option<String>,
+    /// Access token lifetime in seconds; defaults to 15 minutes.
+    pub token_ttl: Option<u64>,
+    /// Refresh token lifetime in seconds; defaults to 7 days.
+    pub refresh_token_ttl: Option<u64>,
+    /// Field holding the user's role, checked against an endpoint's `roles`.
+    pub role_field: Option<String>,
+}
+
+/// `[api_keys]`: static keys for service-to-service callers, checked by `ApiKeyMiddleware`.
+#[derive(Deserialize, Serialize, Clone)]
+pub struct ApiKeys {
+    /// Header carrying the key; defaults to `X-API-Key`.
+    pub header: Option<String>,
+    /// Roles allowed to issue, list and revoke keys; any logged-in user when unset.
+    pub admin_roles: Option<Vec<String>>,
+}
+
+/// `[openapi]`: how the generated `openapi.yaml` is exposed.
+#[derive(Deserialize, Serialize, Clone)]
+pub struct OpenApi {
+    /// Serve the document at `/api/openapi.json` and a Swagger UI at `/api/docs`.
+    pub serve: Option<bool>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
 pub enum Framework {
     Axum,
     ActixWeb,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, Default)]
 pub struct Model {
     pub name: String,
     pub table_name: String,
+    /// Previous table name, so schema diffs emit a rename instead of drop + create.
+    pub renamed_from: Option<String>,
+    /// Generate a `{Model}Response` DTO and return it from handlers instead of the model.
+    pub response_dto: Option<bool>,
+    /// Largest `limit` list endpoints accept; defaults to 100.
+    pub max_page_size: Option<i64>,
     pub fields: Vec<Field>,
     pub endpoints: Option<Vec<Endpoint>>,
+    pub relations: Option<Vec<Relation>>,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Default)]
 pub struct Field {
     pub name: String,
     #[serde(rename = "type")]
     pub field_type: String,
+    /// Previous column name, so schema diffs emit a rename instead of drop + add.
+    #[serde(default, skip_serializing)]
+    pub renamed_from: Option<String>,
+    /// Never serialized into responses, e.g. password hashes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hidden: Option<bool>,
+    /// Marks the primary key. Without one, the field named `id` is the key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_key: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unique: Option<bool>,
+    /// Column default, e.g. `default = 0` or `default = { sql = "CURRENT_TIMESTAMP" }`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<DefaultValue>,
+    /// Create an index on the column.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<bool>,
+    /// Column name, when it differs from the field name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+    /// Let the database assign the value; defaults to true for `i32`/`i64` primary keys.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_increment: Option<bool>,
+    /// Maximum length of a `String` column, stored as `VARCHAR(max_length)`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<u32>,
+    /// Foreign key, usually filled in from the model's `relations`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub references: Option<ForeignKey>,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct ForeignKey {
+    pub table: String,
+    pub column: String,
+}
+
+/// A column default. Strings, numbers and booleans are literals; `{ sql = "..." }`
+/// is copied into the migration as-is.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum DefaultValue {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    Text(String),
+    Sql { sql: String },
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
 pub struct Endpoint {
     pub method: String,
     pub path: String,
     pub middlewares: Option<Vec<String>>,
+    /// Require a valid access token, see `[auth]`.
+    pub auth: Option<bool>,
+    /// Only callers with one of these roles may use the endpoint; implies `auth`.
+    pub roles: Option<Vec<String>>,
+    /// Field holding the owning user's id; only the owner may use the endpoint
+    /// on a row. Implies `auth`.
+    pub owner_field: Option<String>,
     pub path_params: Option<Vec<Field>>,
     pub body_params: Option<Vec<Field>>,
     pub query_params: Option<Vec<Field>>,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Middleware {
     pub model: String,
-    // Add additional fields as needed
+    /// Model whose rows the headers are matched against; defaults to `model`.
+    pub select_from_model: Option<String>,
+    /// Headers that must match a row, e.g. `[{ model_field = "token", header_key = "token" }]`.
+    pub validate_header: Option<Vec<HeaderCheck>>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct HeaderCheck {
+    pub model_field: String,
+    pub header_key: String,
+    /// Match the SHA-256 of the header instead of the header itself; set for API keys.
+    #[serde(skip_deserializing)]
+    pub hashed: bool,
+}
+
+impl Model {
+    /// Fields with the primary key resolved, see `resolve_primary_key`.
+    pub fn columns(&self) -> Vec<Field> {
+        resolve_primary_key(&self.fields)
+    }
+
+    pub fn primary_key(&self) -> Option<Field> {
+        self.columns().into_iter().find(Field::is_primary_key)
+    }
+
+    /// `body_params` of the first endpoint with the given HTTP method that declares them.
+    pub fn body_params_for(&self, method: &str) -> Option<&[Field]> {
+        self.endpoints
+            .iter()
+            .flatten()
+            .filter(|e| e.method.eq_ignore_ascii_case(method))
+            .find_map(|e| e.body_params.as_deref())
+    }
+}
+
+impl Auth {
+    pub fn username_field(&self) -> &str {
+        self.username_field.as_deref().unwrap_or("email")
+    }
+
+    pub fn password_field(&self) -> &str {
+        self.password_field.as_deref().unwrap_or("password_hash")
+    }
+
+    pub fn token_ttl(&self) -> u64 {
+        self.token_ttl.unwrap_or(15 * 60)
+    }
+
+    pub fn refresh_token_ttl(&self) -> u64 {
+        self.refresh_token_ttl.unwrap_or(7 * 24 * 60 * 60)
+    }
+}
+
+impl ApiKeys {
+    pub fn header(&self) -> &str {
+        self.header.as_deref().unwrap_or("X-API-Key")
+    }
+}
+
+impl Config {
+    pub fn serves_openapi(&self) -> bool {
+        self.openapi
+            .as_ref()
+            .is_some_and(|openapi| openapi.serve.unwrap_or(false))
+    }
+}
+
+impl Middleware {
+    /// Name of the generated middleware function and module, e.g. `user_middleware`.
+    pub fn function_name(&self) -> String {
+        format!("{}_middleware", self.model.to_lowercase())
+    }
+
+    pub fn source_model(&self) -> &str {
+        self.select_from_model.as_deref().unwrap_or(&self.model)
+    }
+
+    pub fn header_checks(&self) -> &[HeaderCheck] {
+        self.validate_header.as_deref().unwrap_or_default()
+    }
+}
+
+impl Field {
+    pub fn is_hidden(&self) -> bool {
+        self.hidden.unwrap_or(false)
+    }
+
+    /// Whether the field is explicitly marked `primary_key = true`; see `resolve_primary_key`
+    /// for the implicit `id` key.
+    pub fn is_primary_key(&self) -> bool {
+        self.primary_key.unwrap_or(false)
+    }
+
+    pub fn is_unique(&self) -> bool {
+        self.unique.unwrap_or(false)
+    }
+
+    pub fn is_indexed(&self) -> bool {
+        self.index.unwrap_or(false)
+    }
+
+    pub fn is_auto_increment(&self) -> bool {
+        self.auto_increment.unwrap_or_else(|| {
+            self.is_primary_key()
+                && self.default.is_none()
+                && matches!(self.field_type.trim(), "i32" | "i64")
+        })
+    }
+
+    /// Database column name: `column` if set, otherwise the field name.
+    pub fn column_name(&self) -> &str {
+        self.column.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// Copies `fields`, marking the field named `id` as the primary key when none is
+/// marked explicitly.
+pub fn resolve_primary_key(fields: &[Field]) -> Vec<Field> {
+    let mut fields = fields.to_vec();
+    if !fields.iter().any(Field::is_primary_key) {
+        if let Some(id) = fields.iter_mut().find(|f| f.name == "id") {
+            id.primary_key = Some(true);
+        }
+    }
+    fields
+}
+
+impl Endpoint {
+    /// Name of the generated handler function, e.g. `get_todos_by_id` for `GET /todos/{id}`.
+    pub fn handler_name(&self) -> String {
+        format!(
+            "{}{}",
+            self.method.to_lowercase(),
+            self.path
+                .replace(['/', '-', '.'], "_")
+                .replace(":", "")
+                .replace("{", "by_")
+                .replace("}", "")
+        )
+    }
+
+    pub fn requires_auth(&self) -> bool {
+        self.auth.unwrap_or(false) || self.roles.is_some() || self.owner_field.is_some()
+    }
+
+    /// Generated middleware functions the endpoint is wrapped in, e.g.
+    /// `user_middleware` for `UserMiddleware`; see `Middleware::function_name`.
+    pub fn middleware_functions(&self) -> Vec<String> {
+        self.middlewares
+            .iter()
+            .flatten()
+            .map(|name| name.to_lowercase().replace("middleware", "_middleware"))
+            .collect()
+    }
+
+    /// Names of the `{param}` / `:param` placeholders in the endpoint path.
+    pub fn path_placeholders(&self) -> Vec<String> {
+        self.path
+            .split('/')
+            .filter_map(|segment| {
+                segment
+                    .strip_prefix('{')
+                    .and_then(|s| s.strip_suffix('}'))
+                    .or_else(|| segment.strip_prefix(':'))
+            })
+            .map(str::to_string)
+            .collect()
+    }
 }
@@ -0,0 +1,510 @@
+use convert_case::{Case, Casing};
+
+use crate::domain::models::config::{
+    Config, DefaultValue, Endpoint, Field, ForeignKey, Framework, Model,
+};
+use crate::domain::models::keywords::RUST_KEYWORDS;
+use crate::domain::models::relation::{Relation, RelationKind};
+use crate::domain::models::template::update_request_fields;
+use crate::domain::validation::config_validator::crate_name_error;
+use crate::output::is_integer_type;
+
+/// A table as read from a live database or a DDL dump.
+#[derive(Clone, Debug, Default)]
+pub struct TableSchema {
+    pub name: String,
+    pub columns: Vec<ColumnSchema>,
+    pub primary_key: Vec<String>,
+    pub foreign_keys: Vec<ForeignKeySchema>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ColumnSchema {
+    pub name: String,
+    /// Declared type as the database spells it, e.g. `character varying(80)`.
+    pub sql_type: String,
+    pub nullable: bool,
+    /// Default as an SQL expression, e.g. `'draft'` or `CURRENT_TIMESTAMP`.
+    pub default: Option<String>,
+    pub auto_increment: bool,
+    pub unique: bool,
+    pub indexed: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ForeignKeySchema {
+    pub columns: Vec<String>,
+    pub table: String,
+    /// Empty when the constraint points at the primary key of `table`.
+    pub referenced_columns: Vec<String>,
+}
+
+impl TableSchema {
+    pub fn column_mut(&mut self, name: &str) -> Option<&mut ColumnSchema> {
+        self.columns.iter_mut().find(|c| c.name == name)
+    }
+}
+
+/// A config built from database tables, with what couldn't be carried over.
+pub struct DatabaseImport {
+    pub config: Config,
+    /// One human-readable line per construct that was skipped or changed.
+    pub skipped: Vec<String>,
+}
+
+/// Maps every table with a single-column primary key to a model with
+/// `GET`/`POST`/`PUT`/`DELETE` endpoints, and foreign keys to `belongs_to`
+/// relations.
+pub fn import_tables(
+    tables: &[TableSchema],
+    database_type: &str,
+    database_url: &str,
+    project_name: &str,
+) -> DatabaseImport {
+    let mut skipped = Vec::new();
+    let mut models: Vec<(Model, &TableSchema)> = Vec::new();
+    for table in tables {
+        let context = format!("table '{}'", table.name);
+        let key = match table.primary_key.as_slice() {
+            [key] => key,
+            [] => {
+                skipped.push(format!("{}: no primary key", context));
+                continue;
+            }
+            keys => {
+                skipped.push(format!(
+                    "{}: composite primary key ({}) is not supported",
+                    context,
+                    keys.join(", ")
+                ));
+                continue;
+            }
+        };
+        let name = model_name(&table.name);
+        if let Some((other, _)) = models.iter().find(|(m, _)| m.name == name) {
+            skipped.push(format!(
+                "{}: model name '{}' is already used by table '{}'",
+                context, name, other.table_name
+            ));
+            continue;
+        }
+        let mut fields = Vec::new();
+        for column in &table.columns {
+            let context = format!("column '{}.{}'", table.name, column.name);
+            if let Some(field) = field(
+                &context,
+                column,
+                column.name == *key,
+                database_type,
+                &mut skipped,
+            ) {
+                fields.push(field);
+            }
+        }
+        let Some(key_field) = fields.iter().find(|f| f.column_name() == key.as_str()) else {
+            skipped.push(format!(
+                "{}: primary key '{}' could not be imported",
+                context, key
+            ));
+            continue;
+        };
+        // The key goes first, as in hand-written configs
+        let key_index = fields
+            .iter()
+            .position(|f| f.name == key_field.name)
+            .unwrap_or(0);
+        let key_field = fields.remove(key_index);
+        fields.insert(0, key_field);
+
+        models.push((
+            Model {
+                name,
+                table_name: table.name.clone(),
+                fields,
+                ..Model::default()
+            },
+            table,
+        ));
+    }
+
+    let targets = models
+        .iter()
+        .map(|(m, t)| (t.name.clone(), m.name.clone(), t.primary_key[0].clone()))
+        .collect::<Vec<_>>();
+    for (model, table) in &mut models {
+        // In column order, once per column even when declared twice
+        let mut foreign_keys = table.foreign_keys.iter().collect::<Vec<_>>();
+        foreign_keys.sort_by_key(|fk| {
+            let column = fk.columns.first();
+            table.columns.iter().position(|c| Some(&c.name) == column)
+        });
+        foreign_keys.dedup_by(|a, b| a.columns == b.columns && a.table == b.table);
+        for foreign_key in foreign_keys {
+            let context = format!(
+                "table '{}' foreign key ({})",
+                table.name,
+                foreign_key.columns.join(", ")
+            );
+            let [column] = foreign_key.columns.as_slice() else {
+                skipped.push(format!(
+                    "{}: composite foreign keys are not supported",
+                    context
+                ));
+                continue;
+            };
+            let Some(field) = model
+                .fields
+                .iter_mut()
+                .find(|f| f.column_name() == column.as_str())
+            else {
+                continue;
+            };
+            let target = targets.iter().find(|(t, _, _)| *t == foreign_key.table);
+            let referenced = foreign_key.referenced_columns.first();
+            match target {
+                // A relation when it points at the primary key of an imported table
+                Some((_, target, key)) if referenced.is_none_or(|r| r == key) => {
+                    let default_name = target.to_case(Case::Snake);
+                    // `author_id` → `author`; the include name can't shadow a field
+                    let name = match field.name.strip_suffix("_id") {
+                        Some(name) => name.to_string(),
+                        None => default_name.clone(),
+                    };
+                    let foreign_key = field.name.clone();
+                    let name = if model.fields.iter().any(|f| f.name == name) {
+                        format!("{}_{}", foreign_key, default_name)
+                    } else {
+                        name
+                    };
+                    model.relations.get_or_insert_with(Vec::new).push(Relation {
+                        kind: RelationKind::BelongsTo,
+                        model: target.clone(),
+                        name: (name != default_name).then_some(name),
+                        foreign_key: (foreign_key != format!("{}_id", default_name))
+                            .then_some(foreign_key),
+                        join_table: None,
+                        nested_route: None,
+                    });
+                }
+                Some(_) => {
+                    field.references = Some(ForeignKey {
+                        table: foreign_key.table.clone(),
+                        column: referenced.cloned().unwrap_or_default(),
+                    });
+                }
+                None => skipped.push(format!(
+                    "{}: references '{}', which is not imported",
+                    context, foreign_key.table
+                )),
+            }
+        }
+        model.endpoints = Some(crud_endpoints(model));
+    }
+
+    DatabaseImport {
+        config: Config {
+            project_name: project_name.to_string(),
+            database_url: database_url.to_string(),
+            database_type: database_type.to_string(),
+            models: models.into_iter().map(|(model, _)| model).collect(),
+            middlewares: None,
+            framework: Framework::Axum,
+            auth: None,
+            api_keys: None,
+            openapi: None,
+            templates_dir: None,
+            on_conflict: None,
+        },
+        skipped,
+    }
+}
+
+/// `database_type` for a database URL, from its scheme.
+pub fn database_type_of(database_url: &str) -> Option<&'static str> {
+    match database_url.split(':').next()?.to_lowercase().as_str() {
+        "sqlite" => Some("sqlite"),
+        "postgres" | "postgresql" => Some("postgres"),
+        "mysql" | "mariadb" => Some("mysql"),
+        _ => None,
+    }
+}
+
+/// Project name for a database URL or dump file: its database or file name.
+pub fn project_name(source: &str) -> String {
+    let source = source.split(['?', '#']).next().unwrap_or(source);
+    let name = source
+        .trim_end_matches('/')
+        .rsplit(['/', '\\', ':'])
+        .next()
+        .unwrap_or_default();
+    let name = name.split('.').next().unwrap_or_default();
+    let name = name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+        .collect::<String>()
+        .to_case(Case::Snake);
+    if crate_name_error(&name).is_some() {
+        "backend_project".to_string()
+    } else {
+        name
+    }
+}
+
+/// `user_accounts` → `UserAccount`.
+fn model_name(table: &str) -> String {
+    singular(&table.to_case(Case::Snake)).to_case(Case::Pascal)
+}
+
+/// `categories` → `category`, undoing `plural`.
+fn singular(word: &str) -> String {
+    if let Some(stem) = word.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if ["sses", "xes", "ches", "shes"]
+        .iter()
+        .any(|s| word.ends_with(s))
+    {
+        word[..word.len() - 2].to_string()
+    } else if word.ends_with('s') && !word.ends_with("ss") {
+        word[..word.len() - 1].to_string()
+    } else {
+        word.to_string()
+    }
+}
+
+/// `category` → `categories`, `box` → `boxes`, `key` → `keys`.
+pub fn plural(word: &str) -> String {
+    if let Some(stem) = word
+        .strip_suffix('y')
+        .filter(|stem| !stem.ends_with(['a', 'e', 'i', 'o', 'u']))
+    {
+        format!("{}ies", stem)
+    } else if ["s", "x", "ch", "sh"].iter().any(|s| word.ends_with(s)) {
+        format!("{}es", word)
+    } else {
+        format!("{}s", word)
+    }
+}
+
+fn field(
+    context: &str,
+    column: &ColumnSchema,
+    primary_key: bool,
+    database_type: &str,
+    skipped: &mut Vec<String>,
+) -> Option<Field> {
+    let (field_type, max_length) = match rust_type(&column.sql_type, database_type) {
+        Some(field_type) => field_type,
+        None => {
+            skipped.push(format!(
+                "{}: type '{}' has no supported Rust type",
+                context, column.sql_type
+            ));
+            return None;
+        }
+    };
+    let mut name = match column.name.to_case(Case::Snake) {
+        name if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) => {
+            format!("column_{}", name)
+        }
+        name => name,
+    };
+    if RUST_KEYWORDS.contains(&name.as_str()) {
+        name.push('_');
+    }
+    if name != column.name {
+        skipped.push(format!("{}: renamed to '{}'", context, name));
+    }
+    let default = match (&column.default, column.auto_increment) {
+        (Some(default), false) => parse_default(default, &field_type),
+        _ => None,
+    };
+    let integer_key = matches!(field_type.as_str(), "i32" | "i64");
+    // A SQLite `INTEGER PRIMARY KEY` is an alias of the rowid
+    let auto_increment = column.auto_increment
+        || database_type.eq_ignore_ascii_case("sqlite")
+            && primary_key
+            && column.sql_type.eq_ignore_ascii_case("integer");
+    Some(Field {
+        column: (name != column.name).then(|| column.name.clone()),
+        primary_key: (primary_key && name != "id").then_some(true),
+        unique: (column.unique && !primary_key).then_some(true),
+        index: (column.indexed && !column.unique && !primary_key).then_some(true),
+        // `i32`/`i64` keys are assigned by the database unless told otherwise
+        auto_increment: match (primary_key, auto_increment, integer_key) {
+            (true, false, true) => Some(false),
+            (true, true, false) if is_integer_type(&field_type) => Some(true),
+            _ => None,
+        },
+        field_type: if column.nullable && !primary_key {
+            format!("Option<{}>", field_type)
+        } else {
+            field_type
+        },
+        max_length,
+        default,
+        name,
+        ..Field::default()
+    })
+}
+
+/// Rust type of a column type, with its `VARCHAR` length.
+pub fn rust_type(sql_type: &str, database_type: &str) -> Option<(String, Option<u32>)> {
+    let sql_type = sql_type.trim().to_lowercase();
+    if sql_type.ends_with("[]") || sql_type.starts_with("array") {
+        return None;
+    }
+    // `varchar(80)` → (`varchar`, `80`); `int(11) unsigned` → `int unsigned`
+    let (base, args) = match (sql_type.find('('), sql_type.rfind(')')) {
+        (Some(open), Some(close)) if open < close => (
+            format!("{} {}", &sql_type[..open], &sql_type[close + 1..]),
+            Some(sql_type[open + 1..close].trim().to_string()),
+        ),
+        _ => (sql_type.clone(), None),
+    };
+    let base = base.split_whitespace().collect::<Vec<_>>().join(" ");
+    let sqlite = database_type.eq_ignore_ascii_case("sqlite");
+    let mysql = database_type.eq_ignore_ascii_case("mysql");
+    let length = args.as_deref().and_then(|args| args.parse::<u32>().ok());
+    let rust_type = match base.as_str() {
+        "tinyint" if mysql && args.as_deref() == Some("1") => "bool",
+        "bool" | "boolean" => "bool",
+        "tinyint" => "i8",
+        "tinyint unsigned" => "u8",
+        "smallint" | "int2" | "smallserial" => "i16",
+        "smallint unsigned" => "u16",
+        // SQLite integers are 64-bit
+        "int" | "integer" | "int4" | "mediumint" | "serial" if sqlite => "i64",
+        "int" | "integer" | "int4" | "mediumint" | "serial" => "i32",
+        "int unsigned" | "integer unsigned" | "mediumint unsigned" => "u32",
+        "bigint" | "int8" | "bigserial" => "i64",
+        "bigint unsigned" => "u64",
+        "real" | "float" | "float4" if sqlite => "f64",
+        "real" | "float" | "float4" => "f32",
+        "double" | "double precision" | "float8" => "f64",
+        "varchar" | "character varying" | "nvarchar" | "varchar2" => {
+            // MySQL strings are generated as `VARCHAR(255)` anyway
+            let max_length = length.filter(|length| !(mysql && *length == 255));
+            return Some(("String".to_string(), max_length));
+        }
+        "char" | "character" | "nchar" | "text" | "tinytext" | "mediumtext" | "longtext"
+        | "citext" | "clob" | "enum" | "set" | "name" => "String",
+        "uuid" => "uuid::Uuid",
+        "date" => "chrono::NaiveDate",
+        "time" | "time without time zone" => "chrono::NaiveTime",
+        "timestamp" if mysql => "chrono::DateTime<chrono::Utc>",
+        "timestamp" | "timestamp without time zone" | "datetime" => "chrono::NaiveDateTime",
+        "timestamptz" | "timestamp with time zone" => "chrono::DateTime<chrono::Utc>",
+        "json" | "jsonb" => "serde_json::Value",
+        "bytea" | "blob" | "tinyblob" | "mediumblob" | "longblob" | "binary" | "varbinary" => {
+            "Vec<u8>"
+        }
+        // Anything else in SQLite goes by its type affinity
+        other if sqlite && other.contains("int") => "i64",
+        other if sqlite && ["char", "clob", "text"].iter().any(|t| other.contains(t)) => "String",
+        other if sqlite && ["real", "floa", "doub"].iter().any(|t| other.contains(t)) => "f64",
+        "" if sqlite => "Vec<u8>",
+        _ => return None,
+    };
+    Some((rust_type.to_string(), None))
+}
+
+/// A column default as a config default; expressions are kept as `{ sql = ".." }`.
+pub fn parse_default(expression: &str, field_type: &str) -> Option<DefaultValue> {
+    let mut expression = expression.trim();
+    // `('draft')` and `(0)`: SQLite and MySQL wrap expression defaults
+    while let Some(inner) = expression
+        .strip_prefix('(')
+        .and_then(|e| e.strip_suffix(')'))
+    {
+        expression = inner.trim();
+    }
+    // Postgres casts literals, e.g. `'draft'::character varying`
+    let literal = match expression.strip_prefix('\'') {
+        Some(rest) => {
+            let mut text = String::new();
+            let mut chars = rest.chars().peekable();
+            let mut closed = false;
+            while let Some(c) = chars.next() {
+                if c == '\'' {
+                    if chars.peek() == Some(&'\'') {
+                        chars.next();
+                        text.push('\'');
+                        continue;
+                    }
+                    closed = true;
+                    break;
+                }
+                text.push(c);
+            }
+            let rest = chars.collect::<String>();
+            if closed && (rest.trim().is_empty() || rest.trim_start().starts_with("::")) {
+                Some(text)
+            } else {
+                None
+            }
+        }
+        None => None,
+    };
+    let value = literal
+        .as_deref()
+        .unwrap_or(match expression.split_once("::") {
+            Some((value, _)) => value.trim(),
+            None => expression,
+        });
+    if literal.is_none() && value.eq_ignore_ascii_case("null") {
+        return None;
+    }
+    match field_type {
+        "bool" => match value.to_lowercase().as_str() {
+            "true" | "t" | "1" => return Some(DefaultValue::Bool(true)),
+            "false" | "f" | "0" => return Some(DefaultValue::Bool(false)),
+            _ => {}
+        },
+        "f32" | "f64" => {
+            if let Ok(value) = value.parse() {
+                return Some(DefaultValue::Float(value));
+            }
+        }
+        field_type if field_type.starts_with(['i', 'u']) => {
+            if let Ok(value) = value.parse() {
+                return Some(DefaultValue::Integer(value));
+            }
+        }
+        _ => {}
+    }
+    match literal {
+        Some(text) => Some(DefaultValue::Text(text)),
+        None => Some(DefaultValue::Sql {
+            sql: expression.to_string(),
+        }),
+    }
+}
+
+/// `GET` list, `POST`, and `GET`/`PUT`/`DELETE` by primary key; `PUT` only
+/// when there is a column besides the key to update.
+pub fn crud_endpoints(model: &Model) -> Vec<Endpoint> {
+    let collection = format!("/{}", model.table_name.to_case(Case::Snake));
+    let key = model.fields[0].clone();
+    let item = format!("{}/{{{}}}", collection, key.name);
+    let key_param = Field {
+        name: key.name,
+        field_type: key.field_type,
+        ..Field::default()
+    };
+    let endpoint = |method: &str, path: &str, path_params: Option<Vec<Field>>| Endpoint {
+        method: method.to_string(),
+        path: path.to_string(),
+        path_params,
+        ..Endpoint::default()
+    };
+    let mut endpoints = vec![
+        endpoint("GET", &collection, None),
+        endpoint("POST", &collection, None),
+        endpoint("GET", &item, Some(vec![key_param.clone()])),
+    ];
+    if !update_request_fields(model).is_empty() {
+        endpoints.push(endpoint("PUT", &item, Some(vec![key_param.clone()])));
+    }
+    endpoints.push(endpoint("DELETE", &item, Some(vec![key_param])));
+    endpoints
+}
@@ -0,0 +1,624 @@
+use crate::domain::models::database_import::{ColumnSchema, ForeignKeySchema, TableSchema};
+
+/// Tables described by an SQL dump, with the statements that couldn't be used.
+pub struct DdlSchema {
+    pub tables: Vec<TableSchema>,
+    pub skipped: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TokenKind {
+    Word,
+    /// `"name"`, `` `name` `` or `[name]`; the text is the bare name.
+    Quoted,
+    String,
+    Symbol,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Token<'s> {
+    kind: TokenKind,
+    text: &'s str,
+    start: usize,
+    end: usize,
+}
+
+impl Token<'_> {
+    fn is(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        self.kind == TokenKind::Symbol && self.text == symbol
+    }
+
+    fn is_name(&self) -> bool {
+        matches!(self.kind, TokenKind::Word | TokenKind::Quoted)
+    }
+}
+
+/// Words ending a column's type and starting one of its constraints.
+const COLUMN_CONSTRAINTS: &[&str] = &[
+    "NOT",
+    "NULL",
+    "PRIMARY",
+    "UNIQUE",
+    "DEFAULT",
+    "REFERENCES",
+    "CHECK",
+    "CONSTRAINT",
+    "AUTO_INCREMENT",
+    "AUTOINCREMENT",
+    "GENERATED",
+    "AS",
+    "COLLATE",
+    "COMMENT",
+    "ON",
+    "CHARSET",
+];
+
+/// Reads the `CREATE TABLE`, `ALTER TABLE ... ADD` and `CREATE INDEX`
+/// statements of a dump from `pg_dump`, `mysqldump` or SQLite's `.schema`;
+/// everything else (data, sequences, grants, ...) is ignored.
+pub fn parse_ddl(sql: &str) -> Result<DdlSchema, String> {
+    let tokens = tokenize(sql)?;
+    let mut schema = DdlSchema {
+        tables: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for statement in tokens.split(|t| t.is_symbol(";")) {
+        let mut parser = Parser {
+            sql,
+            tokens: statement,
+            position: 0,
+        };
+        if parser.keyword("CREATE") {
+            parser.keyword("OR");
+            parser.keyword("REPLACE");
+            for modifier in ["GLOBAL", "LOCAL", "TEMP", "TEMPORARY", "UNLOGGED"] {
+                parser.keyword(modifier);
+            }
+            if parser.keyword("TABLE") {
+                parser.create_table(&mut schema);
+            } else if parser.keyword("UNIQUE") && parser.keyword("INDEX") {
+                parser.create_index(&mut schema, true);
+            } else if parser.keyword("INDEX") {
+                parser.create_index(&mut schema, false);
+            } else if parser.keyword("VIEW") || parser.keyword("MATERIALIZED") {
+                parser.keyword("VIEW");
+                parser.if_not_exists();
+                if let Some(name) = parser.qualified_name() {
+                    schema.skipped.push(format!("view '{}'", name));
+                }
+            }
+        } else if parser.keyword("ALTER") && parser.keyword("TABLE") {
+            parser.alter_table(&mut schema);
+        }
+    }
+    Ok(schema)
+}
+
+/// Guesses which database a dump comes from by its quoting and keywords.
+pub fn ddl_database_type(sql: &str) -> &'static str {
+    let upper = sql.to_uppercase();
+    if sql.contains('`') || upper.contains("ENGINE=") || upper.contains("AUTO_INCREMENT") {
+        "mysql"
+    } else if upper.contains("AUTOINCREMENT") || upper.contains("WITHOUT ROWID") {
+        "sqlite"
+    } else {
+        "postgres"
+    }
+}
+
+fn tokenize(sql: &str) -> Result<Vec<Token<'_>>, String> {
+    let bytes = sql.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    let line = |at: usize| sql[..at].matches('\n').count() + 1;
+    while i < bytes.len() {
+        let c = bytes[i];
+        let start = i;
+        if c.is_ascii_whitespace() {
+            i += 1;
+        } else if sql[i..].starts_with("--") || c == b'#' {
+            i = sql[i..].find('\n').map_or(bytes.len(), |end| i + end);
+        } else if sql[i..].starts_with("/*") {
+            i = sql[i + 2..]
+                .find("*/")
+                .map(|end| i + 2 + end + 2)
+                .ok_or_else(|| format!("line {}: unterminated comment", line(start)))?;
+        } else if c == b'\'' {
+            i += 1;
+            loop {
+                match bytes.get(i) {
+                    None => return Err(format!("line {}: unterminated string", line(start))),
+                    Some(b'\\') => i += 2,
+                    Some(b'\'') if bytes.get(i + 1) == Some(&b'\'') => i += 2,
+                    Some(b'\'') => break,
+                    Some(_) => i += 1,
+                }
+            }
+            i += 1;
+            tokens.push(Token {
+                kind: TokenKind::String,
+                text: &sql[start..i],
+                start,
+                end: i,
+            });
+        } else if matches!(c, b'"' | b'`' | b'[') {
+            let close = match c {
+                b'[' => ']',
+                _ => c as char,
+            };
+            let end = sql[i + 1..]
+                .find(close)
+                .map(|end| i + 1 + end)
+                .ok_or_else(|| format!("line {}: unterminated identifier", line(start)))?;
+            i = end + 1;
+            tokens.push(Token {
+                kind: TokenKind::Quoted,
+                text: &sql[start + 1..end],
+                start,
+                end: i,
+            });
+        } else if c.is_ascii_alphanumeric() || c == b'_' || c == b'$' || c >= 0x80 {
+            // Numbers keep their decimal point
+            let number = c.is_ascii_digit();
+            while i < bytes.len()
+                && (bytes[i].is_ascii_alphanumeric()
+                    || matches!(bytes[i], b'_' | b'$')
+                    || bytes[i] >= 0x80
+                    || number && bytes[i] == b'.')
+            {
+                i += 1;
+            }
+            tokens.push(Token {
+                kind: TokenKind::Word,
+                text: &sql[start..i],
+                start,
+                end: i,
+            });
+        } else {
+            i += sql[i..].chars().next().map_or(1, char::len_utf8);
+            tokens.push(Token {
+                kind: TokenKind::Symbol,
+                text: &sql[start..i],
+                start,
+                end: i,
+            });
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'p, 's> {
+    sql: &'s str,
+    tokens: &'p [Token<'s>],
+    position: usize,
+}
+
+impl<'p, 's> Parser<'p, 's> {
+    fn peek(&self) -> Option<&Token<'s>> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token<'s>> {
+        let token = self.tokens.get(self.position).copied();
+        self.position += 1;
+        token
+    }
+
+    /// Consumes `keyword` if it comes next.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        if self.peek().is_some_and(|t| t.is(keyword)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn symbol(&mut self, symbol: &str) -> bool {
+        if self.peek().is_some_and(|t| t.is_symbol(symbol)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn if_not_exists(&mut self) {
+        if self.keyword("IF") {
+            self.keyword("NOT");
+            self.keyword("EXISTS");
+        }
+    }
+
+    fn name(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(token) if token.is_name() => Some(self.next()?.text.to_string()),
+            _ => None,
+        }
+    }
+
+    /// `schema.table` → `table`.
+    fn qualified_name(&mut self) -> Option<String> {
+        let mut name = self.name()?;
+        while self.symbol(".") {
+            name = self.name()?;
+        }
+        Some(name)
+    }
+
+    /// Tokens of the parenthesized group starting here, without the parentheses.
+    fn group(&mut self) -> Option<&'p [Token<'s>]> {
+        if !self.peek()?.is_symbol("(") {
+            return None;
+        }
+        let start = self.position + 1;
+        let mut depth = 0;
+        while let Some(token) = self.next() {
+            if token.is_symbol("(") {
+                depth += 1;
+            } else if token.is_symbol(")") {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&self.tokens[start..self.position - 1]);
+                }
+            }
+        }
+        Some(&self.tokens[start..])
+    }
+
+    /// Skips one token, or a whole parenthesized group.
+    fn skip(&mut self) {
+        if self.group().is_none() {
+            self.position += 1;
+        }
+    }
+
+    /// Column names of a `(a, b DESC, c(10))` list.
+    fn column_list(&mut self) -> Vec<String> {
+        let Some(group) = self.group() else {
+            return Vec::new();
+        };
+        split_commas(group)
+            .iter()
+            .filter_map(|part| part.first().filter(|t| t.is_name()))
+            .map(|t| t.text.to_string())
+            .collect()
+    }
+
+    fn source(&self, tokens: &[Token<'s>]) -> String {
+        match (tokens.first(), tokens.last()) {
+            (Some(first), Some(last)) => self.sql[first.start..last.end].to_string(),
+            _ => String::new(),
+        }
+    }
+
+    fn create_table(&mut self, schema: &mut DdlSchema) {
+        self.if_not_exists();
+        let Some(name) = self.qualified_name() else {
+            return;
+        };
+        let Some(elements) = self.group() else {
+            schema
+                .skipped
+                .push(format!("table '{}': not created from a column list", name));
+            return;
+        };
+        schema.tables.retain(|t| t.name != name);
+        let mut table = TableSchema {
+            name: name.clone(),
+            ..TableSchema::default()
+        };
+        for element in split_commas(elements) {
+            let mut parser = Parser {
+                sql: self.sql,
+                tokens: element,
+                position: 0,
+            };
+            if !parser.table_constraint(&mut table) {
+                let mut parser = Parser {
+                    sql: self.sql,
+                    tokens: element,
+                    position: 0,
+                };
+                parser.column(&mut table, &mut schema.skipped);
+            }
+        }
+        schema.tables.push(table);
+    }
+
+    /// `PRIMARY KEY (..)`, `UNIQUE (..)`, `FOREIGN KEY (..) REFERENCES ..` and
+    /// MySQL's `KEY name (..)`; false when the element is a column.
+    fn table_constraint(&mut self, table: &mut TableSchema) -> bool {
+        if self.keyword("CONSTRAINT") {
+            self.name();
+        }
+        if self.keyword("PRIMARY") {
+            self.keyword("KEY");
+            table.primary_key = self.column_list();
+        } else if self.keyword("UNIQUE") {
+            let _ = self.keyword("KEY") || self.keyword("INDEX");
+            if !self.peek().is_some_and(|t| t.is_symbol("(")) {
+                self.name();
+            }
+            if let [column] = self.column_list().as_slice() {
+                if let Some(column) = table.column_mut(column) {
+                    column.unique = true;
+                }
+            }
+        } else if self.keyword("FOREIGN") {
+            self.keyword("KEY");
+            if !self.peek().is_some_and(|t| t.is_symbol("(")) {
+                self.name();
+            }
+            let columns = self.column_list();
+            if self.keyword("REFERENCES") {
+                if let Some(foreign_key) = self.references(columns) {
+                    table.foreign_keys.push(foreign_key);
+                }
+            }
+        } else if self.keyword("KEY") || self.keyword("INDEX") {
+            if !self.peek().is_some_and(|t| t.is_symbol("(")) {
+                self.name();
+            }
+            if let [column] = self.column_list().as_slice() {
+                if let Some(column) = table.column_mut(column) {
+                    column.indexed = true;
+                }
+            }
+        } else {
+            return ["CHECK", "FULLTEXT", "SPATIAL", "EXCLUDE"]
+                .iter()
+                .any(|keyword| self.keyword(keyword));
+        }
+        true
+    }
+
+    /// `REFERENCES table [(columns)]`, after the keyword.
+    fn references(&mut self, columns: Vec<String>) -> Option<ForeignKeySchema> {
+        let table = self.qualified_name()?;
+        Some(ForeignKeySchema {
+            columns,
+            table,
+            referenced_columns: self.column_list(),
+        })
+    }
+
+    fn column(&mut self, table: &mut TableSchema, skipped: &mut Vec<String>) {
+        let Some(name) = self.name() else {
+            return;
+        };
+        let type_start = self.position;
+        while let Some(token) = self.peek() {
+            let constraint = COLUMN_CONSTRAINTS.iter().any(|k| token.is(k))
+                // `character varying` is a type, `CHARACTER SET utf8` is not
+                || token.is("CHARACTER") && self.position > type_start;
+            if constraint {
+                break;
+            }
+            self.skip();
+        }
+        let sql_type = self.source(&self.tokens[type_start..self.position]);
+        let serial = ["serial", "bigserial", "smallserial"]
+            .iter()
+            .any(|s| sql_type.eq_ignore_ascii_case(s));
+        let mut column = ColumnSchema {
+            name: name.clone(),
+            sql_type,
+            nullable: true,
+            auto_increment: serial,
+            ..ColumnSchema::default()
+        };
+        while let Some(token) = self.peek().copied() {
+            if self.keyword("NOT") {
+                self.keyword("NULL");
+                column.nullable = false;
+            } else if self.keyword("PRIMARY") {
+                self.keyword("KEY");
+                table.primary_key = vec![name.clone()];
+                column.nullable = false;
+            } else if self.keyword("UNIQUE") {
+                self.keyword("KEY");
+                column.unique = true;
+            } else if self.keyword("DEFAULT") {
+                let default = self.default_expression();
+                if default.to_lowercase().starts_with("nextval(") {
+                    column.auto_increment = true;
+                } else {
+                    column.default = Some(default);
+                }
+            } else if self.keyword("REFERENCES") {
+                if let Some(foreign_key) = self.references(vec![name.clone()]) {
+                    table.foreign_keys.push(foreign_key);
+                }
+            } else if self.keyword("AUTO_INCREMENT") || self.keyword("AUTOINCREMENT") {
+                column.auto_increment = true;
+            } else if token.is("GENERATED") || token.is("AS") {
+                // `GENERATED .. AS IDENTITY` is assigned by the database; `AS (expr)` is computed
+                let mut identity = false;
+                while let Some(token) = self.peek().copied() {
+                    if token.is("IDENTITY") {
+                        identity = true;
+                    }
+                    let computed = token.is_symbol("(") && !identity;
+                    self.skip();
+                    if computed {
+                        skipped.push(format!(
+                            "column '{}.{}': computed columns are not supported",
+                            table.name, name
+                        ));
+                        return;
+                    }
+                    if identity {
+                        break;
+                    }
+                }
+                column.auto_increment |= identity;
+            } else if self.keyword("CONSTRAINT") {
+                self.name();
+            } else {
+                self.skip();
+            }
+        }
+        table.columns.retain(|c| c.name != name);
+        table.columns.push(column);
+    }
+
+    /// A default: a literal, a function call or a parenthesized expression,
+    /// with any `::type` casts.
+    fn default_expression(&mut self) -> String {
+        let start = self.position;
+        if self.symbol("-") || self.symbol("+") {
+            self.position += 1;
+        } else {
+            self.skip();
+        }
+        // Function call, e.g. `now()`
+        if self.peek().is_some_and(|t| t.is_symbol("(")) {
+            self.skip();
+        }
+        while self.peek().is_some_and(|t| t.is_symbol(":"))
+            && self
+                .tokens
+                .get(self.position + 1)
+                .is_some_and(|t| t.is_symbol(":"))
+        {
+            self.position += 2;
+            while self
+                .peek()
+                .is_some_and(|t| t.is_name() && !COLUMN_CONSTRAINTS.iter().any(|k| t.is(k)))
+            {
+                self.skip();
+                if self.peek().is_some_and(|t| t.is_symbol("(")) {
+                    self.skip();
+                }
+            }
+        }
+        let end = self.position.min(self.tokens.len());
+        self.source(&self.tokens[start..end])
+    }
+
+    /// `CREATE [UNIQUE] INDEX name ON table (column)`, after `INDEX`.
+    fn create_index(&mut self, schema: &mut DdlSchema, unique: bool) {
+        self.keyword("CONCURRENTLY");
+        self.if_not_exists();
+        if !self.peek().is_some_and(|t| t.is("ON")) {
+            self.qualified_name();
+        }
+        if !self.keyword("ON") {
+            return;
+        }
+        self.keyword("ONLY");
+        let Some(table) = self.qualified_name() else {
+            return;
+        };
+        if self.keyword("USING") {
+            self.name();
+        }
+        let Some(table) = schema.tables.iter_mut().find(|t| t.name == table) else {
+            return;
+        };
+        if let [column] = self.column_list().as_slice() {
+            if let Some(column) = table.column_mut(column) {
+                if unique {
+                    column.unique = true;
+                } else {
+                    column.indexed = true;
+                }
+            }
+        }
+    }
+
+    /// The `ADD`, `ALTER COLUMN` and `MODIFY` actions of `pg_dump` and
+    /// `mysqldump`, which add keys and sequences after the tables.
+    fn alter_table(&mut self, schema: &mut DdlSchema) {
+        self.if_not_exists();
+        self.keyword("ONLY");
+        let Some(name) = self.qualified_name() else {
+            return;
+        };
+        let Some(index) = schema.tables.iter().position(|t| t.name == name) else {
+            return;
+        };
+        let actions = split_commas(&self.tokens[self.position..]);
+        for action in actions {
+            let mut parser = Parser {
+                sql: self.sql,
+                tokens: action,
+                position: 0,
+            };
+            let table = &mut schema.tables[index];
+            if parser.keyword("ADD") {
+                if !parser.table_constraint(table) {
+                    let mut parser = Parser {
+                        sql: self.sql,
+                        tokens: &action[1..],
+                        position: 0,
+                    };
+                    parser.keyword("COLUMN");
+                    parser.if_not_exists();
+                    let tokens = &parser.tokens[parser.position..];
+                    let mut parser = Parser {
+                        sql: self.sql,
+                        tokens,
+                        position: 0,
+                    };
+                    parser.column(table, &mut schema.skipped);
+                }
+            } else if parser.keyword("ALTER") {
+                parser.keyword("COLUMN");
+                let Some(column) = parser.name() else {
+                    continue;
+                };
+                let rest = parser.source(&action[parser.position..]).to_lowercase();
+                if rest.contains("nextval(") || rest.contains("identity") {
+                    if let Some(column) = table.column_mut(&column) {
+                        column.auto_increment = true;
+                    }
+                }
+            } else if parser.keyword("MODIFY") || parser.keyword("CHANGE") {
+                parser.keyword("COLUMN");
+                if action[0].is("CHANGE") {
+                    parser.name();
+                }
+                let mut modified = TableSchema {
+                    name: name.clone(),
+                    ..TableSchema::default()
+                };
+                parser.column(&mut modified, &mut Vec::new());
+                if let Some(column) = modified.columns.pop() {
+                    if let Some(existing) = table.column_mut(&column.name) {
+                        *existing = column;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Splits tokens on top-level commas.
+fn split_commas<'p, 's>(tokens: &'p [Token<'s>]) -> Vec<&'p [Token<'s>]> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        if token.is_symbol("(") {
+            depth += 1;
+        } else if token.is_symbol(")") {
+            depth -= 1;
+        } else if token.is_symbol(",") && depth == 0 {
+            parts.push(&tokens[start..i]);
+            start = i + 1;
+        }
+    }
+    if start < tokens.len() {
+        parts.push(&tokens[start..]);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests;
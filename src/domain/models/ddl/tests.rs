@@ -0,0 +1,101 @@
+use super::{ddl_database_type, parse_ddl};
+
+const PG_DUMP: &str = r#"
+--
+-- PostgreSQL database dump
+--
+SET statement_timeout = 0;
+
+CREATE TABLE public.users (
+    id integer NOT NULL,
+    email character varying(80) NOT NULL,
+    bio text,
+    created_at timestamp without time zone DEFAULT now() NOT NULL
+);
+
+CREATE SEQUENCE public.users_id_seq AS integer START WITH 1;
+ALTER TABLE ONLY public.users ALTER COLUMN id SET DEFAULT nextval('public.users_id_seq'::regclass);
+ALTER TABLE ONLY public.users ADD CONSTRAINT users_pkey PRIMARY KEY (id);
+CREATE UNIQUE INDEX users_email_key ON public.users USING btree (email);
+CREATE INDEX users_created_at_idx ON public.users USING btree (created_at);
+CREATE VIEW public.active_users AS SELECT * FROM public.users;
+COPY public.users (id, email) FROM stdin;
+"#;
+
+const MYSQLDUMP: &str = r#"
+/*!40101 SET NAMES utf8 */;
+CREATE TABLE `posts` (
+  `id` int NOT NULL AUTO_INCREMENT,
+  `user_id` int NOT NULL,
+  `title` varchar(120) NOT NULL DEFAULT 'untitled',
+  `body` text,
+  PRIMARY KEY (`id`),
+  KEY `posts_user_id` (`user_id`),
+  CONSTRAINT `posts_user_fk` FOREIGN KEY (`user_id`) REFERENCES `users` (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+"#;
+
+#[test]
+fn pg_dump_tables_pick_up_later_constraints_and_indexes() {
+    let schema = parse_ddl(PG_DUMP).unwrap();
+    assert_eq!(schema.tables.len(), 1);
+    let users = &schema.tables[0];
+    assert_eq!(users.name, "users");
+    assert_eq!(users.primary_key, ["id"]);
+
+    let id = &users.columns[0];
+    assert!(id.auto_increment);
+    let email = &users.columns[1];
+    assert_eq!(email.sql_type, "character varying(80)");
+    assert!(email.unique && !email.nullable);
+    assert!(users.columns[2].nullable);
+    let created_at = &users.columns[3];
+    assert_eq!(created_at.default.as_deref(), Some("now()"));
+    assert!(created_at.indexed);
+
+    assert_eq!(schema.skipped, ["view 'active_users'"]);
+}
+
+#[test]
+fn mysqldump_inline_keys_and_foreign_keys_are_read() {
+    let schema = parse_ddl(MYSQLDUMP).unwrap();
+    let posts = &schema.tables[0];
+    assert_eq!(posts.name, "posts");
+    assert_eq!(posts.primary_key, ["id"]);
+    assert!(posts.columns[0].auto_increment);
+    assert!(posts.columns[1].indexed);
+    assert_eq!(posts.columns[2].default.as_deref(), Some("'untitled'"));
+    assert!(posts.columns[3].nullable);
+
+    let fk = &posts.foreign_keys[0];
+    assert_eq!(fk.columns, ["user_id"]);
+    assert_eq!(fk.table, "users");
+    assert_eq!(fk.referenced_columns, ["id"]);
+    assert!(schema.skipped.is_empty());
+}
+
+#[test]
+fn unterminated_strings_and_comments_report_their_line() {
+    assert_eq!(
+        parse_ddl("CREATE TABLE t (a text DEFAULT 'x);")
+            .err()
+            .as_deref(),
+        Some("line 1: unterminated string")
+    );
+    assert_eq!(
+        parse_ddl("CREATE TABLE t (a text);\n/* open")
+            .err()
+            .as_deref(),
+        Some("line 2: unterminated comment")
+    );
+}
+
+#[test]
+fn database_type_is_guessed_from_dialect_markers() {
+    assert_eq!(ddl_database_type(MYSQLDUMP), "mysql");
+    assert_eq!(
+        ddl_database_type("CREATE TABLE t (id INTEGER PRIMARY KEY AUTOINCREMENT);"),
+        "sqlite"
+    );
+    assert_eq!(ddl_database_type(PG_DUMP), "postgres");
+}
@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// Opens a protected region, optionally followed by its name; works in any comment syntax.
+pub const BEGIN_USER: &str = "prkserver:begin-user";
+/// Closes a protected region.
+pub const END_USER: &str = "prkserver:end-user";
+
+/// `.prkserver/manifest.json`: the hash of every file as it was last generated,
+/// used to tell files edited since apart from ones that can be replaced.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Manifest {
+    pub files: BTreeMap<String, String>,
+}
+
+/// What regeneration did with one file.
+#[derive(Debug, PartialEq)]
+pub enum FileOutcome {
+    /// Created, or replaced because it hadn't been edited.
+    Written,
+    /// Already identical to the new output.
+    Unchanged,
+    /// Edited, and the edits were merged with the new output.
+    Merged { conflicts: usize },
+    /// Edited, and left alone with the new output written to `{file}.new`.
+    WroteNew,
+    /// Edited, and left alone.
+    Skipped,
+    /// Edited, and replaced anyway.
+    Overwritten,
+}
+
+/// The outcome of every file a generation wrote, by path within the project.
+#[derive(Default)]
+pub struct GenerationReport {
+    pub files: Vec<(String, FileOutcome)>,
+}
+
+/// Each protected region of `content`: its name (or `#n` for the n-th unnamed
+/// one) and the lines between its markers.
+fn user_regions(content: &str) -> Vec<(String, String)> {
+    let mut regions = Vec::new();
+    let mut current: Option<(String, String)> = None;
+    let mut unnamed = 0;
+    for line in content.split_inclusive('\n') {
+        if let Some((_, rest)) = line.split_once(BEGIN_USER) {
+            let name = match rest.split_whitespace().next() {
+                Some(name) => name.to_string(),
+                None => {
+                    unnamed += 1;
+                    format!("#{}", unnamed)
+                }
+            };
+            current = Some((name, String::new()));
+        } else if line.contains(END_USER) {
+            regions.extend(current.take());
+        } else if let Some((_, body)) = current.as_mut() {
+            body.push_str(line);
+        }
+    }
+    regions
+}
+
+/// `generated` with the body of each protected region taken from the region of
+/// the same name in `existing`, when it has one.
+pub fn carry_user_regions(generated: &str, existing: &str) -> String {
+    let existing: BTreeMap<String, String> = user_regions(existing).into_iter().collect();
+    let mut names = user_regions(generated).into_iter().map(|(name, _)| name);
+    let mut content = String::new();
+    let mut skipping = false;
+    for line in generated.split_inclusive('\n') {
+        if line.contains(BEGIN_USER) {
+            content.push_str(line);
+            if let Some(body) = names.next().and_then(|name| existing.get(&name)) {
+                content.push_str(body);
+                skipping = true;
+            }
+        } else if line.contains(END_USER) {
+            content.push_str(line);
+            skipping = false;
+        } else if !skipping {
+            content.push_str(line);
+        }
+    }
+    content
+}
+
+/// SHA-256 of `content` with the bodies of its protected regions left out, so
+/// edits inside them don't count as changes to the file.
+pub fn content_hash(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    let mut inside = false;
+    for line in content.split_inclusive('\n') {
+        if line.contains(BEGIN_USER) {
+            inside = true;
+        } else if line.contains(END_USER) {
+            inside = false;
+        } else if inside {
+            continue;
+        }
+        hasher.update(line.as_bytes());
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
@@ -0,0 +1,141 @@
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+/// The result of a three-way merge; conflicting lines are wrapped in
+/// `<<<<<<< local` / `=======` / `>>>>>>> generated` markers.
+pub struct Merge {
+    pub content: String,
+    pub conflicts: usize,
+}
+
+/// Lines `base[start..end]` replaced by `other[other_start..other_end]`.
+struct Hunk {
+    start: usize,
+    end: usize,
+    other_start: usize,
+    other_end: usize,
+}
+
+fn hunks(base: &[&str], other: &[&str]) -> Vec<Hunk> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    for op in capture_diff_slices(Algorithm::Myers, base, other) {
+        let (old, new) = (op.old_range(), op.new_range());
+        if matches!(op, DiffOp::Equal { .. }) {
+            continue;
+        }
+        // A delete next to an insert is one replacement
+        match hunks.last_mut() {
+            Some(last) if last.end == old.start && last.other_end == new.start => {
+                last.end = old.end;
+                last.other_end = new.end;
+            }
+            _ => hunks.push(Hunk {
+                start: old.start,
+                end: old.end,
+                other_start: new.start,
+                other_end: new.end,
+            }),
+        }
+    }
+    hunks
+}
+
+/// `base[start..end]` with the changes of `hunks` applied.
+fn apply<'a>(
+    base: &[&'a str],
+    other: &[&'a str],
+    (start, end): (usize, usize),
+    hunks: &[&Hunk],
+) -> Vec<&'a str> {
+    let mut lines = Vec::new();
+    let mut position = start;
+    for hunk in hunks {
+        lines.extend_from_slice(&base[position..hunk.start]);
+        lines.extend_from_slice(&other[hunk.other_start..hunk.other_end]);
+        position = hunk.end;
+    }
+    lines.extend_from_slice(&base[position..end]);
+    lines
+}
+
+/// Whether `hunk` touches the group of lines `first..end`.
+fn overlaps(hunk: &Hunk, first: usize, end: usize) -> bool {
+    hunk.start < end || hunk.start == first
+}
+
+fn push_lines(content: &mut String, lines: &[&str]) {
+    for line in lines {
+        content.push_str(line);
+    }
+}
+
+/// Conflict markers go on their own line, even after a last line without a newline.
+fn push_marker(content: &mut String, marker: &str) {
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(marker);
+}
+
+/// Merges the changes from `base` to `local` with those from `base` to
+/// `generated`, line by line. Changes touching the same lines conflict unless
+/// both sides made the same change.
+pub fn merge3(base: &str, local: &str, generated: &str) -> Merge {
+    let base: Vec<&str> = base.split_inclusive('\n').collect();
+    let local: Vec<&str> = local.split_inclusive('\n').collect();
+    let generated: Vec<&str> = generated.split_inclusive('\n').collect();
+    let (local_hunks, generated_hunks) = (hunks(&base, &local), hunks(&base, &generated));
+
+    let mut content = String::new();
+    let mut conflicts = 0;
+    let mut position = 0;
+    let (mut l, mut g) = (0, 0);
+    loop {
+        // Start a group at the next hunk of either side and grow it while hunks
+        // overlap; edits to neighbouring lines don't
+        let first = match (local_hunks.get(l), generated_hunks.get(g)) {
+            (None, None) => break,
+            (Some(a), Some(b)) => a.start.min(b.start),
+            (Some(a), None) => a.start,
+            (None, Some(b)) => b.start,
+        };
+        let (mut end, mut group_local, mut group_generated) = (first, Vec::new(), Vec::new());
+        loop {
+            if let Some(hunk) = local_hunks.get(l).filter(|hunk| overlaps(hunk, first, end)) {
+                end = end.max(hunk.end);
+                group_local.push(hunk);
+                l += 1;
+            } else if let Some(hunk) = generated_hunks
+                .get(g)
+                .filter(|hunk| overlaps(hunk, first, end))
+            {
+                end = end.max(hunk.end);
+                group_generated.push(hunk);
+                g += 1;
+            } else {
+                break;
+            }
+        }
+
+        push_lines(&mut content, &base[position..first]);
+        let ours = apply(&base, &local, (first, end), &group_local);
+        let theirs = apply(&base, &generated, (first, end), &group_generated);
+        if group_local.is_empty() || ours == theirs {
+            push_lines(&mut content, &theirs);
+        } else if group_generated.is_empty() {
+            push_lines(&mut content, &ours);
+        } else {
+            conflicts += 1;
+            push_marker(&mut content, "<<<<<<< local\n");
+            push_lines(&mut content, &ours);
+            push_marker(&mut content, "=======\n");
+            push_lines(&mut content, &theirs);
+            push_marker(&mut content, ">>>>>>> generated\n");
+        }
+        position = end;
+    }
+    push_lines(&mut content, &base[position..]);
+    Merge { content, conflicts }
+}
+
+#[cfg(test)]
+mod tests;
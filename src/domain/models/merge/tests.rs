@@ -0,0 +1,54 @@
+use super::merge3;
+
+const BASE: &str = "fn main() {\n    let a = 1;\n    let b = 2;\n    let c = 3;\n}\n";
+
+#[test]
+fn edits_to_different_lines_are_both_kept() {
+    let local = BASE.replace("let a = 1", "let a = 10");
+    let generated = BASE.replace("let c = 3", "let c = 30");
+    let merge = merge3(BASE, &local, &generated);
+    assert_eq!(merge.conflicts, 0);
+    assert_eq!(
+        merge.content,
+        "fn main() {\n    let a = 10;\n    let b = 2;\n    let c = 30;\n}\n"
+    );
+}
+
+#[test]
+fn one_sided_changes_win() {
+    let local = BASE.replace("    let b = 2;\n", "");
+    assert_eq!(merge3(BASE, &local, BASE).content, local);
+    let generated = BASE.replace("}\n", "    let d = 4;\n}\n");
+    assert_eq!(merge3(BASE, BASE, &generated).content, generated);
+}
+
+#[test]
+fn the_same_change_on_both_sides_does_not_conflict() {
+    let changed = BASE.replace("let b = 2", "let b = 20");
+    let merge = merge3(BASE, &changed, &changed);
+    assert_eq!(merge.conflicts, 0);
+    assert_eq!(merge.content, changed);
+}
+
+#[test]
+fn different_changes_to_the_same_line_conflict() {
+    let local = BASE.replace("let b = 2", "let b = 20");
+    let generated = BASE.replace("let b = 2", "let b = 200");
+    let merge = merge3(BASE, &local, &generated);
+    assert_eq!(merge.conflicts, 1);
+    assert_eq!(
+        merge.content,
+        "fn main() {\n    let a = 1;\n<<<<<<< local\n    let b = 20;\n=======\n    \
+         let b = 200;\n>>>>>>> generated\n    let c = 3;\n}\n"
+    );
+}
+
+#[test]
+fn markers_start_on_their_own_line_without_a_trailing_newline() {
+    let merge = merge3("a\nb", "a\nlocal", "a\ngenerated");
+    assert_eq!(merge.conflicts, 1);
+    assert_eq!(
+        merge.content,
+        "a\n<<<<<<< local\nlocal\n=======\ngenerated\n>>>>>>> generated\n"
+    );
+}
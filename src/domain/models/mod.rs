@@ -1,2 +1,12 @@
+pub mod api_key;
 pub mod config;
+pub mod database_import;
+pub mod ddl;
+pub mod keywords;
+pub mod manifest;
+pub mod merge;
+pub mod openapi_import;
+pub mod relation;
+pub mod scaffold;
+pub mod schema;
 pub mod template;
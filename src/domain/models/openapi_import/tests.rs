@@ -0,0 +1,160 @@
+use super::{import_openapi, OpenApiImport};
+use crate::output::config_toml_from;
+
+const PETSTORE: &str = r##"
+openapi: 3.0.3
+info:
+  title: Pet Store
+  version: "1.0"
+security:
+  - bearer: []
+paths:
+  /pets:
+    get:
+      parameters:
+        - { name: tag, in: query, schema: { type: string } }
+        - { name: limit, in: query, schema: { type: integer } }
+        - { name: X-Trace, in: header, schema: { type: string } }
+      responses:
+        "200":
+          description: A page of pets
+          content:
+            application/json:
+              schema: { $ref: "#/components/schemas/PetPage" }
+    post:
+      requestBody:
+        content:
+          application/json:
+            schema: { $ref: "#/components/schemas/NewPet" }
+      responses:
+        "201":
+          description: Created
+          content:
+            application/json:
+              schema: { $ref: "#/components/schemas/Pet" }
+        "400":
+          description: Invalid
+          content:
+            application/json:
+              schema: { $ref: "#/components/schemas/Error" }
+  /pets/{petId}:
+    parameters:
+      - { name: petId, in: path, required: true, schema: { type: integer, format: int64 } }
+    get:
+      responses:
+        "200":
+          description: A pet
+          content:
+            application/json:
+              schema: { $ref: "#/components/schemas/Pet" }
+    options:
+      responses:
+        "204": { description: Allowed methods }
+components:
+  schemas:
+    Pet:
+      type: object
+      required: [id, name]
+      properties:
+        id: { type: integer, format: int64 }
+        name: { type: string, maxLength: 60 }
+        type: { type: string, default: dog }
+        birthDate: { type: string, format: date, nullable: true }
+        toys: { type: array, items: { type: string } }
+    NewPet:
+      type: object
+      required: [name]
+      properties:
+        name: { type: string }
+        type: { type: string }
+    PetPage:
+      type: object
+      properties:
+        items: { type: array, items: { $ref: "#/components/schemas/Pet" } }
+        total: { type: integer }
+    Error:
+      type: object
+      properties:
+        message: { type: string }
+"##;
+
+fn import(yaml: &str) -> OpenApiImport {
+    let document: serde_json::Value = serde_yaml::from_str(yaml).unwrap();
+    import_openapi(&document).unwrap()
+}
+
+#[test]
+fn schemas_become_models_and_operations_their_endpoints() {
+    let import = import(PETSTORE);
+    let content = config_toml_from(&import.config);
+    let models = &content[content.find("[[models]]").unwrap()..];
+    // `PetPage` is folded into pagination, `NewPet` into the body params
+    assert_eq!(
+        models,
+        r#"[[models]]
+name = "Pet"
+table_name = "pets"
+fields = [
+    { name = "id", type = "i64" },
+    { name = "name", type = "String", max_length = 60 },
+    { name = "type_", type = "Option<String>", column = "type", default = "dog" },
+    { name = "birth_date", type = "Option<NaiveDate>" },
+]
+endpoints = [
+    { method = "GET", path = "/pets", query_params = [
+        { name = "tag", type = "String" },
+    ] },
+    { method = "POST", path = "/pets", body_params = [
+        { name = "name", type = "String" },
+        { name = "type_", type = "Option<String>" },
+    ] },
+    { method = "GET", path = "/pets/{pet_id}", path_params = [
+        { name = "pet_id", type = "i64" },
+    ] },
+]
+"#
+    );
+    assert_eq!(import.config.project_name, "pet_store");
+}
+
+#[test]
+fn what_config_toml_cannot_express_is_listed() {
+    assert_eq!(
+        import(PETSTORE).skipped,
+        [
+            "schema 'Error': only used by error responses",
+            "schema 'Pet' property 'type': renamed to 'type_'",
+            "schema 'Pet' property 'birthDate': renamed to 'birth_date'",
+            "schema 'Pet' property 'toys': arrays are not supported",
+            "GET /pets parameter 'X-Trace': header parameters are not supported",
+            "POST /pets body property 'type': renamed to 'type_'",
+            "GET /pets/{petId}: path changed to '/pets/{pet_id}'",
+            "OPTIONS /pets/{petId}: unsupported HTTP method",
+            "security requirements: use `auth`, `roles` or `middlewares` on the endpoints instead",
+        ]
+    );
+}
+
+#[test]
+fn only_openapi_3_documents_are_imported() {
+    let swagger = serde_json::json!({ "swagger": "2.0", "paths": {} });
+    assert_eq!(
+        import_openapi(&swagger).err().unwrap(),
+        "Swagger 2.0 documents are not supported; convert to OpenAPI 3 first"
+    );
+    let unknown = serde_json::json!({ "paths": {} });
+    assert!(import_openapi(&unknown).is_err());
+}
+
+#[test]
+fn titles_that_are_not_crate_names_fall_back_to_the_default() {
+    for (title, project_name) in [
+        ("Pet Store", "pet_store"),
+        ("3D Printers", "backend_project"),
+        ("type", "backend_project"),
+    ] {
+        let document = serde_json::json!({ "openapi": "3.1.0", "info": { "title": title } });
+        let import = import_openapi(&document).unwrap();
+        assert_eq!(import.config.project_name, project_name, "{}", title);
+    }
+}
@@ -0,0 +1,280 @@
+use convert_case::{Case, Casing};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::models::config::{Config, Endpoint, Field, ForeignKey, Model};
+use crate::output::unwrap_option;
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationKind {
+    BelongsTo,
+    HasMany,
+    ManyToMany,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Relation {
+    pub kind: RelationKind,
+    /// Name of the related model.
+    pub model: String,
+    /// Name used by `?include=` and nested routes; defaults to the related model
+    /// in snake_case for `belongs_to` and to its table name otherwise.
+    pub name: Option<String>,
+    /// Foreign-key field; defaults to `{model}_id` of the model being pointed at.
+    pub foreign_key: Option<String>,
+    /// Join table of a `many_to_many`; defaults to both table names in alphabetical order.
+    pub join_table: Option<String>,
+    /// Generate `GET /{models}/{model_id}/{name}` for `has_many` and `many_to_many`.
+    pub nested_route: Option<bool>,
+}
+
+impl Relation {
+    pub fn name(&self, target: &Model) -> String {
+        match (&self.name, self.kind) {
+            (Some(name), _) => name.clone(),
+            (None, RelationKind::BelongsTo) => target.name.to_case(Case::Snake),
+            (None, _) => target.table_name.clone(),
+        }
+    }
+
+    /// Foreign-key field: on `owner` for `belongs_to`, on `target` for `has_many`
+    /// and on the join table, pointing at `owner`, for `many_to_many`.
+    pub fn foreign_key(&self, owner: &Model, target: &Model) -> String {
+        match (&self.foreign_key, self.kind) {
+            (Some(foreign_key), _) => foreign_key.clone(),
+            (None, RelationKind::BelongsTo) => foreign_key_name(target),
+            (None, _) => foreign_key_name(owner),
+        }
+    }
+
+    pub fn has_nested_route(&self) -> bool {
+        self.nested_route.unwrap_or(false)
+    }
+
+    /// Table and model name of a `many_to_many` join table, e.g. `roles_users` / `RoleUser`.
+    pub fn join_model(&self, owner: &Model, target: &Model) -> (String, String) {
+        let mut models = [owner, target];
+        models.sort_by(|a, b| a.name.cmp(&b.name));
+        let table = self.join_table.clone().unwrap_or_else(|| {
+            let mut tables = [&owner.table_name, &target.table_name];
+            tables.sort();
+            format!("{}_{}", tables[0], tables[1])
+        });
+        (table, format!("{}{}", models[0].name, models[1].name))
+    }
+}
+
+/// Default foreign-key field pointing at `model`, e.g. `user_id`.
+pub fn foreign_key_name(model: &Model) -> String {
+    format!("{}_id", model.name.to_case(Case::Snake))
+}
+
+/// A `find_by_{key}` method generated on a model's repository for a relation.
+pub struct Lookup {
+    pub key: String,
+    /// Column compared against the key, on the join table for `many_to_many`.
+    pub column: String,
+    pub key_type: String,
+    /// `(join table, join column pointing at this model)` for `many_to_many` lookups.
+    pub join: Option<(String, String)>,
+}
+
+/// A relation a handler can embed through `?include=`.
+pub struct Include {
+    pub name: String,
+    pub kind: RelationKind,
+    pub target: Model,
+    /// Foreign-key field on the model for `belongs_to`, else the target's lookup key.
+    pub key: String,
+}
+
+/// A `GET /{models}/{model_id}/{name}` route listing related rows.
+pub struct NestedRoute {
+    pub endpoint: Endpoint,
+    pub target: Model,
+    /// Lookup on the target's repository, keyed by the path parameter.
+    pub key: String,
+}
+
+/// The related model of every relation on `model` that refers to a known model.
+fn related<'a>(config: &'a Config, model: &'a Model) -> Vec<(&'a Relation, &'a Model)> {
+    model
+        .relations
+        .iter()
+        .flatten()
+        .filter_map(|relation| {
+            config
+                .models
+                .iter()
+                .find(|m| m.name == relation.model)
+                .map(|target| (relation, target))
+        })
+        .collect()
+}
+
+/// Copies `config` with the foreign-key columns and join-table models its
+/// relations need. Running it on an already resolved config changes nothing.
+pub fn resolve_relations(config: &Config) -> Config {
+    let mut resolved = config.clone();
+    // (table, field, referenced model)
+    let mut foreign_keys = Vec::new();
+    for model in &config.models {
+        for (relation, target) in related(config, model) {
+            let foreign_key = relation.foreign_key(model, target);
+            match relation.kind {
+                RelationKind::BelongsTo => {
+                    foreign_keys.push((model.table_name.clone(), foreign_key, target.clone()))
+                }
+                RelationKind::HasMany => {
+                    foreign_keys.push((target.table_name.clone(), foreign_key, model.clone()))
+                }
+                RelationKind::ManyToMany => {
+                    let (table, name) = relation.join_model(model, target);
+                    if resolved.models.iter().all(|m| m.table_name != table) {
+                        resolved.models.push(Model {
+                            name,
+                            table_name: table.clone(),
+                            fields: vec![Field {
+                                name: "id".to_string(),
+                                field_type: "i32".to_string(),
+                                ..Field::default()
+                            }],
+                            ..Model::default()
+                        });
+                    }
+                    foreign_keys.push((table.clone(), foreign_key, model.clone()));
+                    foreign_keys.push((table, foreign_key_name(target), target.clone()));
+                }
+            }
+        }
+    }
+
+    for (table, field_name, target) in foreign_keys {
+        let Some(key) = target.primary_key() else {
+            continue;
+        };
+        let references = ForeignKey {
+            table: target.table_name.clone(),
+            column: key.column_name().to_string(),
+        };
+        let Some(model) = resolved.models.iter_mut().find(|m| m.table_name == table) else {
+            continue;
+        };
+        match model.fields.iter_mut().find(|f| f.name == field_name) {
+            Some(field) => {
+                field.references.get_or_insert(references);
+            }
+            None => model.fields.push(Field {
+                name: field_name,
+                field_type: key.field_type.clone(),
+                references: Some(references),
+                ..Field::default()
+            }),
+        }
+    }
+    resolved
+}
+
+/// Repository lookups for `model`: one per foreign-key field, plus one per
+/// `many_to_many` reaching it through a join table.
+pub fn lookups(config: &Config, model: &Model) -> Vec<Lookup> {
+    let mut lookups: Vec<Lookup> = model
+        .fields
+        .iter()
+        .filter(|f| f.references.is_some())
+        .map(|f| Lookup {
+            key: f.name.clone(),
+            column: f.column_name().to_string(),
+            key_type: unwrap_option(&f.field_type).0.to_string(),
+            join: None,
+        })
+        .collect();
+    for other in &config.models {
+        for (relation, target) in related(config, other) {
+            if relation.kind != RelationKind::ManyToMany {
+                continue;
+            }
+            let (table, _) = relation.join_model(other, target);
+            let owner_column = relation.foreign_key(other, target);
+            let target_column = foreign_key_name(target);
+            // Either side of the join table can be looked up by the other's key:
+            // (side, other side, join column pointing at side, join column filtered on)
+            let sides = [
+                (target, other, target_column.clone(), owner_column.clone()),
+                (other, target, owner_column, target_column),
+            ];
+            for (side, from, own_column, key) in sides {
+                if side.name != model.name || lookups.iter().any(|l| l.key == key) {
+                    continue;
+                }
+                if let Some(from_key) = from.primary_key() {
+                    lookups.push(Lookup {
+                        column: key.clone(),
+                        key,
+                        key_type: from_key.field_type,
+                        join: Some((table.clone(), own_column)),
+                    });
+                }
+            }
+        }
+    }
+    lookups
+}
+
+pub fn includes(config: &Config, model: &Model) -> Vec<Include> {
+    related(config, model)
+        .into_iter()
+        .map(|(relation, target)| Include {
+            name: relation.name(target),
+            kind: relation.kind,
+            target: target.clone(),
+            key: relation.foreign_key(model, target),
+        })
+        .collect()
+}
+
+pub fn nested_routes(config: &Config, model: &Model) -> Vec<NestedRoute> {
+    let Some(key) = model.primary_key() else {
+        return Vec::new();
+    };
+    // Nest under the model's own list route when it has one, guarded like it
+    let list = model
+        .endpoints
+        .iter()
+        .flatten()
+        .find(|e| e.method.eq_ignore_ascii_case("GET") && e.path_placeholders().is_empty());
+    let base = list.map_or_else(|| format!("/{}", model.table_name), |e| e.path.clone());
+    let auth = list.and_then(|e| e.auth);
+    related(config, model)
+        .into_iter()
+        .filter(|(relation, _)| relation.kind != RelationKind::BelongsTo)
+        .filter(|(relation, _)| relation.has_nested_route())
+        .map(|(relation, target)| {
+            let parameter = foreign_key_name(model);
+            NestedRoute {
+                endpoint: Endpoint {
+                    method: "GET".to_string(),
+                    path: format!(
+                        "{}/{{{}}}/{}",
+                        base.trim_end_matches('/'),
+                        parameter,
+                        relation.name(target)
+                    ),
+                    middlewares: None,
+                    auth,
+                    roles: None,
+                    owner_field: None,
+                    path_params: Some(vec![Field {
+                        name: parameter,
+                        field_type: key.field_type.clone(),
+                        ..Field::default()
+                    }]),
+                    body_params: None,
+                    query_params: None,
+                },
+                target: target.clone(),
+                key: relation.foreign_key(model, target),
+            }
+        })
+        .collect()
+}
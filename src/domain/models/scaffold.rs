@@ -0,0 +1,93 @@
+use convert_case::{Case, Casing};
+
+use crate::domain::models::config::{Endpoint, Field, Model};
+use crate::domain::models::database_import::{crud_endpoints, plural};
+
+/// What `add` put into a config, so only the files it touches are generated.
+pub struct Addition {
+    /// The new model, or the one given an endpoint.
+    pub model: String,
+    /// Whether the model, and so its table, is new.
+    pub new_table: bool,
+}
+
+/// Parses a `name:type` field spec from the command line, e.g. `total:f64` or
+/// `note:Option<String>`.
+pub fn parse_field(spec: &str) -> Result<Field, String> {
+    match spec.split_once(':') {
+        Some((name, field_type)) if !name.trim().is_empty() && !field_type.trim().is_empty() => {
+            Ok(Field {
+                name: name.trim().to_string(),
+                field_type: field_type.trim().to_string(),
+                ..Field::default()
+            })
+        }
+        _ => Err(format!("expected `name:type`, got '{}'", spec)),
+    }
+}
+
+/// The snake case plural of a model name, e.g. `OrderItem` -> `order_items`.
+pub fn table_name_for(model_name: &str) -> String {
+    plural(&model_name.to_case(Case::Snake))
+}
+
+/// A model with `fields`, keyed by `id: i32` unless one of them is the key,
+/// optionally with CRUD endpoints.
+pub fn new_model(name: &str, table_name: Option<&str>, fields: &[Field], crud: bool) -> Model {
+    let mut model = Model {
+        name: name.to_string(),
+        table_name: table_name.map_or_else(|| table_name_for(name), str::to_string),
+        fields: fields.to_vec(),
+        ..Model::default()
+    };
+    // The key goes first, as in hand-written configs
+    match model.primary_key() {
+        Some(key) => {
+            let index = model.fields.iter().position(|f| f.name == key.name);
+            let key = model.fields.remove(index.unwrap_or(0));
+            model.fields.insert(0, key);
+        }
+        None => model.fields.insert(
+            0,
+            Field {
+                name: "id".to_string(),
+                field_type: "i32".to_string(),
+                ..Field::default()
+            },
+        ),
+    }
+    if crud {
+        model.endpoints = Some(crud_endpoints(&model));
+    }
+    model
+}
+
+/// An endpoint of `model`, with a path parameter for each placeholder typed
+/// like the model field of the same name (`String` when there is none).
+pub fn new_endpoint(model: &Model, method: &str, path: &str) -> Endpoint {
+    let mut endpoint = Endpoint {
+        method: method.to_uppercase(),
+        path: path.to_string(),
+        ..Endpoint::default()
+    };
+    let path_params: Vec<Field> = endpoint
+        .path_placeholders()
+        .into_iter()
+        .map(|name| Field {
+            field_type: model
+                .fields
+                .iter()
+                .find(|f| f.name == name)
+                .map_or("String".to_string(), |f| f.field_type.clone()),
+            name,
+            ..Field::default()
+        })
+        .collect();
+    if !path_params.is_empty() {
+        endpoint.path_params = Some(path_params);
+    }
+    endpoint
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,17 @@
+use super::table_name_for;
+
+#[test]
+fn table_names_are_english_plurals() {
+    for (model, table) in [
+        ("Order", "orders"),
+        ("OrderItem", "order_items"),
+        ("Category", "categories"),
+        ("ApiKey", "api_keys"),
+        ("Address", "addresses"),
+        ("Box", "boxes"),
+        ("Match", "matches"),
+        ("Wish", "wishes"),
+    ] {
+        assert_eq!(table_name_for(model), table);
+    }
+}
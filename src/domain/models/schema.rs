@@ -0,0 +1,323 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::models::config::{Config, DefaultValue, Field};
+use crate::output::{is_integer_type, is_string_type, type_name, unwrap_option};
+
+/// The database schema as of the last generation, stored in the generated
+/// project so later runs can emit incremental migrations.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SchemaSnapshot {
+    pub tables: Vec<TableSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TableSnapshot {
+    pub table_name: String,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Clone, Debug)]
+pub enum SchemaChange {
+    CreateTable(TableSnapshot),
+    DropTable(String),
+    RenameTable {
+        from: String,
+        to: String,
+    },
+    AddColumn {
+        table: String,
+        field: Field,
+    },
+    DropColumn {
+        table: String,
+        field: Field,
+    },
+    AlterColumn {
+        table: String,
+        from: Field,
+        to: Field,
+    },
+    RenameColumn {
+        table: String,
+        from: String,
+        to: String,
+    },
+}
+
+impl SchemaSnapshot {
+    /// Tables of `config`, each after the tables its foreign keys reference.
+    pub fn from_config(config: &Config) -> Self {
+        let mut pending = config
+            .models
+            .iter()
+            .map(|model| TableSnapshot {
+                table_name: model.table_name.clone(),
+                fields: model.columns(),
+            })
+            .collect::<Vec<_>>();
+        let mut tables: Vec<TableSnapshot> = Vec::new();
+        while !pending.is_empty() {
+            let ready = pending
+                .iter()
+                .position(|table| {
+                    table
+                        .fields
+                        .iter()
+                        .filter_map(|f| f.references.as_ref())
+                        .all(|r| {
+                            r.table == table.table_name
+                                || tables.iter().any(|t| t.table_name == r.table)
+                                || pending.iter().all(|t| t.table_name != r.table)
+                        })
+                })
+                // Cycles keep their config order
+                .unwrap_or(0);
+            tables.push(pending.remove(ready));
+        }
+        Self { tables }
+    }
+
+    pub fn table(&self, table_name: &str) -> Option<&TableSnapshot> {
+        self.tables.iter().find(|t| t.table_name == table_name)
+    }
+
+    /// Computes the changes that turn `self` into the schema described by `config`.
+    /// `columns_differ` decides whether two versions of a column need an alter.
+    pub fn diff(
+        &self,
+        config: &Config,
+        columns_differ: impl Fn(&Field, &Field) -> bool,
+    ) -> Vec<SchemaChange> {
+        let mut changes = Vec::new();
+        let mut matched_tables = Vec::new();
+
+        // New tables are created after the tables they reference
+        let ordered = Self::from_config(config).tables;
+        let models = ordered.iter().filter_map(|table| {
+            config
+                .models
+                .iter()
+                .find(|m| m.table_name == table.table_name)
+        });
+        for model in models {
+            let previous = match self.table(&model.table_name) {
+                Some(previous) => Some(previous),
+                None => model
+                    .renamed_from
+                    .as_deref()
+                    .filter(|old| config.models.iter().all(|m| &m.table_name != old))
+                    .and_then(|old| self.table(old))
+                    .inspect(|previous| {
+                        changes.push(SchemaChange::RenameTable {
+                            from: previous.table_name.clone(),
+                            to: model.table_name.clone(),
+                        })
+                    }),
+            };
+            match previous {
+                Some(previous) => {
+                    matched_tables.push(previous.table_name.as_str());
+                    diff_columns(
+                        &model.table_name,
+                        &previous.fields,
+                        &model.columns(),
+                        &columns_differ,
+                        &mut changes,
+                    );
+                }
+                None => changes.push(SchemaChange::CreateTable(TableSnapshot {
+                    table_name: model.table_name.clone(),
+                    fields: model.columns(),
+                })),
+            }
+        }
+
+        // Tables are recorded after the ones they reference, so they are dropped first
+        for table in self.tables.iter().rev() {
+            if !matched_tables.contains(&table.table_name.as_str()) {
+                changes.push(SchemaChange::DropTable(table.table_name.clone()));
+            }
+        }
+        changes
+    }
+}
+
+/// Renames first, then drops, adds and alters, so that by the time a column is
+/// altered the table already has its final set of columns.
+fn diff_columns(
+    table: &str,
+    old_fields: &[Field],
+    new_fields: &[Field],
+    columns_differ: &impl Fn(&Field, &Field) -> bool,
+    changes: &mut Vec<SchemaChange>,
+) {
+    let mut renames = Vec::new();
+    let mut adds = Vec::new();
+    let mut alters = Vec::new();
+    let mut matched = Vec::new();
+
+    let is_new_column = |column: &str| new_fields.iter().all(|f| f.column_name() != column);
+    for field in new_fields {
+        let previous = old_fields
+            .iter()
+            .find(|old| old.column_name() == field.column_name())
+            .or_else(|| {
+                // A field whose `column` changed keeps its name, otherwise a hint is needed
+                old_fields
+                    .iter()
+                    .find(|old| old.name == field.name && is_new_column(old.column_name()))
+                    .or_else(|| {
+                        field
+                            .renamed_from
+                            .as_deref()
+                            .filter(|old| is_new_column(old))
+                            .and_then(|old| old_fields.iter().find(|f| f.column_name() == old))
+                    })
+                    .inspect(|previous| {
+                        renames.push(SchemaChange::RenameColumn {
+                            table: table.to_string(),
+                            from: previous.column_name().to_string(),
+                            to: field.column_name().to_string(),
+                        })
+                    })
+            });
+        match previous {
+            Some(previous) => {
+                matched.push(previous.column_name());
+                if columns_differ(previous, field) {
+                    alters.push(SchemaChange::AlterColumn {
+                        table: table.to_string(),
+                        from: previous.clone(),
+                        to: field.clone(),
+                    });
+                }
+            }
+            None => adds.push(SchemaChange::AddColumn {
+                table: table.to_string(),
+                field: field.clone(),
+            }),
+        }
+    }
+
+    let drops = old_fields
+        .iter()
+        .filter(|old| !matched.contains(&old.column_name()))
+        .map(|old| SchemaChange::DropColumn {
+            table: table.to_string(),
+            field: old.clone(),
+        });
+
+    changes.extend(renames);
+    changes.extend(drops);
+    changes.extend(adds);
+    changes.extend(alters);
+}
+
+/// Whether every row needs a value for `field`: it's `NOT NULL`, has no
+/// default and isn't filled in by the database as an auto-incremented key.
+pub fn requires_value(field: &Field) -> bool {
+    !unwrap_option(&field.field_type).1 && field.default.is_none() && !field.is_primary_key()
+}
+
+/// The zero value existing rows get when `field` is added as a `NOT NULL`
+/// column without a default, for the types that have an obvious one.
+pub fn backfill_default(field: &Field) -> Option<DefaultValue> {
+    let field_type = type_name(unwrap_option(&field.field_type).0);
+    match field_type.as_str() {
+        field_type if is_integer_type(field_type) => Some(DefaultValue::Integer(0)),
+        "f32" | "f64" => Some(DefaultValue::Float(0.0)),
+        "bool" => Some(DefaultValue::Bool(false)),
+        field_type if is_string_type(field_type) => Some(DefaultValue::Text(String::new())),
+        _ => None,
+    }
+}
+
+impl SchemaChange {
+    /// Whether applying the change can lose data or fail on existing rows.
+    /// Altering only a column's default or index is safe; changing its type,
+    /// length or uniqueness is not, nor is adding a `NOT NULL` column that has
+    /// no default to fill existing rows with.
+    pub fn is_destructive(&self) -> bool {
+        match self {
+            SchemaChange::DropTable(_) | SchemaChange::DropColumn { .. } => true,
+            // Rows already in the table would have nothing to put in the column
+            SchemaChange::AddColumn { field, .. } => {
+                requires_value(field) && backfill_default(field).is_none()
+            }
+            SchemaChange::AlterColumn { from, to, .. } => {
+                from.field_type != to.field_type
+                    || from.max_length != to.max_length
+                    || from.is_primary_key() != to.is_primary_key()
+                    || (to.is_unique() && !from.is_unique())
+            }
+            _ => false,
+        }
+    }
+
+    /// Short snake_case description, used to name single-change migrations.
+    pub fn slug(&self) -> String {
+        match self {
+            SchemaChange::CreateTable(table) => format!("create_{}", table.table_name),
+            SchemaChange::DropTable(table) => format!("drop_{}", table),
+            SchemaChange::RenameTable { from, to } => format!("rename_{}_to_{}", from, to),
+            SchemaChange::AddColumn { table, field } => {
+                format!("add_{}_to_{}", field.column_name(), table)
+            }
+            SchemaChange::DropColumn { table, field } => {
+                format!("drop_{}_from_{}", field.column_name(), table)
+            }
+            SchemaChange::AlterColumn { table, to, .. } => {
+                format!("alter_{}_in_{}", to.column_name(), table)
+            }
+            SchemaChange::RenameColumn { table, from, to } => {
+                format!("rename_{}_to_{}_in_{}", from, to, table)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaChange::CreateTable(table) => write!(f, "create table '{}'", table.table_name),
+            SchemaChange::DropTable(table) => write!(f, "drop table '{}'", table),
+            SchemaChange::RenameTable { from, to } => {
+                write!(f, "rename table '{}' to '{}'", from, to)
+            }
+            SchemaChange::AddColumn { table, field }
+                if requires_value(field) && backfill_default(field).is_none() =>
+            {
+                write!(
+                    f,
+                    "add column '{}.{}' (NOT NULL without a default, fails if '{}' has rows)",
+                    table,
+                    field.column_name(),
+                    table
+                )
+            }
+            SchemaChange::AddColumn { table, field } => {
+                write!(f, "add column '{}.{}'", table, field.column_name())
+            }
+            SchemaChange::DropColumn { table, field } => {
+                write!(f, "drop column '{}.{}'", table, field.column_name())
+            }
+            SchemaChange::AlterColumn { table, from, to } if from.field_type == to.field_type => {
+                write!(f, "alter column '{}.{}'", table, to.column_name())
+            }
+            SchemaChange::AlterColumn { table, from, to } => write!(
+                f,
+                "alter column '{}.{}' ({} -> {})",
+                table,
+                to.column_name(),
+                from.field_type,
+                to.field_type
+            ),
+            SchemaChange::RenameColumn { table, from, to } => {
+                write!(f, "rename column '{}.{}' to '{}'", table, from, to)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
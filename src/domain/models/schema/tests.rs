@@ -0,0 +1,204 @@
+use super::{SchemaChange, SchemaSnapshot};
+use crate::domain::models::config::{Config, DefaultValue, Field};
+use crate::output::{schema_migration_context, TemplateEngine};
+
+fn field(name: &str, field_type: &str) -> Field {
+    Field {
+        name: name.to_string(),
+        field_type: field_type.to_string(),
+        ..Field::default()
+    }
+}
+
+fn add_column(field: Field) -> SchemaChange {
+    SchemaChange::AddColumn {
+        table: "users".to_string(),
+        field,
+    }
+}
+
+/// `migration.sql.tmpl` rendered for `change` alone.
+fn migration(change: SchemaChange, database_type: &str) -> String {
+    let mut config = library("models = []");
+    config.database_type = database_type.to_string();
+    TemplateEngine::new(&config)
+        .render(
+            "migration.sql.tmpl",
+            schema_migration_context(&[change], &SchemaSnapshot::default(), database_type),
+        )
+        .unwrap()
+}
+
+#[test]
+fn not_null_columns_are_added_with_a_zero_default() {
+    for (field_type, default) in [
+        ("i32", "DEFAULT 0"),
+        ("f64", "DEFAULT 0"),
+        ("String", "DEFAULT ''"),
+    ] {
+        let change = add_column(field("age", field_type));
+        assert!(!change.is_destructive());
+        assert!(
+            migration(change, "sqlite").contains(&format!("NOT NULL {}", default)),
+            "{}",
+            field_type
+        );
+    }
+    assert!(migration(add_column(field("active", "bool")), "postgres")
+        .contains("\"active\" BOOLEAN NOT NULL DEFAULT FALSE"));
+}
+
+#[test]
+fn nullable_or_defaulted_columns_are_added_as_they_are() {
+    let sql = migration(add_column(field("nickname", "Option<String>")), "sqlite");
+    assert!(!sql.contains("DEFAULT"), "{}", sql);
+
+    let mut rank = field("rank", "i32");
+    rank.default = Some(DefaultValue::Integer(5));
+    let sql = migration(add_column(rank), "sqlite");
+    assert!(sql.contains("NOT NULL DEFAULT 5"), "{}", sql);
+}
+
+#[test]
+fn not_null_columns_without_a_zero_value_need_confirmation() {
+    let change = add_column(field("born_at", "DateTime<Utc>"));
+    assert!(change.is_destructive());
+    assert!(change.to_string().contains("fails if 'users' has rows"));
+    assert!(!add_column(field("born_at", "Option<DateTime<Utc>>")).is_destructive());
+}
+
+#[test]
+fn added_not_null_columns_accept_existing_rows() {
+    let config: Config = toml::from_str(
+        r#"
+project_name = "app"
+database_url = "sqlite://app.db"
+database_type = "sqlite"
+framework = "Axum"
+
+[[models]]
+name = "User"
+table_name = "users"
+fields = [{ name = "id", type = "i32" }, { name = "age", type = "i32" }]
+"#,
+    )
+    .unwrap();
+    let mut previous = SchemaSnapshot::from_config(&config);
+    previous.tables[0].fields.pop();
+    let changes = previous.diff(&config, |old, new| old != new);
+    assert!(matches!(
+        changes.as_slice(),
+        [SchemaChange::AddColumn { field, .. }] if field.name == "age"
+    ));
+    assert!(!changes[0].is_destructive());
+}
+
+fn library(models: &str) -> Config {
+    toml::from_str(&format!(
+        r#"
+project_name = "library"
+database_url = "sqlite://library.db"
+database_type = "sqlite"
+framework = "Axum"
+{}"#,
+        models
+    ))
+    .unwrap()
+}
+
+/// The slugs of the changes turning `old` into `new`, where a column differs
+/// when its type does.
+fn changes(old: &Config, new: &Config) -> Vec<String> {
+    SchemaSnapshot::from_config(old)
+        .diff(new, |old, new| old.field_type != new.field_type)
+        .iter()
+        .map(SchemaChange::slug)
+        .collect()
+}
+
+const AUTHORS: &str = r#"
+[[models]]
+name = "Author"
+table_name = "authors"
+fields = [
+    { name = "id", type = "i32" },
+    { name = "name", type = "String" },
+    { name = "bio", type = "String" },
+]
+"#;
+
+#[test]
+fn an_unchanged_config_has_no_changes() {
+    let config = library(AUTHORS);
+    assert_eq!(changes(&config, &config), Vec::<String>::new());
+}
+
+#[test]
+fn new_tables_are_created_after_the_tables_they_reference() {
+    let old = library("models = []");
+    let new = library(&format!(
+        r#"
+[[models]]
+name = "Book"
+table_name = "books"
+fields = [
+    {{ name = "id", type = "i32" }},
+    {{ name = "author_id", type = "i32", references = {{ table = "authors", column = "id" }} }},
+]
+{}"#,
+        AUTHORS
+    ));
+    assert_eq!(changes(&old, &new), ["create_authors", "create_books"]);
+    assert_eq!(changes(&new, &old), ["drop_books", "drop_authors"]);
+}
+
+#[test]
+fn renamed_tables_keep_their_columns() {
+    let old = library(AUTHORS);
+    let new = library(&AUTHORS.replace(
+        r#"table_name = "authors""#,
+        r#"table_name = "writers"
+renamed_from = "authors""#,
+    ));
+    assert_eq!(changes(&old, &new), ["rename_authors_to_writers"]);
+
+    // Without the hint the old table is dropped and a new one created
+    let new = library(&AUTHORS.replace("authors", "writers"));
+    assert_eq!(changes(&old, &new), ["create_writers", "drop_authors"]);
+}
+
+#[test]
+fn column_changes_are_renames_then_drops_adds_and_alters() {
+    let old = library(AUTHORS);
+    let new = library(&AUTHORS.replace(
+        r#"    { name = "name", type = "String" },
+    { name = "bio", type = "String" },"#,
+        r#"    { name = "full_name", type = "String", renamed_from = "name" },
+    { name = "born", type = "i32" },
+    { name = "id_card", type = "String", column = "card" },"#,
+    ));
+    assert_eq!(
+        changes(&old, &new),
+        [
+            "rename_name_to_full_name_in_authors",
+            "drop_bio_from_authors",
+            "add_born_to_authors",
+            "add_card_to_authors",
+        ]
+    );
+
+    let retyped = library(&AUTHORS.replace(
+        r#"{ name = "bio", type = "String" }"#,
+        r#"{ name = "bio", type = "Option<String>" }"#,
+    ));
+    assert_eq!(changes(&old, &retyped), ["alter_bio_in_authors"]);
+
+    let recolumned = library(&AUTHORS.replace(
+        r#"{ name = "bio", type = "String" }"#,
+        r#"{ name = "bio", type = "String", column = "biography" }"#,
+    ));
+    assert_eq!(
+        changes(&old, &recolumned),
+        ["rename_bio_to_biography_in_authors"]
+    );
+}
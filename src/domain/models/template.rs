@@ -1,588 +1,610 @@
 use crate::{
-    domain::models::config::{Config, Middleware, Model},
-    output::generate_handler,
+    domain::models::api_key::API_KEY_PATH,
+    domain::models::config::{Config, Field, Middleware, Model},
+    domain::models::relation::{includes, lookups, nested_routes},
+    output::{unwrap_option, EndpointView, IncludeView, NestedRouteView},
 };
 
 use super::config::Framework;
+use convert_case::{Case, Casing};
+use minijinja::{context, Value};
+use serde::Serialize;
+
+/// `(path, handler)` of the routes `[openapi] serve = true` adds, relative to `/api`.
+pub const OPENAPI_ROUTES: [(&str, &str); 2] =
+    [("/openapi.json", "openapi_json"), ("/docs", "swagger_ui")];
+/// `(path, handler)` of the routes `[auth]` adds, relative to `/api`.
+pub const AUTH_ROUTES: [(&str, &str); 2] = [("/auth/login", "login"), ("/auth/refresh", "refresh")];
 
 pub struct Template {
     pub config: Config,
 }
 
+/// A field as templates see it: `type` is the Rust type, `inner_type` the
+/// same without `Option`, and `column` the database column.
+#[derive(Serialize)]
+pub struct FieldView {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+    pub inner_type: String,
+    pub optional: bool,
+    pub column: String,
+    /// The column has a name of its own, see `Field::column`.
+    pub renamed: bool,
+    pub hidden: bool,
+    pub primary_key: bool,
+}
+
+impl From<&Field> for FieldView {
+    fn from(field: &Field) -> Self {
+        let (inner_type, optional) = unwrap_option(&field.field_type);
+        Self {
+            name: field.name.clone(),
+            field_type: field.field_type.clone(),
+            inner_type: inner_type.to_string(),
+            optional,
+            column: field.column_name().to_string(),
+            renamed: field.column_name() != field.name,
+            hidden: field.is_hidden(),
+            primary_key: field.is_primary_key(),
+        }
+    }
+}
+
+/// A model as templates see it, with its primary key resolved. `module` is the
+/// name of its files, e.g. `user` for `src/domain/models/user.rs`, and
+/// `snake_name` prefixes the functions generated for it.
+#[derive(Serialize)]
+pub struct ModelView {
+    pub name: String,
+    pub module: String,
+    pub snake_name: String,
+    pub table_name: String,
+    pub fields: Vec<FieldView>,
+    pub primary_key: FieldView,
+    pub response_dto: bool,
+}
+
+impl From<&Model> for ModelView {
+    fn from(model: &Model) -> Self {
+        Self {
+            name: model.name.clone(),
+            module: model.name.to_lowercase(),
+            snake_name: model.name.to_case(Case::Snake),
+            table_name: model.table_name.clone(),
+            fields: model.columns().iter().map(FieldView::from).collect(),
+            primary_key: FieldView::from(&primary_key(model)),
+            response_dto: model.response_dto.unwrap_or(false),
+        }
+    }
+}
+
 impl Template {
     pub fn new(config: Config) -> Self {
         Self { config }
     }
 
-    pub fn generate_model_content(&self, model: &Model) -> String {
-        let fields = model
-            .fields
-            .iter()
-            .map(|f| format!("    pub {}: {},", f.name, f.field_type))
-            .collect::<Vec<_>>()
-            .join("\n");
-        format!(
-            "use serde::{{Deserialize, Serialize}};\n\
-             use prkorm::Table;\n\
-             use sqlx::FromRow;\n\n\
-             #[derive(Debug, Serialize, Deserialize, Table, Default, FromRow)]\n\
-             #[table_name(\"{}\")]\n\
-             #[primary_key(\"id\")]\n\
-             pub struct {} {{\n\
-             {}\n\
-             }}",
-            model.table_name, model.name, fields
-        )
-    }
-
-    pub fn generate_router(&self) -> String {
-        match self.config.framework {
-            Framework::Axum => self.generate_axum_router(),
-            Framework::ActixWeb => self.generate_actix_router(),
+    /// Context of `model.rs.tmpl`: the request, query and response structs of `model`.
+    pub fn model_context(&self, model: &Model) -> Value {
+        let views = |fields: &[Field]| fields.iter().map(FieldView::from).collect::<Vec<_>>();
+        context! {
+            model => ModelView::from(model),
+            create_fields => views(&create_request_fields(model)),
+            update_fields => views(&update_request_fields(model)),
+            patch_fields => views(&patch_request_fields(model)),
+            query_fields => views(&query_param_fields(model)),
+            includes => !includes(&self.config, model).is_empty(),
         }
     }
 
-    fn generate_axum_router(&self) -> String {
-        let mut routes = String::new();
+    /// `(path, method, handler, middleware functions)` of every route under
+    /// `/api`, in registration order.
+    fn routes(&self) -> Vec<(String, String, String, Vec<String>)> {
+        let mut routes = Vec::new();
         for model in &self.config.models {
-            if let Some(endpoints) = &model.endpoints {
-                for endpoint in endpoints {
-                    let method = endpoint.method.to_lowercase();
-                    let path = &endpoint.path;
-                    let middlewares =  endpoint.middlewares.clone().unwrap_or_default().iter().map(|f| f.to_lowercase().replace("middleware", "_middleware").to_string()).map(|f| format!(".layer(axum::middleware::from_fn_with_state(state.clone(), super::{}::{}))", &f, &f)).collect::<String>();
-                    let handler = format!(
-                        "{}{}",
-                        method,
-                        path.replace("/", "_")
-                            .replace(":", "")
-                            .replace("{", "by_")
-                            .replace("}", "")
-                    );
-                    routes.push_str(&format!(
-                        "    .route(\"{}\", {}({}){})\n",
-                        path, method, handler, middlewares
-                    ));
-                }
+            for endpoint in model.endpoints.iter().flatten() {
+                routes.push((
+                    endpoint.path.clone(),
+                    endpoint.method.to_lowercase(),
+                    endpoint.handler_name(),
+                    endpoint.middleware_functions(),
+                ));
+            }
+            for route in nested_routes(&self.config, model) {
+                routes.push((
+                    route.endpoint.path.clone(),
+                    "get".to_string(),
+                    route.endpoint.handler_name(),
+                    Vec::new(),
+                ));
+            }
+        }
+        let mut push = |path: &str, method: &str, handler: String| {
+            routes.push((path.to_string(), method.to_string(), handler, Vec::new()))
+        };
+        if self.config.auth.is_some() {
+            for (path, handler) in AUTH_ROUTES {
+                push(path, "post", format!("super::auth::{}", handler));
             }
         }
+        if self.config.serves_openapi() {
+            for (path, handler) in OPENAPI_ROUTES {
+                push(path, "get", format!("super::openapi::{}", handler));
+            }
+        }
+        if self.config.api_keys.is_some() {
+            push(
+                API_KEY_PATH,
+                "post",
+                "super::api_keys::issue_api_key".to_string(),
+            );
+        }
         routes
     }
 
-    fn generate_actix_router(&self) -> String {
-        let mut routes = String::new();
-        for model in &self.config.models {
-            if let Some(endpoints) = &model.endpoints {
-                for endpoint in endpoints {
-                    let method = match endpoint.method.to_lowercase().as_str() {
-                        "get" => "web::get",
-                        "post" => "web::post",
-                        "put" => "web::put",
-                        "delete" => "web::delete",
-                        _ => "web::route",
-                    };
-                    let path = &endpoint.path;
-                    let handler = format!(
-                        "{}{}",
-                        endpoint.method.to_lowercase(),
-                        path.replace("/", "_")
-                            .replace(":", "")
-                            .replace("{", "by_")
-                            .replace("}", "")
-                    );
-                    routes.push_str(&format!(
-                        "    .route(\"{}\", {}().to({}))\n",
-                        path, method, handler
-                    ));
-                }
+    fn axum_routes(&self) -> Vec<Value> {
+        self.routes()
+            .into_iter()
+            .map(|(path, method, handler, middlewares)| {
+                context! { path, method, handler, middlewares, scope => () }
+            })
+            .collect()
+    }
+
+    /// Routes for actix, where those sharing a middleware list go into one
+    /// wrapped scope: an entry with a `scope` prefix and the `routes` under it.
+    fn actix_routes(&self) -> Vec<Value> {
+        let routes = self.routes();
+        // Routes sharing a middleware list go into one wrapped scope, as long as
+        // the scope's prefix doesn't also cover a route outside the group
+        let mut scopes: Vec<(String, Vec<usize>)> = Vec::new();
+        for (index, (_, _, _, middlewares)) in routes.iter().enumerate() {
+            if middlewares.is_empty() || scopes.iter().any(|(_, group)| group.contains(&index)) {
+                continue;
+            }
+            let group = (index..routes.len())
+                .filter(|&other| &routes[other].3 == middlewares)
+                .collect::<Vec<_>>();
+            if group.len() < 2 {
+                continue;
+            }
+            let prefix = common_prefix(group.iter().map(|&i| routes[i].0.as_str()));
+            let covers_other = routes
+                .iter()
+                .enumerate()
+                .any(|(i, (path, _, _, _))| !group.contains(&i) && path_within(path, &prefix));
+            if !prefix.is_empty() && !covers_other {
+                scopes.push((prefix, group));
             }
         }
-        routes
-    }
 
-    pub fn generate_middleware_content(&self, middleware: &Middleware, _db_type: &str) -> String {
-        let model_lower = middleware.model.to_lowercase();
-        match self.config.framework {
-            Framework::Axum => format!(
-                "use crate::adapters::http::http::AppState;\n\
-                 use crate::domain::error::AppError;\n\
-                 use axum::{{extract::{{State, Request}}, middleware::Next, response::Response}};\n\n\
-                 pub async fn {}_middleware(\n\
-                     State(state): State<AppState>,\n\
-                     request: Request,\n\
-                     next: Next,\n\
-                 ) -> Result<Response, AppError> {{\n\
-                     let service = state.{}_service;\n\
-                     tracing::info!(\"Processing request for {} model\");\n\
-                     let response = next.run(request).await;\n\
-                     Ok(response)\n\
-                 }}",
-                model_lower, model_lower, middleware.model
-            ),
-            Framework::ActixWeb => format!(
-                "use crate::adapters::http::http::AppState;
-                use actix_web::{{
-                        body::BoxBody,
-                        dev::{{ ServiceRequest, ServiceResponse}},
-                        middleware::Next,
-                        web, Error,
-                    }};
-                 pub async fn {}_middleware(\n\
-                     req: ServiceRequest,\n\
-                     next: Next<BoxBody>,\n\
-                 ) -> Result<ServiceResponse, Error> {{\n\
-                     let state = req.app_data::<web::Data<AppState>>().unwrap();\n\
-                     let service = state.{}_service.clone();\n\
-                     tracing::info!(\"Processing request for {} model\");\n\
-                     let res = next.call(req).await?;\n\
-                     Ok(res)\n\
-                 }}",
-                model_lower, model_lower, middleware.model
-            ),
+        let mut entries = Vec::new();
+        for (index, (path, method, handler, middlewares)) in routes.iter().enumerate() {
+            match scopes.iter().find(|(_, group)| group.contains(&index)) {
+                Some((prefix, group)) if group[0] == index => {
+                    let scoped: Vec<Value> = group
+                        .iter()
+                        .map(|&i| {
+                            let (path, method, handler, _) = &routes[i];
+                            context! { path => &path[prefix.len()..], method, handler }
+                        })
+                        .collect();
+                    entries.push(context! { scope => prefix, middlewares, routes => scoped });
+                }
+                Some(_) => {}
+                None => entries.push(context! { path, method, handler, middlewares, scope => () }),
+            }
         }
+        entries
     }
 
-    pub fn generate_repository_trait(&self, model: &Model) -> String {
-        let model_name = &model.name;
-        format!(
-            "use std::future::Future;\n\
-             use crate::domain::models::{}::{};\n\
-             use crate::domain::error::AppError;\n\n\
-             pub trait {}Repository: Send + Sync + 'static {{\n\
-                 fn find_all(&self) -> impl Future<Output = Result<Vec<{}>, AppError>> + Send;\n\
-                 fn find_by_id(&self, id: i32) -> impl Future<Output = Result<Option<{}>, AppError>> + Send;\n\
-                 fn create(&self, body: {}) -> impl Future<Output = Result<{}, AppError>> + Send;\n\
-             }}",
-            model_name.to_lowercase(), model_name, model_name, model_name, model_name, model_name, model_name
-        )
+    /// Whether any handler takes the `AuthUser` extractor.
+    fn uses_auth_user(&self) -> bool {
+        self.config.auth.is_some()
+            && self
+                .config
+                .models
+                .iter()
+                .flat_map(|m| m.endpoints.iter().flatten())
+                .any(|e| e.requires_auth())
     }
 
-    pub fn generate_repository_impl(&self, model: &Model, database_type: &str) -> String {
-        let model_name = &model.name;
-        let pool_type = match database_type.to_lowercase().as_str() {
-            "mysql" => "sqlx::MySqlPool",
-            "postgres" => "sqlx::PgPool",
-            "sqlite" => "sqlx::SqlitePool",
-            _ => panic!("Unsupported database type: {}", database_type),
-        };
-        format!(
-            "use crate::domain::models::{}::{};\n\
-             use crate::domain::ports::{}_repository::{}Repository;\n\
-             use crate::domain::error::AppError;\n\
-             use sqlx::{};\n\n\
-             #[derive(Clone)]\n\
-             pub struct Sqlx{}Repository {{\n\
-                 pool: {},\n\
-             }}\n\n\
-             impl Sqlx{}Repository {{\n\
-                 pub fn new(pool: {}) -> Self {{\n\
-                     Self {{ pool }}\n\
-                 }}\n\
-             }}\n\n\
-             impl {}Repository for Sqlx{}Repository {{\n\
-                 async fn find_all(&self) -> Result<Vec<{}>, AppError> {{\n\
-                    let query = {}::select().build();\n\
-                       sqlx::query_as(&query).fetch_all(&self.pool).await.map_err(AppError::from) \n\
-                 }}\n\
-                 async fn find_by_id(&self, id: i32) -> Result<Option<{}>, AppError> {{\n\
-                    let query = {}::select().where_id(id).build();\n\
-                       sqlx::query_as(&query).fetch_optional(&self.pool).await.map_err(AppError::from) \n\
-                 }}\n\
-                 async fn create(&self, body: {}) -> Result<{}, AppError> {{\n\
-                    todo!()\n\
-                 }}\n\
-                 // Implement create, update, delete similarly\n\
-             }}",
-            model_name.to_lowercase(), model_name, model_name.to_lowercase(), model_name, database_type, model_name, pool_type,
-            model_name, pool_type, model_name, model_name, model_name,model_name, model_name, model_name, model_name, model_name
-        )
+    /// Context of `middleware.rs.tmpl`. `checks` pairs each `validate_header`
+    /// header with the source model field it must match; without any the
+    /// middleware only traces the request.
+    pub fn middleware_context(&self, middleware: &Middleware) -> Value {
+        let checks: Vec<Value> = middleware
+            .header_checks()
+            .iter()
+            .zip(self.header_fields(middleware))
+            .map(|(check, field)| {
+                context! {
+                    header_key => check.header_key,
+                    hashed => check.hashed,
+                    field => FieldView::from(&field),
+                }
+            })
+            .collect();
+        context! {
+            middleware,
+            function_name => middleware.function_name(),
+            source => middleware.source_model(),
+            lookup_name => header_lookup_name(&self.header_fields(middleware)),
+            checks,
+        }
     }
 
-    pub fn generate_service(&self, model: &Model) -> String {
-        let model_name = &model.name;
-        format!(
-            "use crate::domain::models::{}::{};\n\
-             use crate::domain::ports::{}_repository::{}Repository;\n\
-             use crate::domain::error::AppError;\n\n\
-             #[derive(Clone)]\n\
-             pub struct {}Service<R: {}Repository> {{\n\
-                 repo: R,\n\
-             }}\n\n\
-             impl<R: {}Repository> {}Service<R> {{\n\
-                 pub fn new(repo: R) -> Self {{\n\
-                     Self {{ repo }}\n\
-                 }}\n\n\
-                 pub async fn get_all(&self) -> Result<Vec<{}>, AppError> {{\n\
-                     self.repo.find_all().await\n\
-                 }}\n\
-                 pub async fn get_by_id(&self, id: i32) -> Result<Option<{}>, AppError> {{\n\
-                     self.repo.find_by_id(id).await\n\
-                 }}\n\
-                    pub async fn create(&self, body: {}) -> Result<{}, AppError> {{\n\
-                        self.repo.create(body).await\n\
-                    }}\n\
-                 // Add other methods as needed\n\
-             }}",
-            model_name.to_lowercase(),
-            model_name,
-            model_name.to_lowercase(),
-            model_name,
-            model_name,
-            model_name,
-            model_name,
-            model_name,
-            model_name,
-            model_name,
-            model_name,
-            model_name
-        )
+    /// Fields of the source model named by a middleware's `validate_header`, in order.
+    fn header_fields(&self, middleware: &Middleware) -> Vec<Field> {
+        let Some(model) = self
+            .config
+            .models
+            .iter()
+            .find(|m| m.name == middleware.source_model())
+        else {
+            return Vec::new();
+        };
+        middleware
+            .header_checks()
+            .iter()
+            .filter_map(|check| model.fields.iter().find(|f| f.name == check.model_field))
+            .cloned()
+            .collect()
     }
 
-    pub fn generate_error_content(&self) -> String {
-        match self.config.framework {
-            Framework::Axum => {
-                "use axum::{response::IntoResponse, http::StatusCode};\n\
-                 use thiserror::Error;\n\n\
-                 #[derive(Error, Debug)]\n\
-                 pub enum AppError {\n\
-                     #[error(\"Database error: {0}\")]\n\
-                     Database(#[from] sqlx::Error),\n\
-                     #[error(\"Not found: {0}\")]\n\
-                     NotFound(String),\n\
-                     #[error(\"Unauthorized: {0}\")]\n\
-                     Unauthorized(String),\n\
-                 }\n\n\
-                 impl IntoResponse for AppError {\n\
-                     fn into_response(self) -> axum::response::Response {\n\
-                         match self {\n\
-                             AppError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response(),\n\
-                             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg).into_response(),\n\
-                             AppError::Unauthorized(_) => (StatusCode::UNAUTHORIZED, self.to_string()).into_response(),\n\
-                         }\n\
-                     }\n\
-                 }".to_string()
+    /// Field sets middlewares and `[auth]` login look `model` up by, one
+    /// `find_one_by_*` method each.
+    fn find_one_lookups(&self, model: &Model) -> Vec<Vec<Field>> {
+        let mut lookups: Vec<Vec<Field>> = Vec::new();
+        for middleware in self.config.middlewares.iter().flatten() {
+            let fields = self.header_fields(middleware);
+            if middleware.source_model() == model.name
+                && !fields.is_empty()
+                && !lookups.contains(&fields)
+            {
+                lookups.push(fields);
             }
-            Framework::ActixWeb => {
-                "use actix_web::{error::Error as ActixError, HttpResponse, http::StatusCode};\n\
-                 use thiserror::Error;\n\n\
-                 #[derive(Error, Debug)]\n\
-                 pub enum AppError {\n\
-                     #[error(\"Database error: {0}\")]\n\
-                     Database(#[from] sqlx::Error),\n\
-                     #[error(\"Not found: {0}\")]\n\
-                     NotFound(String),\n\
-                     #[error(\"Unauthorized: {0}\")]\n\
-                     Unauthorized(String),\n\
-                 }\n\n\
-                 impl actix_web::error::ResponseError for AppError {\n\
-                     fn error_response(&self) -> HttpResponse {\n\
-                         match self {\n\
-                             AppError::Database(_) => HttpResponse::InternalServerError().body(self.to_string()),\n\
-                             AppError::NotFound(msg) => HttpResponse::NotFound().body(msg.clone()),\n\
-                             AppError::Unauthorized(_) => HttpResponse::Unauthorized().body(self.to_string()),\n\
-                         }\n\
-                     }\n\
-                 }".to_string()
+        }
+        if let Some(auth) = self
+            .config
+            .auth
+            .as_ref()
+            .filter(|a| a.user_model == model.name)
+        {
+            let username = model
+                .fields
+                .iter()
+                .filter(|f| f.name == auth.username_field())
+                .cloned()
+                .collect::<Vec<_>>();
+            if !username.is_empty() && !lookups.contains(&username) {
+                lookups.push(username);
             }
         }
+        lookups
     }
 
-    pub fn generate_http_content(&self) -> String {
-        match self.config.framework {
-            Framework::Axum => self.generate_axum_http_content(),
-            Framework::ActixWeb => self.generate_actix_http_content(),
+    /// Context of `repository_trait.rs.tmpl`.
+    pub fn repository_trait_context(&self, model: &Model) -> Value {
+        context! {
+            model => ModelView::from(model),
+            lookups => self.lookup_views(model),
+            find_one_lookups => self.find_one_lookup_views(model),
         }
     }
 
-    fn generate_axum_http_content(&self) -> String {
-        // Your existing Axum implementation (slightly adjusted for clarity)
-        let template = self;
-        let app_state_fields = template
-            .config
-            .models
+    /// `find_by_{key}` lookups of `model`, see `relation::lookups`.
+    fn lookup_views(&self, model: &Model) -> Vec<Value> {
+        lookups(&self.config, model)
             .iter()
-            .map(|model| {
-                let service_name = format!("{}_service", model.name.to_lowercase());
-                let repo_type = format!("Sqlx{}Repository", model.name);
-                format!(
-                    "    pub {}: Arc<services::{}Service<{}>>,\n",
-                    service_name, model.name, repo_type
-                )
-            })
-            .collect::<String>();
-
-        let app_state = format!(
-            "#[derive(Clone)]\n\
-             pub struct AppState {{\n\
-             {}\n\
-             }}",
-            app_state_fields
-        );
+            .map(|lookup| context! { key => lookup.key, key_type => lookup.key_type })
+            .collect()
+    }
 
-        let new_params = template
-            .config
-            .models
+    /// `find_one_by_{name}` lookups of `model`, one per set of header fields.
+    fn find_one_lookup_views(&self, model: &Model) -> Vec<Value> {
+        self.find_one_lookups(model)
             .iter()
-            .map(|m| {
-                let service_name = format!("{}_service", m.name.to_lowercase());
-                format!(
-                    "{}: services::{}Service<Sqlx{}Repository>",
-                    service_name, m.name, m.name
-                )
+            .map(|fields| {
+                context! {
+                    name => header_lookup_name(fields),
+                    fields => fields.iter().map(FieldView::from).collect::<Vec<_>>(),
+                }
             })
-            .collect::<Vec<_>>()
-            .join(", ");
+            .collect()
+    }
 
-        let state_fields = template
-            .config
-            .models
+    /// Context of `repository_impl.rs.tmpl`. Columns are unquoted; templates
+    /// quote them for the configured database with `quote_identifier`.
+    pub fn repository_impl_context(&self, model: &Model, database_type: &str) -> Value {
+        let (pool_type, database) = match database_type.to_lowercase().as_str() {
+            "mysql" => ("sqlx::MySqlPool", "MySql"),
+            "postgres" => ("sqlx::PgPool", "Postgres"),
+            "sqlite" => ("sqlx::SqlitePool", "Sqlite"),
+            _ => panic!("Unsupported database type: {}", database_type),
+        };
+        // Body parameters take their column from the model field of the same name
+        let views = |fields: Vec<Field>| {
+            fields
+                .iter()
+                .map(|f| FieldView {
+                    column: column_name(model, f).to_string(),
+                    ..FieldView::from(f)
+                })
+                .collect::<Vec<_>>()
+        };
+        let lookups: Vec<Value> = lookups(&self.config, model)
             .iter()
-            .map(|m| {
-                let service_name = format!("{}_service", m.name.to_lowercase());
-                format!("        {}: Arc::new({}),", service_name, service_name)
+            .map(|lookup| {
+                context! {
+                    key => lookup.key,
+                    key_type => lookup.key_type,
+                    column => lookup.column,
+                    join_table => lookup.join.as_ref().map(|(table, _)| table),
+                    join_column => lookup.join.as_ref().map(|(_, column)| column),
+                }
             })
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        let handlers: Vec<String> = template
-            .config
-            .models
+            .collect();
+        let find_one_lookups: Vec<Value> = self
+            .find_one_lookups(model)
             .iter()
-            .flat_map(|model| {
-                model.endpoints.clone().map(|endpoint| {
-                    endpoint
-                        .iter()
-                        .map(|endpoint| generate_handler(model, endpoint, Framework::Axum))
-                        .collect::<Vec<_>>()
-                })
+            .map(|fields| {
+                context! {
+                    name => header_lookup_name(fields),
+                    fields => views(fields.clone()),
+                }
             })
-            .flatten()
             .collect();
-        let handlers = handlers.join("\n\n");
-
-        let router = template.generate_axum_router();
-
-        format!(
-            r#"
-    use std::{{net::SocketAddr, sync::Arc}};
-    use anyhow::Context;
-    use serde::*;
-    use axum::{{routing::{{get, post}}, Router, http::StatusCode, extract::*}};
-    use tokio::net;
-    use tower_http::{{cors::CorsLayer, trace::TraceLayer}};
-    use crate::infrastructure::*;
-    use crate::domain::*;
-    use crate::application::services;
-    use crate::domain::error::AppError;
-    
-    #[derive(Debug, Clone, PartialEq, Eq)]
-    pub struct HttpServerConfig<'a> {{
-        pub port: &'a str,
-    }}
-    
-    {app_state}
-    
-    {handlers}
-    
-    pub struct HttpServer {{
-        router: Router,
-        listener: net::TcpListener,
-    }}
-    
-    impl HttpServer {{
-        pub async fn new(
-            {new_params},
-            config: HttpServerConfig<'_>,
-        ) -> anyhow::Result<Self> {{
-            let trace_layer = TraceLayer::new_for_http().make_span_with(
-                |request: &axum::extract::Request<_>| {{
-                    let uri = request.uri().to_string();
-                    tracing::info_span!("http_request", method = ?request.method(), uri)
-                }}
-            );
-    
-            let state = AppState {{
-    {state_fields}
-            }};
-    
-            let router = Router::new()
-                .route("/health", get(health_route))
-                .nest("/api", api_routes(state.clone()))
-                .layer(CorsLayer::permissive())
-                .layer(trace_layer)
-                .with_state(state);
-    
-            let addr = SocketAddr::from((
-                [0, 0, 0, 0, 0, 0, 0, 0],
-                config.port.parse::<u16>().unwrap_or(3000),
-            ));
-    
-            let listener = net::TcpListener::bind(&addr)
-                .await
-                .with_context(|| format!("failed to listen on port {{}}", config.port))?;
-    
-            Ok(Self {{ router, listener }})
-        }}
-    
-        pub async fn run(self) -> anyhow::Result<()> {{
-            tracing::info!("listening on {{}}", self.listener.local_addr().unwrap());
-            axum::serve(self.listener, self.router)
-                .await
-                .context("received error from running server")?;
-            Ok(())
-        }}
-    }}
-    
-    fn api_routes(state: AppState) -> Router<AppState> {{
-        Router::new()
-    {router}
-    }}
-    
-    async fn health_route() -> (StatusCode, &'static str) {{
-        (StatusCode::OK, "OK")
-    }}
-            "#
-        )
+        let filters: Vec<Value> = query_filters(model)
+            .into_iter()
+            .map(|(param, column, operator)| context! { param, column, operator })
+            .collect();
+        context! {
+            model => ModelView::from(model),
+            pool_type,
+            database,
+            // MySQL has no `RETURNING`, so writes are followed by a lookup instead
+            returning => database_type.to_lowercase() != "mysql",
+            create_fields => views(create_request_fields(model)),
+            update_fields => views(update_request_fields(model)),
+            patch_fields => views(patch_request_fields(model)),
+            filters,
+            lookups,
+            find_one_lookups,
+        }
     }
 
-    fn generate_actix_http_content(&self) -> String {
-        let template = self;
-        let app_state_fields = template
+    /// Context of `service.rs.tmpl`. `password` is the field hashed before it
+    /// is stored, for the `[auth]` user model, and `hash_*` say which requests
+    /// carry it.
+    pub fn service_context(&self, model: &Model) -> Value {
+        // Passwords of the `[auth]` user model are stored as argon2 hashes
+        let password = self
             .config
-            .models
-            .iter()
-            .map(|model| {
-                let service_name = format!("{}_service", model.name.to_lowercase());
-                let repo_type = format!("Sqlx{}Repository", model.name);
-                format!(
-                    "    pub {}: Arc<services::{}Service<{}>>,\n",
-                    service_name, model.name, repo_type
-                )
-            })
-            .collect::<String>();
+            .auth
+            .as_ref()
+            .filter(|auth| auth.user_model == model.name)
+            .map(|auth| auth.password_field());
+        let hashes = |fields: Vec<Field>| {
+            password.is_some_and(|password| fields.iter().any(|f| f.name == password))
+        };
+        context! {
+            model => ModelView::from(model),
+            max_page_size => model.max_page_size.unwrap_or(DEFAULT_MAX_PAGE_SIZE),
+            lookups => self.lookup_views(model),
+            find_one_lookups => self.find_one_lookup_views(model),
+            password,
+            hash_create => hashes(create_request_fields(model)),
+            hash_update => hashes(update_request_fields(model)),
+            hash_patch => hashes(patch_request_fields(model)),
+        }
+    }
 
-        let app_state = format!(
-            "#[derive(Clone)]\n\
-             pub struct AppState {{\n\
-             {}\n\
-             }}",
-            app_state_fields
-        );
+    /// Context of `http.rs.tmpl`. Every endpoint is rendered by
+    /// `handler.rs.tmpl`; `relations` holds, per model, the nested routes for
+    /// `nested_handler.rs.tmpl` and the `?include=` relations for `relations.rs.tmpl`.
+    pub fn http_context(&self) -> Value {
+        let framework = self.config.framework.clone();
+        let mut handlers = Vec::new();
+        let mut relations = Vec::new();
+        for model in &self.config.models {
+            let includes = includes(&self.config, model)
+                .iter()
+                .map(|include| IncludeView::new(model, include))
+                .collect::<Vec<_>>();
+            for endpoint in model.endpoints.iter().flatten() {
+                handlers.push(context! {
+                    model => ModelView::from(model),
+                    endpoint => EndpointView::new(model, endpoint),
+                    includes => &includes,
+                });
+            }
+            let nested_routes = nested_routes(&self.config, model)
+                .iter()
+                .map(|route| NestedRouteView::new(model, route))
+                .collect::<Vec<_>>();
+            if !nested_routes.is_empty() || !includes.is_empty() {
+                relations.push(context! {
+                    model => ModelView::from(model),
+                    nested_routes,
+                    includes,
+                });
+            }
+        }
+        context! {
+            models => self.config.models.iter().map(ModelView::from).collect::<Vec<_>>(),
+            handlers,
+            relations,
+            list_endpoint => self.has_list_endpoint(),
+            routes => match framework {
+                Framework::Axum => self.axum_routes(),
+                Framework::ActixWeb => self.actix_routes(),
+            },
+            auth_user => self.uses_auth_user(),
+        }
+    }
 
-        let new_params = template
-            .config
-            .models
-            .iter()
-            .map(|m| {
-                let service_name = format!("{}_service", m.name.to_lowercase());
-                format!(
-                    "{}: services::{}Service<Sqlx{}Repository>",
-                    service_name, m.name, m.name
-                )
+    /// Whether any model has a list endpoint, i.e. a `GET` without a path parameter.
+    fn has_list_endpoint(&self) -> bool {
+        self.config.models.iter().any(|model| {
+            model.endpoints.iter().flatten().any(|endpoint| {
+                endpoint.method.eq_ignore_ascii_case("get")
+                    && endpoint.path_placeholders().is_empty()
             })
-            .collect::<Vec<_>>()
-            .join(", ");
+        })
+    }
+}
 
-        let state_fields = template
-            .config
-            .models
-            .iter()
-            .map(|m| {
-                let service_name = format!("{}_service", m.name.to_lowercase());
-                format!("        {}: {},", service_name, service_name)
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
-        let self_state_fields = template
-            .config
-            .models
+/// Fields of `Create{Model}Request`: the POST endpoint's `body_params`, or every
+/// column the database doesn't assign itself.
+pub fn create_request_fields(model: &Model) -> Vec<Field> {
+    match model.body_params_for("POST") {
+        Some(params) => params.to_vec(),
+        None => model
+            .columns()
+            .into_iter()
+            .filter(|f| !(f.is_primary_key() && (f.is_auto_increment() || f.default.is_some())))
+            .collect(),
+    }
+}
+
+/// Fields of `Update{Model}Request`: the PUT endpoint's `body_params`, or every
+/// column except the primary key.
+pub fn update_request_fields(model: &Model) -> Vec<Field> {
+    match model.body_params_for("PUT") {
+        Some(params) => params.to_vec(),
+        None => model
+            .columns()
+            .into_iter()
+            .filter(|f| !f.is_primary_key())
+            .collect(),
+    }
+}
+
+/// Fields of `Patch{Model}Request`: the PATCH endpoint's `body_params`, or every
+/// column except the primary key.
+pub fn patch_request_fields(model: &Model) -> Vec<Field> {
+    match model.body_params_for("PATCH") {
+        Some(params) => params.to_vec(),
+        None => model
+            .columns()
+            .into_iter()
+            .filter(|f| !f.is_primary_key())
+            .collect(),
+    }
+}
+
+/// Longest run of leading static path segments shared by `paths`, e.g. `/todos`
+/// for `/todos` and `/todos/{id}`; empty when they share none.
+fn common_prefix<'a>(mut paths: impl Iterator<Item = &'a str>) -> String {
+    let Some(first) = paths.next() else {
+        return String::new();
+    };
+    let mut prefix = first
+        .split('/')
+        .skip(1)
+        .take_while(|segment| !segment.starts_with('{') && !segment.starts_with(':'))
+        .collect::<Vec<_>>();
+    for path in paths {
+        let shared = prefix
             .iter()
-            .map(|m| {
-                let service_name = format!("{}_service", m.name.to_lowercase());
-                format!("        {}: Arc::new(self.{}),", service_name, service_name)
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
+            .zip(path.split('/').skip(1))
+            .take_while(|(a, b)| a == &b)
+            .count();
+        prefix.truncate(shared);
+    }
+    prefix
+        .iter()
+        .map(|segment| format!("/{}", segment))
+        .collect()
+}
 
-        let handlers: Vec<String> = template
-            .config
-            .models
+/// Whether requests for `path` could fall under the scope `prefix`.
+fn path_within(path: &str, prefix: &str) -> bool {
+    let segments = path.split('/').skip(1).collect::<Vec<_>>();
+    let prefix = prefix.split('/').skip(1).collect::<Vec<_>>();
+    segments.len() >= prefix.len()
+        && segments.iter().zip(&prefix).all(|(segment, wanted)| {
+            segment == wanted || segment.starts_with('{') || segment.starts_with(':')
+        })
+}
+
+/// `token` or `user_id_and_token`, naming a `find_one_by_*` lookup.
+fn header_lookup_name(fields: &[Field]) -> String {
+    fields
+        .iter()
+        .map(|f| f.name.as_str())
+        .collect::<Vec<_>>()
+        .join("_and_")
+}
+
+/// The model's primary key. Validation rejects models without one before
+/// anything is generated.
+pub fn primary_key(model: &Model) -> Field {
+    model
+        .primary_key()
+        .unwrap_or_else(|| panic!("model '{}' has no primary key", model.name))
+}
+
+/// Column of the model field that `field` (e.g. a body parameter) refers to.
+fn column_name<'a>(model: &'a Model, field: &'a Field) -> &'a str {
+    model
+        .fields
+        .iter()
+        .find(|f| f.name == field.name)
+        .map_or(field.column_name(), Field::column_name)
+}
+
+/// `limit` cap for list endpoints of models that don't set `max_page_size`.
+pub const DEFAULT_MAX_PAGE_SIZE: i64 = 100;
+
+/// Range filters a query parameter can apply to a model field via its suffix, e.g. `age_gte`.
+const RANGE_SUFFIXES: &[(&str, &str)] =
+    &[("_gte", ">="), ("_lte", "<="), ("_gt", ">"), ("_lt", "<")];
+
+/// Query parameters declared across a model's endpoints, first declaration wins.
+pub fn query_param_fields(model: &Model) -> Vec<Field> {
+    let mut fields: Vec<Field> = Vec::new();
+    for param in model
+        .endpoints
+        .iter()
+        .flatten()
+        .flat_map(|e| e.query_params.iter().flatten())
+    {
+        if !fields.iter().any(|f| f.name == param.name) {
+            fields.push(param.clone());
+        }
+    }
+    fields
+}
+
+/// `(parameter, column, operator)` for every query parameter that filters on a
+/// model field: `field` for equality, `field_gte`/`_lte`/`_gt`/`_lt` for ranges.
+pub fn query_filters(model: &Model) -> Vec<(String, String, &'static str)> {
+    let column = |name: &str| {
+        model
+            .fields
             .iter()
-            .flat_map(|model| {
-                model.endpoints.clone().map(|endpoint| {
-                    endpoint
-                        .iter()
-                        .map(|endpoint| generate_handler(model, endpoint, Framework::ActixWeb))
-                        .collect::<Vec<_>>()
-                })
+            .find(|f| f.name == name)
+            .map(|f| f.column_name().to_string())
+    };
+    query_param_fields(model)
+        .into_iter()
+        .filter_map(|param| {
+            if let Some(column) = column(&param.name) {
+                return Some((param.name.clone(), column, "="));
+            }
+            RANGE_SUFFIXES.iter().find_map(|(suffix, operator)| {
+                param
+                    .name
+                    .strip_suffix(suffix)
+                    .and_then(column)
+                    .map(|column| (param.name.clone(), column, *operator))
             })
-            .flatten()
-            .collect();
-        let handlers = handlers.join("\n\n");
-
-        let router = template.generate_actix_router();
-
-        format!(
-            r#"
-    use actix_web::{{web, App, HttpResponse}};
-    use std::sync::Arc;
-    use anyhow::Context;
-    use crate::infrastructure::*;
-    use crate::domain::*;
-    use crate::application::services;
-    use crate::domain::error::AppError;
-    use serde::*;
-    
-    #[derive(Debug, Clone, PartialEq, Eq)]
-    pub struct HttpServerConfig {{
-        pub port: String,
-    }}
-    
-    {app_state}
-    
-    {handlers}
-    
-    pub struct HttpServer {{
-    {new_params},
-    config: HttpServerConfig
-    }}
-    
-    impl HttpServer {{
-
-        pub async fn new(
-        {new_params},
-        config: HttpServerConfig,
-        ) -> anyhow::Result<Self> {{
-            Ok(
-            Self {{config, {state_fields}}} 
-            )
-        }}
-
-        pub async fn run(self) -> anyhow::Result<()> {{
-            let state = web::Data::new(AppState {{
-    {self_state_fields}
-            }});
-    
-            actix_web::HttpServer::new(move || {{
-                App::new()
-                    .app_data(state.clone())
-                    .route("/health", web::get().to(health_route))
-                    .service(
-                        web::scope("/api")
-    {router}
-                    )
-            }})
-            .bind(format!("0.0.0.0:{{}}", self.config.port.parse::<u16>().unwrap_or(3000)))?
-            .run()
-            .await
-            .context("received error from running server")?;
-            Ok(())
-        }}
-    }}
-    
-    async fn health_route() -> impl actix_web::Responder {{
-        HttpResponse::Ok().body("OK")
-    }}
-            "#
-        )
-    }
+        })
+        .collect()
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,453 @@
+use super::Template;
+use crate::domain::models::api_key::resolve_api_keys;
+use crate::domain::models::config::Config;
+use crate::output::TemplateEngine;
+use minijinja::Value;
+
+/// A `Tag` keyed by its `String` slug, which is not `Copy`.
+fn config(database_type: &str) -> Config {
+    toml::from_str(&format!(
+        r#"
+project_name = "tags"
+database_url = "{database_type}://localhost/tags"
+database_type = "{database_type}"
+framework = "Axum"
+
+[[models]]
+name = "Tag"
+table_name = "tags"
+fields = [
+    {{ name = "slug", type = "String", primary_key = true }},
+    {{ name = "label", type = "String" }},
+]
+"#
+    ))
+    .unwrap()
+}
+
+fn render(config: &Config, name: &str, context: Value) -> String {
+    TemplateEngine::new(config).render(name, context).unwrap()
+}
+
+#[test]
+fn models_derive_clone_so_middlewares_can_hand_them_to_handlers() {
+    let config = config("postgres");
+    let context = Template::new(config.clone()).model_context(&config.models[0]);
+    let model = render(&config, "model.rs.tmpl", context);
+    assert!(
+        model.contains("#[derive(Debug, Clone, Serialize, Deserialize, Table, Default, FromRow)]\n#[table_name(\"tags\")]"),
+        "{}",
+        model
+    );
+}
+
+fn repository_impl(database_type: &str) -> String {
+    let config = config(database_type);
+    let context =
+        Template::new(config.clone()).repository_impl_context(&config.models[0], database_type);
+    render(&config, "repository_impl.rs.tmpl", context)
+}
+
+#[test]
+fn keyset_cursors_are_borrowed_so_string_keys_are_not_moved_out_of_the_query() {
+    let repository = repository_impl("postgres");
+    assert!(
+        repository.contains("if let Some(after) = &query.after {"),
+        "{}",
+        repository
+    );
+    assert!(
+        repository.contains(".push_bind(after.clone());"),
+        "{}",
+        repository
+    );
+}
+
+#[test]
+fn mysql_updates_bind_a_clone_of_the_key_they_read_the_row_back_by() {
+    let repository = repository_impl("mysql");
+    assert_eq!(
+        repository
+            .matches(" WHERE `slug` = \").push_bind(id.clone());")
+            .count(),
+        2,
+        "{}",
+        repository
+    );
+}
+
+#[test]
+fn nullable_patch_fields_tell_null_apart_from_missing() {
+    let mut config = config("postgres");
+    config.models[0].fields[1].field_type = "Option<String>".into();
+    let context = Template::new(config.clone()).model_context(&config.models[0]);
+    let model = render(&config, "model.rs.tmpl", context);
+    assert!(
+        model.contains(
+            "    #[serde(default, deserialize_with = \"double_option\")]\n    pub label: Option<Option<String>>,"
+        ),
+        "{}",
+        model
+    );
+    assert!(model.contains("fn double_option<'de, T, D>"), "{}", model);
+
+    let required = render(
+        &config,
+        "model.rs.tmpl",
+        Template::new(config.clone()).model_context(&{
+            let mut model = config.models[0].clone();
+            model.fields[1].field_type = "String".into();
+            model
+        }),
+    );
+    assert!(!required.contains("double_option"), "{}", required);
+}
+
+#[test]
+fn the_primary_key_and_column_names_come_from_the_field_attributes() {
+    let mut config = config("postgres");
+    config.models[0].fields[1].column = Some("tag_label".to_string());
+    let template = Template::new(config.clone());
+    let model = render(
+        &config,
+        "model.rs.tmpl",
+        template.model_context(&config.models[0]),
+    );
+    assert!(model.contains("#[primary_key(\"slug\")]"), "{}", model);
+    assert!(
+        model.contains("#[sqlx(rename = \"tag_label\")]\n    pub label: String,"),
+        "{}",
+        model
+    );
+
+    let repository = render(
+        &config,
+        "repository_trait.rs.tmpl",
+        template.repository_trait_context(&config.models[0]),
+    );
+    assert!(
+        repository.contains("fn find_by_id(&self, id: String)"),
+        "{}",
+        repository
+    );
+}
+
+/// A `ClientMiddleware` looking a `Client` up by its token and team headers.
+fn middleware_config(framework: &str) -> Config {
+    toml::from_str(&format!(
+        r#"
+project_name = "clients"
+database_url = "postgres://localhost/clients"
+database_type = "postgres"
+framework = "{framework}"
+
+[[models]]
+name = "Client"
+table_name = "clients"
+fields = [
+    {{ name = "id", type = "i32" }},
+    {{ name = "token", type = "String" }},
+    {{ name = "team_id", type = "i32" }},
+]
+endpoints = [
+    {{ method = "GET", path = "/clients", middlewares = ["ClientMiddleware"] }},
+    {{ method = "POST", path = "/clients", middlewares = ["ClientMiddleware"] }},
+    {{ method = "GET", path = "/health_checks" }},
+]
+
+[[middlewares]]
+model = "Client"
+validate_header = [
+    {{ model_field = "token", header_key = "x-token" }},
+    {{ model_field = "team_id", header_key = "x-team" }},
+]
+"#
+    ))
+    .unwrap()
+}
+
+fn middleware(framework: &str) -> String {
+    let config = middleware_config(framework);
+    let middleware = &config.middlewares.as_ref().unwrap()[0];
+    let context = Template::new(config.clone()).middleware_context(middleware);
+    render(&config, "middleware.rs.tmpl", context)
+}
+
+#[test]
+fn middlewares_look_the_record_up_by_the_validated_headers() {
+    let axum = middleware("Axum");
+    for expected in [
+        "let token = header_value(&request, \"x-token\")?.to_string();",
+        "let team_id = header_value(&request, \"x-team\")?.parse::<i32>().map_err(|_| AppError::Unauthorized(\"invalid header 'x-team'\".to_string()))?;",
+        "let record = state.client_service.get_one_by_token_and_team_id(token, team_id).await?\n        .ok_or_else(|| AppError::Unauthorized(\"no matching Client\".to_string()))?;",
+        "request.extensions_mut().insert(record);",
+    ] {
+        assert!(axum.contains(expected), "{}\n\n{}", expected, axum);
+    }
+
+    let actix = middleware("ActixWeb");
+    for expected in [
+        ".app_data::<web::Data<AppState>>()",
+        "let token = header_value(&req, \"x-token\")?.to_string();",
+        "req.extensions_mut().insert(record);\n    next.call(req).await",
+    ] {
+        assert!(actix.contains(expected), "{}\n\n{}", expected, actix);
+    }
+}
+
+#[test]
+fn the_source_repository_gets_a_lookup_for_the_headers() {
+    let config = middleware_config("Axum");
+    let context = Template::new(config.clone()).repository_trait_context(&config.models[0]);
+    let repository = render(&config, "repository_trait.rs.tmpl", context);
+    assert!(
+        repository.contains("fn find_one_by_token_and_team_id(&self, token: String, team_id: i32)"),
+        "{}",
+        repository
+    );
+}
+
+#[test]
+fn middlewares_without_header_checks_only_trace() {
+    let mut config = middleware_config("Axum");
+    config.middlewares.as_mut().unwrap()[0].validate_header = None;
+    let middleware = &config.middlewares.as_ref().unwrap()[0];
+    let context = Template::new(config.clone()).middleware_context(middleware);
+    let content = render(&config, "middleware.rs.tmpl", context);
+    assert!(
+        content
+            .contains("pub async fn client_middleware(request: Request, next: Next) -> Response {"),
+        "{}",
+        content
+    );
+    assert!(!content.contains("get_one_by"), "{}", content);
+}
+
+fn http(config: &Config) -> String {
+    let context = Template::new(config.clone()).http_context();
+    render(config, "http.rs.tmpl", context)
+}
+
+#[test]
+fn actix_routes_sharing_middlewares_are_wrapped_in_one_scope() {
+    let http = http(&middleware_config("ActixWeb"));
+    for expected in [
+        "web::scope(\"/clients\").wrap(actix_web::middleware::from_fn(super::client_middleware::client_middleware))\n",
+        ".route(\"\", web::get().to(get_clients))\n",
+        ".route(\"\", web::post().to(post_clients))\n",
+        ".route(\"/health_checks\", web::get().to(get_health_checks))\n",
+    ] {
+        assert!(http.contains(expected), "{}\n\n{}", expected, http);
+    }
+}
+
+#[test]
+fn a_lone_actix_route_is_wrapped_itself() {
+    let mut config = middleware_config("ActixWeb");
+    config.models[0].endpoints.as_mut().unwrap()[1].middlewares = None;
+    let http = http(&config);
+    assert!(
+        http.contains(".route(\"/clients\", web::get().to(get_clients).wrap(actix_web::middleware::from_fn(super::client_middleware::client_middleware)))"),
+        "{}",
+        http
+    );
+    assert!(!http.contains("web::scope(\"/clients\")"), "{}", http);
+}
+
+#[test]
+fn axum_routes_get_a_layer_per_middleware() {
+    let http = http(&middleware_config("Axum"));
+    assert!(
+        http.contains(".route(\"/clients\", post(post_clients).layer(axum::middleware::from_fn_with_state(state.clone(), super::client_middleware::client_middleware)))"),
+        "{}",
+        http
+    );
+}
+
+/// A `User` logging in with `[auth]`, and a `Todo` only they may list.
+fn auth_config(framework: &str) -> Config {
+    toml::from_str(&format!(
+        r#"
+project_name = "todos"
+database_url = "postgres://localhost/todos"
+database_type = "postgres"
+framework = "{framework}"
+
+[auth]
+user_model = "User"
+
+[[models]]
+name = "User"
+table_name = "users"
+fields = [
+    {{ name = "id", type = "i32" }},
+    {{ name = "email", type = "String", unique = true }},
+    {{ name = "password_hash", type = "String", hidden = true }},
+]
+endpoints = [{{ method = "POST", path = "/users" }}]
+
+[[models]]
+name = "Todo"
+table_name = "todos"
+fields = [
+    {{ name = "id", type = "i32" }},
+    {{ name = "title", type = "String" }},
+]
+endpoints = [{{ method = "GET", path = "/todos", auth = true }}]
+"#
+    ))
+    .unwrap()
+}
+
+#[test]
+fn auth_adds_the_login_and_refresh_routes() {
+    let axum = http(&auth_config("Axum"));
+    for expected in [
+        ".route(\"/auth/login\", post(super::auth::login))",
+        ".route(\"/auth/refresh\", post(super::auth::refresh))",
+        "use super::auth::AuthUser;",
+    ] {
+        assert!(axum.contains(expected), "{}\n\n{}", expected, axum);
+    }
+    let actix = http(&auth_config("ActixWeb"));
+    for expected in [
+        ".route(\"/auth/login\", web::post().to(super::auth::login))",
+        ".route(\"/auth/refresh\", web::post().to(super::auth::refresh))",
+        "use super::auth::AuthUser;",
+    ] {
+        assert!(actix.contains(expected), "{}\n\n{}", expected, actix);
+    }
+}
+
+#[test]
+fn the_auth_user_is_only_imported_with_auth() {
+    let http = http(&middleware_config("Axum"));
+    assert!(!http.contains("AuthUser"), "{}", http);
+    assert!(!http.contains("/auth/login"), "{}", http);
+}
+
+#[test]
+fn the_user_service_hashes_passwords_before_storing_them() {
+    let config = auth_config("Axum");
+    let template = Template::new(config.clone());
+    let users = render(
+        &config,
+        "service.rs.tmpl",
+        template.service_context(&config.models[0]),
+    );
+    assert!(
+        users.contains("body.password_hash = hash_password(&body.password_hash)?;"),
+        "{}",
+        users
+    );
+    let todos = render(
+        &config,
+        "service.rs.tmpl",
+        template.service_context(&config.models[1]),
+    );
+    assert!(!todos.contains("hash_password"), "{}", todos);
+}
+
+#[test]
+fn the_user_repository_can_find_a_user_by_the_username_field() {
+    let config = auth_config("Axum");
+    let context =
+        Template::new(config.clone()).repository_impl_context(&config.models[0], "postgres");
+    let repository = render(&config, "repository_impl.rs.tmpl", context);
+    assert!(repository.contains("find_one_by_email"), "{}", repository);
+}
+
+/// `[api_keys]` guarding `GET /invoices`, resolved like the CLI does.
+fn api_key_config(framework: &str) -> Config {
+    let config: Config = toml::from_str(&format!(
+        r#"
+project_name = "billing"
+database_url = "postgres://localhost/billing"
+database_type = "postgres"
+framework = "{framework}"
+
+[auth]
+user_model = "User"
+
+[api_keys]
+admin_roles = ["admin"]
+
+[[models]]
+name = "User"
+table_name = "users"
+fields = [
+    {{ name = "id", type = "i32" }},
+    {{ name = "email", type = "String", unique = true }},
+    {{ name = "password_hash", type = "String", hidden = true }},
+    {{ name = "role", type = "String" }},
+]
+
+[[models]]
+name = "Invoice"
+table_name = "invoices"
+fields = [{{ name = "id", type = "i32" }}]
+endpoints = [{{ method = "GET", path = "/invoices", middlewares = ["ApiKeyMiddleware"] }}]
+"#
+    ))
+    .unwrap();
+    resolve_api_keys(&config)
+}
+
+#[test]
+fn the_api_key_middleware_looks_keys_up_by_their_hash() {
+    let config = api_key_config("Axum");
+    let middleware = &config.middlewares.as_ref().unwrap()[0];
+    let context = Template::new(config.clone()).middleware_context(middleware);
+    let middleware = render(&config, "middleware.rs.tmpl", context);
+    for expected in [
+        "use crate::application::api_keys::hash_api_key;",
+        "let key_hash = hash_api_key(header_value(&request, \"X-API-Key\")?);",
+        "state.apikey_service.get_one_by_key_hash(key_hash).await?",
+    ] {
+        assert!(
+            middleware.contains(expected),
+            "{}\n\n{}",
+            expected,
+            middleware
+        );
+    }
+}
+
+#[test]
+fn api_key_routes_are_wired() {
+    let axum = http(&api_key_config("Axum"));
+    for expected in [
+        ".route(\"/invoices\", get(get_invoices).layer(axum::middleware::from_fn_with_state(state.clone(), super::apikey_middleware::apikey_middleware)))",
+        ".route(\"/api_keys\", get(get_api_keys))",
+        ".route(\"/api_keys/{id}\", delete(delete_api_keys_by_id))",
+        ".route(\"/api_keys\", post(super::api_keys::issue_api_key))",
+    ] {
+        assert!(axum.contains(expected), "{}\n\n{}", expected, axum);
+    }
+    let actix = http(&api_key_config("ActixWeb"));
+    for expected in [
+        ".route(\"/invoices\", web::get().to(get_invoices).wrap(actix_web::middleware::from_fn(super::apikey_middleware::apikey_middleware)))",
+        ".route(\"/api_keys\", web::post().to(super::api_keys::issue_api_key))",
+    ] {
+        assert!(actix.contains(expected), "{}\n\n{}", expected, actix);
+    }
+}
+
+#[test]
+fn only_admins_may_manage_api_keys() {
+    let config = api_key_config("Axum");
+    let handlers = render(&config, "api_key_handlers.rs.tmpl", minijinja::context! {});
+    assert!(handlers.contains("user: AuthUser"), "{}", handlers);
+    assert!(
+        handlers.contains("user.require_role(&[\"admin\"])?;"),
+        "{}",
+        handlers
+    );
+    let http = http(&config);
+    assert_eq!(
+        http.matches("user.require_role(&[\"admin\"])?;").count(),
+        2,
+        "{}",
+        http
+    );
+}
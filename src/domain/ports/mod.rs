@@ -1,2 +1,3 @@
 pub mod file_system;
 pub mod project_generator;
+pub mod schema_introspector;
@@ -1,7 +1,14 @@
 use std::error::Error;
 
 use crate::domain::models::config::Config;
+use crate::domain::models::manifest::GenerationReport;
 
 pub trait ProjectGenerator: Send + Sync {
-    async fn generate_project(&self, config: Config) -> Result<(), Box<dyn Error>>;
+    /// Generates the project described by `config` into the `out_dir` directory,
+    /// reporting what happened to each file.
+    async fn generate_project(
+        &self,
+        config: Config,
+        out_dir: &str,
+    ) -> Result<GenerationReport, Box<dyn Error>>;
 }
@@ -0,0 +1,8 @@
+use std::error::Error;
+
+use crate::domain::models::database_import::TableSchema;
+
+pub trait SchemaIntrospector: Send + Sync {
+    /// Reads the tables, columns and keys of the database at `database_url`.
+    async fn tables(&self, database_url: &str) -> Result<Vec<TableSchema>, Box<dyn Error>>;
+}
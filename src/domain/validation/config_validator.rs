@@ -0,0 +1,868 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::domain::models::api_key::{API_KEY_MODEL, API_KEY_PATH, API_KEY_TABLE};
+use crate::domain::models::config::{Config, DefaultValue, Endpoint, Field, Middleware, Model};
+use crate::domain::models::keywords::RUST_KEYWORDS;
+use crate::domain::models::relation::{nested_routes, RelationKind};
+use crate::domain::models::template::{
+    create_request_fields, patch_request_fields, update_request_fields, AUTH_ROUTES, OPENAPI_ROUTES,
+};
+use crate::domain::validation::diagnostic::{Diagnostic, PathSegment};
+use crate::output::{is_integer_type, is_string_type, unwrap_option};
+
+pub const SUPPORTED_DATABASE_TYPES: &[&str] = &["postgres", "mysql", "sqlite"];
+pub const SUPPORTED_METHODS: &[&str] = &["GET", "POST", "PUT", "PATCH", "DELETE"];
+/// Lowercased model names that would collide with generated modules.
+pub const RESERVED_MODEL_NAMES: &[&str] = &["page"];
+/// Generated into every `{Model}QueryParams`, so endpoints can't declare them again.
+pub const RESERVED_QUERY_PARAMS: &[&str] = &["sort", "limit", "offset", "after", "include"];
+
+/// Why `name` can't be the package name in the generated `Cargo.toml`, if it can't.
+pub fn crate_name_error(name: &str) -> Option<String> {
+    let invalid = name
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && !matches!(c, '-' | '_'));
+    if name.is_empty() {
+        Some("the project name can't be empty".to_string())
+    } else if let Some(invalid) = invalid {
+        Some(format!(
+            "'{}' is not a valid crate name: use letters, digits, '-' and '_', not {:?}",
+            name, invalid
+        ))
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        Some(format!(
+            "'{}' is not a valid crate name: it can't start with a digit",
+            name
+        ))
+    } else if RUST_KEYWORDS.contains(&name) {
+        Some(format!(
+            "'{}' is not a valid crate name: it is a Rust keyword",
+            name
+        ))
+    } else {
+        None
+    }
+}
+
+/// Checks a parsed `Config` for mistakes that would otherwise surface as
+/// panics or as a generated project that doesn't compile. Every problem is
+/// collected; an empty result means the config is valid.
+pub fn validate_config(config: &Config) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    if let Some(error) = crate_name_error(&config.project_name) {
+        diagnostics.push(Diagnostic::new(vec!["project_name".into()], error));
+    }
+    if !SUPPORTED_DATABASE_TYPES.contains(&config.database_type.to_lowercase().as_str()) {
+        diagnostics.push(Diagnostic::new(
+            vec!["database_type".into()],
+            format!(
+                "unsupported database type '{}' (expected one of: {})",
+                config.database_type,
+                SUPPORTED_DATABASE_TYPES.join(", ")
+            ),
+        ));
+    }
+
+    let mut model_names = HashSet::new();
+    let mut handler_names = HashMap::new();
+    for (model_index, model) in config.models.iter().enumerate() {
+        let model_path = vec!["models".into(), model_index.into()];
+        if RESERVED_MODEL_NAMES.contains(&model.name.to_lowercase().as_str()) {
+            diagnostics.push(Diagnostic::new(
+                with(&model_path, &["name".into()]),
+                format!(
+                    "model name '{}' clashes with a generated module",
+                    model.name
+                ),
+            ));
+        }
+        if model.max_page_size.is_some_and(|size| size < 1) {
+            diagnostics.push(Diagnostic::new(
+                with(&model_path, &["max_page_size".into()]),
+                "`max_page_size` must be at least 1",
+            ));
+        }
+        if !model_names.insert(model.name.as_str()) {
+            diagnostics.push(Diagnostic::new(
+                with(&model_path, &["name".into()]),
+                format!("duplicate model name '{}'", model.name),
+            ));
+        }
+        validate_model(
+            model,
+            &model_path,
+            &config.database_type,
+            &mut handler_names,
+            &mut diagnostics,
+        );
+        validate_relations(
+            config,
+            model,
+            &model_path,
+            &mut handler_names,
+            &mut diagnostics,
+        );
+    }
+
+    let mut middleware_names = HashSet::new();
+    for (index, middleware) in config.middlewares.iter().flatten().enumerate() {
+        let middleware_path = vec!["middlewares".into(), index.into()];
+        if !middleware_names.insert(middleware.function_name()) {
+            diagnostics.push(Diagnostic::new(
+                with(&middleware_path, &["model".into()]),
+                format!("duplicate middleware for model '{}'", middleware.model),
+            ));
+        }
+        validate_middleware(config, middleware, &middleware_path, &mut diagnostics);
+    }
+
+    validate_auth(config, &mut diagnostics);
+    validate_api_keys(config, &mut diagnostics);
+    validate_openapi(config, &mut diagnostics);
+
+    let api_key_middleware = config.api_keys.as_ref().map(|_| Middleware {
+        model: API_KEY_MODEL.to_string(),
+        select_from_model: None,
+        validate_header: None,
+    });
+    let known_middlewares = config
+        .middlewares
+        .iter()
+        .flatten()
+        .chain(api_key_middleware.as_ref())
+        .map(|m| (m.function_name(), format!("{}Middleware", m.model)))
+        .collect::<Vec<_>>();
+    for (model_index, model) in config.models.iter().enumerate() {
+        for (index, endpoint) in model.endpoints.iter().flatten().enumerate() {
+            let names = endpoint.middlewares.iter().flatten();
+            for ((name_index, name), function) in
+                names.enumerate().zip(endpoint.middleware_functions())
+            {
+                if known_middlewares
+                    .iter()
+                    .all(|(known, _)| known != &function)
+                {
+                    let expected = if known_middlewares.is_empty() {
+                        "no `[[middlewares]]` are declared".to_string()
+                    } else {
+                        format!(
+                            "expected one of: {}",
+                            known_middlewares
+                                .iter()
+                                .map(|(_, name)| name.as_str())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )
+                    };
+                    diagnostics.push(Diagnostic::new(
+                        vec![
+                            "models".into(),
+                            model_index.into(),
+                            "endpoints".into(),
+                            index.into(),
+                            "middlewares".into(),
+                            name_index.into(),
+                        ],
+                        format!("unknown middleware '{}' ({})", name, expected),
+                    ));
+                }
+            }
+        }
+    }
+
+    diagnostics
+}
+
+fn validate_model(
+    model: &Model,
+    model_path: &[PathSegment],
+    database_type: &str,
+    handler_names: &mut HashMap<String, String>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let fields_path = with(model_path, &["fields".into()]);
+    validate_fields(&model.fields, &fields_path, diagnostics);
+    validate_columns(model, &fields_path, database_type, diagnostics);
+
+    for (index, endpoint) in model.endpoints.iter().flatten().enumerate() {
+        let endpoint_path = with(model_path, &["endpoints".into(), index.into()]);
+
+        if !SUPPORTED_METHODS.contains(&endpoint.method.to_uppercase().as_str()) {
+            diagnostics.push(Diagnostic::new(
+                with(&endpoint_path, &["method".into()]),
+                format!(
+                    "unsupported HTTP method '{}' (expected one of: {})",
+                    endpoint.method,
+                    SUPPORTED_METHODS.join(", ")
+                ),
+            ));
+        }
+
+        let placeholders = endpoint.path_placeholders();
+        let path_params = endpoint.path_params.as_deref().unwrap_or_default();
+        for (param_index, param) in path_params.iter().enumerate() {
+            if !placeholders.contains(&param.name) {
+                diagnostics.push(Diagnostic::new(
+                    with(
+                        &endpoint_path,
+                        &["path_params".into(), param_index.into(), "name".into()],
+                    ),
+                    format!(
+                        "path parameter '{}' does not appear in path '{}'",
+                        param.name, endpoint.path
+                    ),
+                ));
+            }
+        }
+        for placeholder in &placeholders {
+            if !path_params.iter().any(|p| &p.name == placeholder) {
+                diagnostics.push(Diagnostic::new(
+                    with(&endpoint_path, &["path".into()]),
+                    format!(
+                        "placeholder '{}' in path '{}' is not declared in `path_params`",
+                        placeholder, endpoint.path
+                    ),
+                ));
+            }
+        }
+
+        // Handlers pass the last placeholder to the service as the row's key
+        let method = endpoint.method.to_uppercase();
+        let key = model.primary_key();
+        let id_param = placeholders.last().and_then(|placeholder| {
+            path_params
+                .iter()
+                .enumerate()
+                .find(|(_, p)| &p.name == placeholder)
+        });
+        if let (Some(key), Some((param_index, param))) = (key, id_param) {
+            if matches!(method.as_str(), "GET" | "PUT" | "PATCH" | "DELETE")
+                && param.field_type.trim() != key.field_type.trim()
+            {
+                diagnostics.push(Diagnostic::new(
+                    with(
+                        &endpoint_path,
+                        &["path_params".into(), param_index.into(), "type".into()],
+                    ),
+                    format!(
+                        "path parameter '{}' is '{}' but the primary key of '{}' is '{}'",
+                        param.name, param.field_type, model.name, key.field_type
+                    ),
+                ));
+            }
+        }
+
+        // `UPDATE ... SET` needs at least one column to assign
+        let updated_fields = match method.as_str() {
+            "PUT" => Some(update_request_fields(model)),
+            "PATCH" => Some(patch_request_fields(model)),
+            _ => None,
+        };
+        if updated_fields.is_some_and(|fields| fields.is_empty()) {
+            diagnostics.push(Diagnostic::new(
+                with(&endpoint_path, &["method".into()]),
+                format!(
+                    "{} '{}' has no fields to update besides the primary key",
+                    method, endpoint.path
+                ),
+            ));
+        }
+
+        // Write bodies become request DTOs whose fields are bound to columns
+        if matches!(method.as_str(), "POST" | "PUT" | "PATCH") {
+            for (param_index, param) in endpoint.body_params.iter().flatten().enumerate() {
+                if !model.fields.iter().any(|f| f.name == param.name) {
+                    diagnostics.push(Diagnostic::new(
+                        with(
+                            &endpoint_path,
+                            &["body_params".into(), param_index.into(), "name".into()],
+                        ),
+                        format!(
+                            "body parameter '{}' is not a field of model '{}'",
+                            param.name, model.name
+                        ),
+                    ));
+                }
+            }
+        }
+
+        for (param_index, param) in endpoint.query_params.iter().flatten().enumerate() {
+            if RESERVED_QUERY_PARAMS.contains(&param.name.as_str()) {
+                diagnostics.push(Diagnostic::new(
+                    with(
+                        &endpoint_path,
+                        &["query_params".into(), param_index.into(), "name".into()],
+                    ),
+                    format!(
+                        "query parameter '{}' is reserved for sorting and pagination",
+                        param.name
+                    ),
+                ));
+            }
+        }
+
+        for (key, params) in [
+            ("path_params", &endpoint.path_params),
+            ("query_params", &endpoint.query_params),
+            ("body_params", &endpoint.body_params),
+        ] {
+            if let Some(params) = params {
+                validate_fields(params, &with(&endpoint_path, &[key.into()]), diagnostics);
+            }
+        }
+
+        let handler_name = endpoint.handler_name();
+        let route = format!("{} {}", endpoint.method.to_uppercase(), endpoint.path);
+        if let Some(previous) = handler_names.insert(handler_name.clone(), route.clone()) {
+            diagnostics.push(Diagnostic::new(
+                with(&endpoint_path, &["path".into()]),
+                format!(
+                    "handler name '{}' for '{}' clashes with '{}'",
+                    handler_name, route, previous
+                ),
+            ));
+        }
+    }
+}
+
+fn validate_auth(config: &Config, diagnostics: &mut Vec<Diagnostic>) {
+    for (model_index, model) in config.models.iter().enumerate() {
+        for (index, endpoint) in model.endpoints.iter().flatten().enumerate() {
+            let endpoint_path = vec![
+                "models".into(),
+                model_index.into(),
+                "endpoints".into(),
+                index.into(),
+            ];
+            if endpoint.requires_auth() && config.auth.is_none() {
+                let key = match (&endpoint.roles, &endpoint.owner_field) {
+                    (Some(_), _) => "roles",
+                    (_, Some(_)) => "owner_field",
+                    _ => "auth",
+                };
+                diagnostics.push(Diagnostic::new(
+                    with(&endpoint_path, &[key.into()]),
+                    format!("`{}` needs an `[auth]` section", key),
+                ));
+            }
+            validate_access_rules(config, model, endpoint, &endpoint_path, diagnostics);
+            let is_auth_route = AUTH_ROUTES.iter().any(|(path, _)| *path == endpoint.path);
+            if config.auth.is_some()
+                && endpoint.method.eq_ignore_ascii_case("POST")
+                && is_auth_route
+            {
+                diagnostics.push(Diagnostic::new(
+                    with(&endpoint_path, &["path".into()]),
+                    format!("'POST {}' is generated by `[auth]`", endpoint.path),
+                ));
+            }
+        }
+    }
+
+    let Some(auth) = &config.auth else {
+        return;
+    };
+    let Some(model) = config.models.iter().find(|m| m.name == auth.user_model) else {
+        diagnostics.push(Diagnostic::new(
+            vec!["auth".into(), "user_model".into()],
+            format!("`[auth]` refers to unknown model '{}'", auth.user_model),
+        ));
+        return;
+    };
+    let role_field = auth.role_field.as_deref().map(|f| ("role_field", f));
+    for (key, field_name) in [
+        ("username_field", auth.username_field()),
+        ("password_field", auth.password_field()),
+    ]
+    .into_iter()
+    .chain(role_field)
+    {
+        match model.fields.iter().find(|f| f.name == field_name) {
+            None => diagnostics.push(Diagnostic::new(
+                vec!["auth".into(), key.into()],
+                format!("'{}' is not a field of model '{}'", field_name, model.name),
+            )),
+            // A user without a role simply has none
+            Some(field)
+                if key == "role_field" && unwrap_option(&field.field_type).0 == "String" => {}
+            Some(field) if field.field_type.trim() != "String" => {
+                diagnostics.push(Diagnostic::new(
+                    vec!["auth".into(), key.into()],
+                    format!(
+                        "`{}` must be a `String` field, but '{}' is '{}'",
+                        key, field_name, field.field_type
+                    ),
+                ))
+            }
+            Some(_) => {}
+        }
+    }
+    for (key, ttl) in [
+        ("token_ttl", auth.token_ttl),
+        ("refresh_token_ttl", auth.refresh_token_ttl),
+    ] {
+        if ttl == Some(0) {
+            diagnostics.push(Diagnostic::new(
+                vec!["auth".into(), key.into()],
+                format!("`{}` must be at least 1 second", key),
+            ));
+        }
+    }
+}
+
+/// Checks `[api_keys]` and that the model and routes it adds are free.
+fn validate_api_keys(config: &Config, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(api_keys) = &config.api_keys else {
+        return;
+    };
+    if config.auth.is_none() {
+        diagnostics.push(Diagnostic::new(
+            vec!["api_keys".into()],
+            "`[api_keys]` needs an `[auth]` section; keys are issued and revoked by logged-in users",
+        ));
+    }
+    match &api_keys.admin_roles {
+        Some(roles) if roles.is_empty() => diagnostics.push(Diagnostic::new(
+            vec!["api_keys".into(), "admin_roles".into()],
+            "`admin_roles` must list at least one role",
+        )),
+        Some(_) if config.auth.as_ref().is_some_and(|a| a.role_field.is_none()) => diagnostics
+            .push(Diagnostic::new(
+                vec!["api_keys".into(), "admin_roles".into()],
+                "`admin_roles` needs `role_field` in `[auth]`",
+            )),
+        _ => {}
+    }
+    let header = api_keys.header();
+    let valid_header = !header.is_empty()
+        && header
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid_header {
+        diagnostics.push(Diagnostic::new(
+            vec!["api_keys".into(), "header".into()],
+            format!("'{}' is not a valid header name", header),
+        ));
+    }
+
+    for (model_index, model) in config.models.iter().enumerate() {
+        let model_path = vec!["models".into(), model_index.into()];
+        if model.name == API_KEY_MODEL {
+            diagnostics.push(Diagnostic::new(
+                with(&model_path, &["name".into()]),
+                format!("model '{}' is generated by `[api_keys]`", API_KEY_MODEL),
+            ));
+        }
+        if model.table_name == API_KEY_TABLE {
+            diagnostics.push(Diagnostic::new(
+                with(&model_path, &["table_name".into()]),
+                format!("table '{}' is generated by `[api_keys]`", API_KEY_TABLE),
+            ));
+        }
+        for (index, endpoint) in model.endpoints.iter().flatten().enumerate() {
+            let within = endpoint
+                .path
+                .strip_prefix(API_KEY_PATH)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'));
+            if within {
+                diagnostics.push(Diagnostic::new(
+                    with(
+                        &model_path,
+                        &["endpoints".into(), index.into(), "path".into()],
+                    ),
+                    format!("'{}' routes are generated by `[api_keys]`", API_KEY_PATH),
+                ));
+            }
+        }
+    }
+}
+
+/// Checks that served OpenAPI routes don't shadow an endpoint.
+fn validate_openapi(config: &Config, diagnostics: &mut Vec<Diagnostic>) {
+    if !config.serves_openapi() {
+        return;
+    }
+    for (model_index, model) in config.models.iter().enumerate() {
+        for (index, endpoint) in model.endpoints.iter().flatten().enumerate() {
+            let is_openapi_route = OPENAPI_ROUTES
+                .iter()
+                .any(|(path, _)| *path == endpoint.path);
+            if endpoint.method.eq_ignore_ascii_case("GET") && is_openapi_route {
+                diagnostics.push(Diagnostic::new(
+                    vec![
+                        "models".into(),
+                        model_index.into(),
+                        "endpoints".into(),
+                        index.into(),
+                        "path".into(),
+                    ],
+                    format!("'GET {}' is generated by `[openapi]`", endpoint.path),
+                ));
+            }
+        }
+    }
+}
+
+/// Checks an endpoint's `roles` and `owner_field` against `[auth]` and its model.
+fn validate_access_rules(
+    config: &Config,
+    model: &Model,
+    endpoint: &Endpoint,
+    path: &[PathSegment],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if let Some(roles) = &endpoint.roles {
+        if roles.is_empty() {
+            diagnostics.push(Diagnostic::new(
+                with(path, &["roles".into()]),
+                "`roles` must list at least one role",
+            ));
+        }
+        if config.auth.as_ref().is_some_and(|a| a.role_field.is_none()) {
+            diagnostics.push(Diagnostic::new(
+                with(path, &["roles".into()]),
+                "`roles` needs `role_field` in `[auth]`",
+            ));
+        }
+    }
+
+    let Some(owner) = &endpoint.owner_field else {
+        return;
+    };
+    let owner_path = with(path, &["owner_field".into()]);
+    let Some(field) = model.fields.iter().find(|f| &f.name == owner) else {
+        diagnostics.push(Diagnostic::new(
+            owner_path,
+            format!("'{}' is not a field of model '{}'", owner, model.name),
+        ));
+        return;
+    };
+    let user_key = config
+        .auth
+        .as_ref()
+        .and_then(|auth| config.models.iter().find(|m| m.name == auth.user_model))
+        .and_then(Model::primary_key);
+    if let Some(key) = user_key {
+        if unwrap_option(&field.field_type).0 != key.field_type.trim() {
+            diagnostics.push(Diagnostic::new(
+                owner_path.clone(),
+                format!(
+                    "owner field '{}' is '{}' but user ids are '{}'",
+                    owner, field.field_type, key.field_type
+                ),
+            ));
+        }
+    }
+    let method = endpoint.method.to_uppercase();
+    let addresses_row = !endpoint.path_placeholders().is_empty();
+    if method == "POST"
+        && !create_request_fields(model)
+            .iter()
+            .any(|f| &f.name == owner)
+    {
+        diagnostics.push(Diagnostic::new(
+            owner_path,
+            format!(
+                "owner field '{}' must be part of the create request body",
+                owner
+            ),
+        ));
+    } else if method != "POST" && !addresses_row {
+        diagnostics.push(Diagnostic::new(
+            owner_path,
+            "`owner_field` needs a path parameter addressing one row (or a `POST`)",
+        ));
+    }
+}
+
+fn validate_middleware(
+    config: &Config,
+    middleware: &Middleware,
+    path: &[PathSegment],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let source = middleware.source_model();
+    let Some(model) = config.models.iter().find(|m| m.name == source) else {
+        let key = match middleware.select_from_model {
+            Some(_) => "select_from_model",
+            None => "model",
+        };
+        diagnostics.push(Diagnostic::new(
+            with(path, &[key.into()]),
+            format!("middleware refers to unknown model '{}'", source),
+        ));
+        return;
+    };
+    for (index, check) in middleware.header_checks().iter().enumerate() {
+        let check_path = with(path, &["validate_header".into(), index.into()]);
+        match model.fields.iter().find(|f| f.name == check.model_field) {
+            None => diagnostics.push(Diagnostic::new(
+                with(&check_path, &["model_field".into()]),
+                format!(
+                    "'{}' is not a field of model '{}'",
+                    check.model_field, source
+                ),
+            )),
+            Some(field) if unwrap_option(&field.field_type).1 => diagnostics.push(Diagnostic::new(
+                with(&check_path, &["model_field".into()]),
+                format!(
+                    "header field '{}' cannot be optional; a missing header is rejected instead",
+                    check.model_field
+                ),
+            )),
+            Some(_) => {}
+        }
+        let valid_header = !check.header_key.is_empty()
+            && check
+                .header_key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid_header {
+            diagnostics.push(Diagnostic::new(
+                with(&check_path, &["header_key".into()]),
+                format!("'{}' is not a valid header name", check.header_key),
+            ));
+        }
+    }
+}
+
+fn validate_relations(
+    config: &Config,
+    model: &Model,
+    model_path: &[PathSegment],
+    handler_names: &mut HashMap<String, String>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut names = HashSet::new();
+    for (index, relation) in model.relations.iter().flatten().enumerate() {
+        let relation_path = with(model_path, &["relations".into(), index.into()]);
+        let Some(target) = config.models.iter().find(|m| m.name == relation.model) else {
+            diagnostics.push(Diagnostic::new(
+                with(&relation_path, &["model".into()]),
+                format!("relation refers to unknown model '{}'", relation.model),
+            ));
+            continue;
+        };
+
+        let name = relation.name(target);
+        if !names.insert(name.clone()) || model.fields.iter().any(|f| f.name == name) {
+            diagnostics.push(Diagnostic::new(
+                with(&relation_path, &["name".into()]),
+                format!(
+                    "relation name '{}' clashes with another relation or field of '{}'",
+                    name, model.name
+                ),
+            ));
+        }
+
+        match relation.kind {
+            RelationKind::BelongsTo if relation.has_nested_route() => {
+                diagnostics.push(Diagnostic::new(
+                    with(&relation_path, &["nested_route".into()]),
+                    "`nested_route` is only supported on `has_many` and `many_to_many`",
+                ));
+            }
+            RelationKind::BelongsTo => {
+                let foreign_key = relation.foreign_key(model, target);
+                let field = model.fields.iter().find(|f| f.name == foreign_key);
+                if let (Some(field), Some(key)) = (field, target.primary_key()) {
+                    if unwrap_option(&field.field_type).0 != key.field_type.trim() {
+                        diagnostics.push(Diagnostic::new(
+                            with(&relation_path, &["foreign_key".into()]),
+                            format!(
+                                "foreign key '{}' has type '{}' but '{}.{}' is '{}'",
+                                foreign_key,
+                                field.field_type,
+                                target.name,
+                                key.name,
+                                key.field_type
+                            ),
+                        ));
+                    }
+                }
+            }
+            RelationKind::ManyToMany if target.name == model.name => {
+                diagnostics.push(Diagnostic::new(
+                    with(&relation_path, &["model".into()]),
+                    "a `many_to_many` relation cannot point at its own model",
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    for route in nested_routes(config, model) {
+        let handler_name = route.endpoint.handler_name();
+        let route_name = format!("GET {}", route.endpoint.path);
+        if let Some(previous) = handler_names.insert(handler_name.clone(), route_name.clone()) {
+            diagnostics.push(Diagnostic::new(
+                with(model_path, &["relations".into()]),
+                format!(
+                    "handler name '{}' for '{}' clashes with '{}'",
+                    handler_name, route_name, previous
+                ),
+            ));
+        }
+    }
+}
+
+fn validate_fields(fields: &[Field], path: &[PathSegment], diagnostics: &mut Vec<Diagnostic>) {
+    let mut names = HashSet::new();
+    for (index, field) in fields.iter().enumerate() {
+        if !names.insert(field.name.as_str()) {
+            diagnostics.push(Diagnostic::new(
+                with(path, &[index.into(), "name".into()]),
+                format!("duplicate field name '{}'", field.name),
+            ));
+        }
+        if field.field_type.trim().is_empty() {
+            diagnostics.push(Diagnostic::new(
+                with(path, &[index.into(), "type".into()]),
+                format!("field '{}' has an empty type", field.name),
+            ));
+        }
+    }
+}
+
+/// Checks the column attributes of a model's fields and its primary key.
+fn validate_columns(
+    model: &Model,
+    path: &[PathSegment],
+    database_type: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let explicit_keys = model.fields.iter().filter(|f| f.is_primary_key()).count();
+    let primary_key = model.primary_key();
+    match &primary_key {
+        None => diagnostics.push(Diagnostic::new(
+            path.to_vec(),
+            format!(
+                "model '{}' has no primary key (name a field `id` or set `primary_key = true`)",
+                model.name
+            ),
+        )),
+        Some(key) if unwrap_option(&key.field_type).1 => {
+            let index = model.fields.iter().position(|f| f.name == key.name);
+            diagnostics.push(Diagnostic::new(
+                with(path, &[index.unwrap_or_default().into(), "type".into()]),
+                format!("primary key '{}' cannot be optional", key.name),
+            ));
+        }
+        // MySQL has no `RETURNING`, so created rows are looked up by a key the client sent
+        Some(key)
+            if database_type.eq_ignore_ascii_case("mysql")
+                && !key.is_auto_increment()
+                && !create_request_fields(model)
+                    .iter()
+                    .any(|f| f.name == key.name) =>
+        {
+            diagnostics.push(Diagnostic::new(
+                path.to_vec(),
+                format!(
+                    "primary key '{}' must be auto-incremented or sent when creating a '{}' on MySQL",
+                    key.name, model.name
+                ),
+            ));
+        }
+        Some(_) => {}
+    }
+
+    let mut columns = HashSet::new();
+    for (index, field) in model.fields.iter().enumerate() {
+        let field_path = with(path, &[index.into()]);
+        if field.is_primary_key() && explicit_keys > 1 {
+            diagnostics.push(Diagnostic::new(
+                with(&field_path, &["primary_key".into()]),
+                format!(
+                    "model '{}' has more than one primary key; composite keys are not supported",
+                    model.name
+                ),
+            ));
+        }
+        if !columns.insert(field.column_name()) && field.column.is_some() {
+            diagnostics.push(Diagnostic::new(
+                with(&field_path, &["column".into()]),
+                format!("duplicate column name '{}'", field.column_name()),
+            ));
+        }
+
+        let (inner_type, _) = unwrap_option(&field.field_type);
+        let is_key = primary_key
+            .as_ref()
+            .is_some_and(|key| key.name == field.name);
+        if field.auto_increment == Some(true) && !is_key {
+            diagnostics.push(Diagnostic::new(
+                with(&field_path, &["auto_increment".into()]),
+                format!(
+                    "`auto_increment` is only supported on the primary key, not '{}'",
+                    field.name
+                ),
+            ));
+        }
+        if field.auto_increment == Some(true) && !is_integer_type(inner_type) {
+            diagnostics.push(Diagnostic::new(
+                with(&field_path, &["auto_increment".into()]),
+                format!(
+                    "`auto_increment` needs an integer type, '{}' is {}",
+                    field.name, field.field_type
+                ),
+            ));
+        }
+        if field.auto_increment == Some(true) && field.default.is_some() {
+            diagnostics.push(Diagnostic::new(
+                with(&field_path, &["default".into()]),
+                format!(
+                    "auto-incremented field '{}' cannot have a default",
+                    field.name
+                ),
+            ));
+        }
+
+        match field.max_length {
+            Some(_) if !is_string_type(inner_type) => diagnostics.push(Diagnostic::new(
+                with(&field_path, &["max_length".into()]),
+                format!(
+                    "`max_length` only applies to String fields, '{}' is {}",
+                    field.name, field.field_type
+                ),
+            )),
+            Some(0) => diagnostics.push(Diagnostic::new(
+                with(&field_path, &["max_length".into()]),
+                "`max_length` must be at least 1",
+            )),
+            _ => {}
+        }
+
+        let default_fits = match &field.default {
+            None | Some(DefaultValue::Sql { .. }) => true,
+            Some(DefaultValue::Bool(_)) => inner_type == "bool",
+            Some(DefaultValue::Integer(_)) => {
+                is_integer_type(inner_type) || matches!(inner_type, "f32" | "f64")
+            }
+            Some(DefaultValue::Float(_)) => matches!(inner_type, "f32" | "f64"),
+            Some(DefaultValue::Text(text)) => {
+                is_string_type(inner_type)
+                    && field
+                        .max_length
+                        .is_none_or(|max| text.chars().count() <= max as usize)
+            }
+        };
+        if !default_fits {
+            diagnostics.push(Diagnostic::new(
+                with(&field_path, &["default".into()]),
+                format!(
+                    "default for '{}' does not fit its type {} (use `{{ sql = \"...\" }}` for expressions)",
+                    field.name, field.field_type
+                ),
+            ));
+        }
+    }
+}
+
+fn with(base: &[PathSegment], rest: &[PathSegment]) -> Vec<PathSegment> {
+    [base, rest].concat()
+}
+
+#[cfg(test)]
+mod tests;
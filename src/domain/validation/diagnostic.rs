@@ -0,0 +1,50 @@
+use std::fmt;
+
+/// One step in the path from the config root to the offending value,
+/// e.g. `models[1].endpoints[0].path`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+impl From<&str> for PathSegment {
+    fn from(key: &str) -> Self {
+        PathSegment::Key(key.to_string())
+    }
+}
+
+impl From<usize> for PathSegment {
+    fn from(index: usize) -> Self {
+        PathSegment::Index(index)
+    }
+}
+
+/// A problem found in the config, pointing at the value that caused it.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub path: Vec<PathSegment>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new(path: Vec<PathSegment>, message: impl Into<String>) -> Self {
+        Self {
+            path,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.path.iter().enumerate() {
+            match segment {
+                PathSegment::Key(key) if i == 0 => write!(f, "{}", key)?,
+                PathSegment::Key(key) => write!(f, ".{}", key)?,
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        write!(f, ": {}", self.message)
+    }
+}
@@ -0,0 +1,2 @@
+pub mod config_validator;
+pub mod diagnostic;
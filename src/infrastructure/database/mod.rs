@@ -0,0 +1 @@
+pub mod sqlx_introspector;
@@ -0,0 +1,475 @@
+use std::error::Error;
+use std::str::FromStr;
+
+use sqlx::mysql::MySqlPoolOptions;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+use crate::domain::models::database_import::{
+    database_type_of, ColumnSchema, ForeignKeySchema, TableSchema,
+};
+use crate::domain::ports::schema_introspector::SchemaIntrospector;
+
+/// Reads SQLite schemas through its pragmas and Postgres and MySQL schemas
+/// through `information_schema`.
+pub struct SqlxIntrospector;
+
+/// Tables created by `sqlx migrate` rather than by the application.
+const IGNORED_TABLES: &[&str] = &["_sqlx_migrations"];
+
+/// Table, column, data type, length, `is_nullable`, default and `is_identity`.
+type PostgresColumn = (
+    String,
+    String,
+    String,
+    Option<i64>,
+    String,
+    Option<String>,
+    String,
+);
+
+struct ColumnRow {
+    table: String,
+    column: ColumnSchema,
+}
+
+/// A `PRIMARY KEY` or `UNIQUE` constraint column.
+struct KeyRow {
+    table: String,
+    constraint: String,
+    primary: bool,
+    column: String,
+}
+
+struct ForeignKeyRow {
+    table: String,
+    constraint: String,
+    column: String,
+    referenced_table: String,
+    referenced_column: Option<String>,
+}
+
+impl SchemaIntrospector for SqlxIntrospector {
+    async fn tables(&self, database_url: &str) -> Result<Vec<TableSchema>, Box<dyn Error>> {
+        match database_type_of(database_url) {
+            Some("sqlite") => sqlite_tables(database_url).await,
+            Some("postgres") => postgres_tables(database_url).await,
+            Some("mysql") => mysql_tables(database_url).await,
+            _ => Err(format!(
+                "unsupported database URL '{}'; expected sqlite://, postgres:// or mysql://",
+                database_url
+            )
+            .into()),
+        }
+    }
+}
+
+async fn sqlite_tables(database_url: &str) -> Result<Vec<TableSchema>, Box<dyn Error>> {
+    let options = SqliteConnectOptions::from_str(database_url)?.read_only(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await?;
+    let names: Vec<(String,)> = sqlx::query_as(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY rowid",
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let mut tables = Vec::new();
+    for (name,) in names {
+        if IGNORED_TABLES.contains(&name.as_str()) {
+            continue;
+        }
+        let mut table = TableSchema {
+            name: name.clone(),
+            ..TableSchema::default()
+        };
+        let columns: Vec<(String, String, bool, Option<String>, i64)> = sqlx::query_as(
+            "SELECT name, type, \"notnull\", dflt_value, pk FROM pragma_table_info(?) ORDER BY cid",
+        )
+        .bind(&name)
+        .fetch_all(&pool)
+        .await?;
+        let mut key = columns
+            .iter()
+            .filter(|c| c.4 > 0)
+            .map(|c| (c.4, c.0.clone()))
+            .collect::<Vec<_>>();
+        key.sort();
+        table.primary_key = key.into_iter().map(|(_, column)| column).collect();
+        for (column, sql_type, not_null, default, pk) in columns {
+            table.columns.push(ColumnSchema {
+                name: column,
+                sql_type,
+                nullable: !not_null && pk == 0,
+                default,
+                ..ColumnSchema::default()
+            });
+        }
+
+        let indexes: Vec<(String, bool, String)> =
+            sqlx::query_as("SELECT name, \"unique\", origin FROM pragma_index_list(?)")
+                .bind(&name)
+                .fetch_all(&pool)
+                .await?;
+        for (index, unique, origin) in indexes {
+            if origin == "pk" {
+                continue;
+            }
+            let index_columns: Vec<(Option<String>,)> =
+                sqlx::query_as("SELECT name FROM pragma_index_info(?)")
+                    .bind(&index)
+                    .fetch_all(&pool)
+                    .await?;
+            if let [(Some(column),)] = index_columns.as_slice() {
+                if let Some(column) = table.column_mut(column) {
+                    if unique {
+                        column.unique = true;
+                    } else {
+                        column.indexed = true;
+                    }
+                }
+            }
+        }
+
+        let foreign_keys: Vec<(i64, String, String, Option<String>)> = sqlx::query_as(
+            "SELECT id, \"table\", \"from\", \"to\" FROM pragma_foreign_key_list(?) ORDER BY id, seq",
+        )
+        .bind(&name)
+        .fetch_all(&pool)
+        .await?;
+        let rows = foreign_keys
+            .into_iter()
+            .map(
+                |(id, referenced_table, column, referenced_column)| ForeignKeyRow {
+                    table: name.clone(),
+                    constraint: id.to_string(),
+                    column,
+                    referenced_table,
+                    referenced_column,
+                },
+            )
+            .collect();
+        add_foreign_keys(std::slice::from_mut(&mut table), rows);
+        tables.push(table);
+    }
+    pool.close().await;
+    Ok(tables)
+}
+
+async fn postgres_tables(database_url: &str) -> Result<Vec<TableSchema>, Box<dyn Error>> {
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(database_url)
+        .await?;
+    let columns: Vec<PostgresColumn> = sqlx::query_as(
+            "SELECT c.table_name::text, c.column_name::text, \
+                    CASE WHEN c.data_type = 'USER-DEFINED' THEN c.udt_name::text ELSE c.data_type::text END, \
+                    c.character_maximum_length::int8, c.is_nullable::text, c.column_default::text, \
+                    c.is_identity::text \
+             FROM information_schema.columns c \
+             JOIN information_schema.tables t \
+               ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+             WHERE c.table_schema = current_schema() AND t.table_type = 'BASE TABLE' \
+             ORDER BY c.table_name, c.ordinal_position",
+        )
+        .fetch_all(&pool)
+        .await?;
+    let columns = columns
+        .into_iter()
+        .map(
+            |(table, column, data_type, length, nullable, default, identity)| {
+                // Serial columns default to their sequence
+                let serial = default
+                    .as_deref()
+                    .is_some_and(|default| default.starts_with("nextval("));
+                ColumnRow {
+                    table,
+                    column: ColumnSchema {
+                        name: column,
+                        sql_type: match length {
+                            Some(length) => format!("{}({})", data_type, length),
+                            None => data_type,
+                        },
+                        nullable: nullable == "YES",
+                        default: default.filter(|_| !serial),
+                        auto_increment: serial || identity == "YES",
+                        ..ColumnSchema::default()
+                    },
+                }
+            },
+        )
+        .collect();
+
+    let keys: Vec<(String, String, String, String)> = sqlx::query_as(
+        "SELECT tc.table_name::text, tc.constraint_name::text, tc.constraint_type::text, kcu.column_name::text \
+         FROM information_schema.table_constraints tc \
+         JOIN information_schema.key_column_usage kcu \
+           ON kcu.constraint_schema = tc.constraint_schema \
+          AND kcu.constraint_name = tc.constraint_name \
+          AND kcu.table_name = tc.table_name \
+         WHERE tc.table_schema = current_schema() \
+           AND tc.constraint_type IN ('PRIMARY KEY', 'UNIQUE') \
+         ORDER BY tc.table_name, tc.constraint_name, kcu.ordinal_position",
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let foreign_keys: Vec<(String, String, String, String, String)> = sqlx::query_as(
+        "SELECT kcu.table_name::text, kcu.constraint_name::text, kcu.column_name::text, \
+                ref.table_name::text, ref.column_name::text \
+         FROM information_schema.key_column_usage kcu \
+         JOIN information_schema.referential_constraints rc \
+           ON rc.constraint_schema = kcu.constraint_schema \
+          AND rc.constraint_name = kcu.constraint_name \
+         JOIN information_schema.key_column_usage ref \
+           ON ref.constraint_schema = rc.unique_constraint_schema \
+          AND ref.constraint_name = rc.unique_constraint_name \
+          AND ref.ordinal_position = kcu.position_in_unique_constraint \
+         WHERE kcu.table_schema = current_schema() \
+         ORDER BY kcu.table_name, kcu.constraint_name, kcu.ordinal_position",
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    // Indexes aren't part of `information_schema` in Postgres
+    let indexes: Vec<(String, String, bool)> = sqlx::query_as(
+        "SELECT t.relname::text, a.attname::text, ix.indisunique \
+         FROM pg_index ix \
+         JOIN pg_class t ON t.oid = ix.indrelid \
+         JOIN pg_namespace n ON n.oid = t.relnamespace \
+         JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = ix.indkey[0] \
+         WHERE n.nspname = current_schema() AND ix.indnatts = 1 AND NOT ix.indisprimary",
+    )
+    .fetch_all(&pool)
+    .await?;
+    pool.close().await;
+    Ok(information_schema_tables(
+        columns,
+        key_rows(keys),
+        indexes,
+        foreign_key_rows(foreign_keys),
+    ))
+}
+
+async fn mysql_tables(database_url: &str) -> Result<Vec<TableSchema>, Box<dyn Error>> {
+    let pool = MySqlPoolOptions::new()
+        .max_connections(1)
+        .connect(database_url)
+        .await?;
+    // `information_schema` strings may come back as binary, hence the casts
+    let columns: Vec<(String, String, String, String, Option<String>, String)> = sqlx::query_as(
+        "SELECT CAST(c.table_name AS CHAR), CAST(c.column_name AS CHAR), CAST(c.column_type AS CHAR), \
+                CAST(c.is_nullable AS CHAR), CAST(c.column_default AS CHAR), CAST(c.extra AS CHAR) \
+         FROM information_schema.columns c \
+         JOIN information_schema.tables t \
+           ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+         WHERE c.table_schema = DATABASE() AND t.table_type = 'BASE TABLE' \
+         ORDER BY c.table_name, c.ordinal_position",
+    )
+    .fetch_all(&pool)
+    .await?;
+    let columns = columns
+        .into_iter()
+        .map(|(table, column, column_type, nullable, default, extra)| {
+            let extra = extra.to_lowercase();
+            ColumnRow {
+                table,
+                column: ColumnSchema {
+                    name: column,
+                    default: default.and_then(|default| mysql_default(default, &extra)),
+                    sql_type: column_type,
+                    nullable: nullable == "YES",
+                    auto_increment: extra.contains("auto_increment"),
+                    ..ColumnSchema::default()
+                },
+            }
+        })
+        .collect();
+
+    let keys: Vec<(String, String, String, String)> = sqlx::query_as(
+        "SELECT CAST(tc.table_name AS CHAR), CAST(tc.constraint_name AS CHAR), \
+                CAST(tc.constraint_type AS CHAR), CAST(kcu.column_name AS CHAR) \
+         FROM information_schema.table_constraints tc \
+         JOIN information_schema.key_column_usage kcu \
+           ON kcu.constraint_schema = tc.constraint_schema \
+          AND kcu.constraint_name = tc.constraint_name \
+          AND kcu.table_name = tc.table_name \
+         WHERE tc.table_schema = DATABASE() \
+           AND tc.constraint_type IN ('PRIMARY KEY', 'UNIQUE') \
+         ORDER BY tc.table_name, tc.constraint_name, kcu.ordinal_position",
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let foreign_keys: Vec<(String, String, String, String, String)> = sqlx::query_as(
+        "SELECT CAST(table_name AS CHAR), CAST(constraint_name AS CHAR), CAST(column_name AS CHAR), \
+                CAST(referenced_table_name AS CHAR), CAST(referenced_column_name AS CHAR) \
+         FROM information_schema.key_column_usage \
+         WHERE table_schema = DATABASE() AND referenced_table_name IS NOT NULL \
+         ORDER BY table_name, constraint_name, ordinal_position",
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let indexes: Vec<(String, String, i64)> = sqlx::query_as(
+        "SELECT CAST(s.table_name AS CHAR), CAST(s.column_name AS CHAR), CAST(s.non_unique AS SIGNED) \
+         FROM information_schema.statistics s \
+         WHERE s.table_schema = DATABASE() AND s.index_name <> 'PRIMARY' \
+           AND (SELECT COUNT(*) FROM information_schema.statistics o \
+                WHERE o.table_schema = s.table_schema AND o.table_name = s.table_name \
+                  AND o.index_name = s.index_name) = 1",
+    )
+    .fetch_all(&pool)
+    .await?;
+    let indexes = indexes
+        .into_iter()
+        .map(|(table, column, non_unique)| (table, column, non_unique == 0))
+        .collect();
+    pool.close().await;
+    Ok(information_schema_tables(
+        columns,
+        key_rows(keys),
+        indexes,
+        foreign_key_rows(foreign_keys),
+    ))
+}
+
+/// MySQL reports string defaults unquoted; quote them so they read as SQL.
+/// MariaDB already quotes them and reports a missing default as `NULL`.
+fn mysql_default(default: String, extra: &str) -> Option<String> {
+    let expression = extra.contains("default_generated")
+        || default.starts_with('\'')
+        || default.parse::<f64>().is_ok()
+        || default.to_uppercase().starts_with("CURRENT_TIMESTAMP");
+    match (default.as_str(), expression) {
+        ("NULL", _) => None,
+        (_, true) => Some(default),
+        (_, false) => Some(format!("'{}'", default.replace('\'', "''"))),
+    }
+}
+
+fn key_rows(rows: Vec<(String, String, String, String)>) -> Vec<KeyRow> {
+    rows.into_iter()
+        .map(|(table, constraint, constraint_type, column)| KeyRow {
+            table,
+            constraint,
+            primary: constraint_type == "PRIMARY KEY",
+            column,
+        })
+        .collect()
+}
+
+fn foreign_key_rows(rows: Vec<(String, String, String, String, String)>) -> Vec<ForeignKeyRow> {
+    rows.into_iter()
+        .map(
+            |(table, constraint, column, referenced_table, referenced_column)| ForeignKeyRow {
+                table,
+                constraint,
+                column,
+                referenced_table,
+                referenced_column: Some(referenced_column),
+            },
+        )
+        .collect()
+}
+
+/// Groups `information_schema` rows into tables, in column order. `indexes`
+/// are `(table, column, unique)` of single-column indexes.
+fn information_schema_tables(
+    columns: Vec<ColumnRow>,
+    keys: Vec<KeyRow>,
+    indexes: Vec<(String, String, bool)>,
+    foreign_keys: Vec<ForeignKeyRow>,
+) -> Vec<TableSchema> {
+    let mut tables: Vec<TableSchema> = Vec::new();
+    for row in columns {
+        if IGNORED_TABLES.contains(&row.table.as_str()) {
+            continue;
+        }
+        match tables.iter_mut().find(|t| t.name == row.table) {
+            Some(table) => table.columns.push(row.column),
+            None => tables.push(TableSchema {
+                name: row.table,
+                columns: vec![row.column],
+                ..TableSchema::default()
+            }),
+        }
+    }
+
+    // (table, constraint) → columns
+    let mut unique: Vec<(String, String, Vec<String>)> = Vec::new();
+    for key in keys {
+        let Some(table) = tables.iter_mut().find(|t| t.name == key.table) else {
+            continue;
+        };
+        if key.primary {
+            table.primary_key.push(key.column);
+            continue;
+        }
+        match unique
+            .iter_mut()
+            .find(|(t, c, _)| *t == key.table && *c == key.constraint)
+        {
+            Some((_, _, columns)) => columns.push(key.column),
+            None => unique.push((key.table, key.constraint, vec![key.column])),
+        }
+    }
+    for (table, _, columns) in unique {
+        if let [column] = columns.as_slice() {
+            if let Some(column) = tables
+                .iter_mut()
+                .find(|t| t.name == table)
+                .and_then(|t| t.column_mut(column))
+            {
+                column.unique = true;
+            }
+        }
+    }
+    for (table, column, unique) in indexes {
+        if let Some(column) = tables
+            .iter_mut()
+            .find(|t| t.name == table)
+            .and_then(|t| t.column_mut(&column))
+        {
+            if unique {
+                column.unique = true;
+            } else {
+                column.indexed = true;
+            }
+        }
+    }
+    add_foreign_keys(&mut tables, foreign_keys);
+    tables
+}
+
+/// Groups foreign-key rows by constraint onto their tables.
+fn add_foreign_keys(tables: &mut [TableSchema], rows: Vec<ForeignKeyRow>) {
+    let mut constraints: Vec<(String, String, ForeignKeySchema)> = Vec::new();
+    for row in rows {
+        match constraints
+            .iter_mut()
+            .find(|(t, c, _)| *t == row.table && *c == row.constraint)
+        {
+            Some((_, _, foreign_key)) => {
+                foreign_key.columns.push(row.column);
+                foreign_key.referenced_columns.extend(row.referenced_column);
+            }
+            None => constraints.push((
+                row.table,
+                row.constraint,
+                ForeignKeySchema {
+                    columns: vec![row.column],
+                    table: row.referenced_table,
+                    referenced_columns: row.referenced_column.into_iter().collect(),
+                },
+            )),
+        }
+    }
+    for (table, _, foreign_key) in constraints {
+        if let Some(table) = tables.iter_mut().find(|t| t.name == table) {
+            table.foreign_keys.push(foreign_key);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use crate::domain::ports::file_system::FileSystem;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{Error, ErrorKind};
+use std::path::{Component, Path};
+use std::sync::Mutex;
+
+/// A `FileSystem` that keeps everything in memory, so a generation can be
+/// inspected without touching the disk.
+#[derive(Default)]
+pub struct InMemoryFileSystem {
+    files: Mutex<BTreeMap<String, String>>,
+    dirs: Mutex<BTreeSet<String>>,
+}
+
+impl InMemoryFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every file written so far, keyed by normalized path.
+    pub fn files(&self) -> BTreeMap<String, String> {
+        self.files.lock().unwrap().clone()
+    }
+}
+
+/// `a/./b//c`, `a/x/../b/c` and `a/b/c` are the same entry. A leading `..`
+/// is kept, so paths outside the current directory stay distinct and can be
+/// read from the disk underneath a `RecordingFileSystem`.
+pub fn normalize(path: &str) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+            Component::RootDir => parts.push(String::new()),
+            Component::ParentDir => match parts.last().map(String::as_str) {
+                Some("..") | None => parts.push("..".to_string()),
+                // The root has no parent
+                Some("") => {}
+                Some(_) => {
+                    parts.pop();
+                }
+            },
+            Component::CurDir | Component::Prefix(_) => {}
+        }
+    }
+    parts.join("/")
+}
+
+fn parent(path: &str) -> Option<&str> {
+    path.rsplit_once('/').map(|(parent, _)| parent)
+}
+
+fn not_found(path: &str) -> Error {
+    Error::new(
+        ErrorKind::NotFound,
+        format!("{}: no such file or directory", path),
+    )
+}
+
+impl FileSystem for InMemoryFileSystem {
+    async fn create_dir_all(&self, path: &str) -> std::io::Result<()> {
+        let path = normalize(path);
+        if self.files.lock().unwrap().contains_key(&path) {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("{}: is a file", path),
+            ));
+        }
+        let mut dirs = self.dirs.lock().unwrap();
+        let mut dir = Some(path.as_str());
+        while let Some(current) = dir.filter(|current| !current.is_empty()) {
+            dirs.insert(current.to_string());
+            dir = parent(current);
+        }
+        Ok(())
+    }
+
+    async fn write_file(&self, path: &str, content: &str) -> std::io::Result<()> {
+        let path = normalize(path);
+        if let Some(dir) = parent(&path).filter(|dir| !dir.is_empty()) {
+            if !self.dirs.lock().unwrap().contains(dir) {
+                return Err(not_found(dir));
+            }
+        }
+        self.files
+            .lock()
+            .unwrap()
+            .insert(path.clone(), content.to_string());
+        Ok(())
+    }
+
+    async fn read_to_string(&self, path: &str) -> std::io::Result<String> {
+        let path = normalize(path);
+        self.files
+            .lock()
+            .unwrap()
+            .get(&path)
+            .cloned()
+            .ok_or_else(|| not_found(&path))
+    }
+
+    async fn exists(&self, path: &str) -> bool {
+        let path = normalize(path);
+        self.files.lock().unwrap().contains_key(&path) || self.dirs.lock().unwrap().contains(&path)
+    }
+
+    async fn read_dir(&self, path: &str) -> std::io::Result<Vec<String>> {
+        let path = normalize(path);
+        if !self.dirs.lock().unwrap().contains(&path) {
+            return Err(not_found(&path));
+        }
+        let prefix = format!("{}/", path);
+        let child = |entry: &String| {
+            entry
+                .strip_prefix(&prefix)
+                .filter(|name| !name.contains('/'))
+                .map(str::to_string)
+        };
+        let mut names: Vec<String> = self
+            .files
+            .lock()
+            .unwrap()
+            .keys()
+            .filter_map(child)
+            .collect();
+        names.extend(self.dirs.lock().unwrap().iter().filter_map(child));
+        names.sort();
+        Ok(names)
+    }
+}
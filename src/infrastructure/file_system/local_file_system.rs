@@ -1,5 +1,6 @@
 use crate::domain::ports::file_system::FileSystem;
 use std::fs;
+use std::path::Path;
 
 pub struct LocalFileSystem;
 
@@ -15,4 +16,16 @@ impl FileSystem for LocalFileSystem {
     async fn read_to_string(&self, path: &str) -> std::io::Result<String> {
         fs::read_to_string(path)
     }
+
+    async fn exists(&self, path: &str) -> bool {
+        Path::new(path).exists()
+    }
+
+    async fn read_dir(&self, path: &str) -> std::io::Result<Vec<String>> {
+        let mut names = fs::read_dir(path)?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        names.sort();
+        Ok(names)
+    }
 }
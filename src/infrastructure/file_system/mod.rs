@@ -1 +1,3 @@
+pub mod in_memory_file_system;
 pub mod local_file_system;
+pub mod recording_file_system;
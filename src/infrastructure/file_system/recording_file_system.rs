@@ -0,0 +1,98 @@
+use crate::domain::ports::file_system::FileSystem;
+use crate::infrastructure::file_system::in_memory_file_system::{normalize, InMemoryFileSystem};
+
+/// What a recorded write would do to the file underneath.
+pub enum FileChange {
+    Created,
+    Modified { before: String },
+    Unchanged,
+}
+
+/// One recorded write, relative to the root passed to `RecordingFileSystem::changes`.
+pub struct RecordedWrite {
+    pub path: String,
+    pub content: String,
+    pub change: FileChange,
+}
+
+/// Records writes in memory instead of passing them on, while reads see the
+/// recorded files on top of `inner`. Used to preview a generation.
+pub struct RecordingFileSystem<'a, F: FileSystem> {
+    inner: &'a F,
+    recorded: InMemoryFileSystem,
+}
+
+impl<'a, F: FileSystem> RecordingFileSystem<'a, F> {
+    pub fn new(inner: &'a F) -> Self {
+        Self {
+            inner,
+            recorded: InMemoryFileSystem::new(),
+        }
+    }
+
+    /// The files written under `root`, compared with what `inner` holds, sorted by path.
+    pub async fn changes(&self, root: &str) -> Vec<RecordedWrite> {
+        let prefix = format!("{}/", normalize(root));
+        let mut changes = Vec::new();
+        for (path, content) in self.recorded.files() {
+            let Some(relative) = path.strip_prefix(&prefix) else {
+                continue;
+            };
+            let change = match self.inner.read_to_string(&path).await {
+                Ok(before) if before == content => FileChange::Unchanged,
+                Ok(before) => FileChange::Modified { before },
+                Err(_) => FileChange::Created,
+            };
+            changes.push(RecordedWrite {
+                path: relative.to_string(),
+                content,
+                change,
+            });
+        }
+        changes
+    }
+}
+
+impl<F: FileSystem> FileSystem for RecordingFileSystem<'_, F> {
+    async fn create_dir_all(&self, path: &str) -> std::io::Result<()> {
+        self.recorded.create_dir_all(path).await
+    }
+
+    async fn write_file(&self, path: &str, content: &str) -> std::io::Result<()> {
+        // The parent only has to exist on one side
+        if let Some(parent) = std::path::Path::new(path).parent() {
+            self.recorded
+                .create_dir_all(&parent.to_string_lossy())
+                .await?;
+        }
+        self.recorded.write_file(path, content).await
+    }
+
+    async fn read_to_string(&self, path: &str) -> std::io::Result<String> {
+        match self.recorded.read_to_string(path).await {
+            Ok(content) => Ok(content),
+            Err(_) => self.inner.read_to_string(path).await,
+        }
+    }
+
+    async fn exists(&self, path: &str) -> bool {
+        self.recorded.exists(path).await || self.inner.exists(path).await
+    }
+
+    async fn read_dir(&self, path: &str) -> std::io::Result<Vec<String>> {
+        let (recorded, inner) = (
+            self.recorded.read_dir(path).await,
+            self.inner.read_dir(path).await,
+        );
+        let mut names = match (recorded, inner) {
+            (Err(error), Err(_)) => return Err(error),
+            (recorded, inner) => [recorded.unwrap_or_default(), inner.unwrap_or_default()].concat(),
+        };
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,50 @@
+use super::{FileChange, RecordingFileSystem};
+use crate::domain::ports::file_system::FileSystem;
+use crate::infrastructure::file_system::in_memory_file_system::normalize;
+use crate::infrastructure::file_system::local_file_system::LocalFileSystem;
+
+#[test]
+fn normalizing_keeps_paths_outside_the_current_directory() {
+    assert_eq!(normalize("a/./b//c"), "a/b/c");
+    assert_eq!(normalize("a/x/../b"), "a/b");
+    assert_eq!(normalize("../demo/./src"), "../demo/src");
+    assert_eq!(normalize("../../demo"), "../../demo");
+    assert_eq!(normalize("/../demo"), "/demo");
+}
+
+#[tokio::test]
+async fn changes_compare_with_the_files_behind_parent_components() {
+    let root = std::env::temp_dir().join(format!("prkserver-recording-{}", std::process::id()));
+    let project = root.join("project");
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::create_dir_all(root.join("elsewhere")).unwrap();
+    std::fs::write(project.join("kept.txt"), "same\n").unwrap();
+    // Like `--out-dir ../project` run from a sibling directory
+    let out_dir = format!("{}/elsewhere/../project", root.display());
+
+    let local = LocalFileSystem;
+    let recording = RecordingFileSystem::new(&local);
+    assert!(recording.exists(&format!("{}/kept.txt", out_dir)).await);
+    recording
+        .write_file(&format!("{}/kept.txt", out_dir), "same\n")
+        .await
+        .unwrap();
+    recording
+        .write_file(&format!("{}/new.txt", out_dir), "new\n")
+        .await
+        .unwrap();
+    let changes = recording.changes(&out_dir).await;
+    std::fs::remove_dir_all(&root).unwrap();
+
+    let changes: Vec<_> = changes
+        .iter()
+        .map(|write| (write.path.as_str(), &write.change))
+        .collect();
+    assert!(matches!(
+        changes.as_slice(),
+        [
+            ("kept.txt", FileChange::Unchanged),
+            ("new.txt", FileChange::Created)
+        ]
+    ));
+}
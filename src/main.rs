@@ -4,15 +4,27 @@ mod domain;
 mod infrastructure;
 mod output;
 
+use std::process::ExitCode;
+
 use adapters::cli::cli_adapter::CliAdapter;
+use adapters::cli::commands::Cli;
 use application::services::project_service::ProjectService;
+use clap::Parser;
+use infrastructure::database::sqlx_introspector::SqlxIntrospector;
 use infrastructure::file_system::local_file_system::LocalFileSystem;
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
     let file_system = LocalFileSystem;
     let project_service = ProjectService::new(file_system);
-    let cli_adapter = CliAdapter::new(project_service);
+    let cli_adapter = CliAdapter::new(project_service, SqlxIntrospector);
 
-    cli_adapter.run("./config.toml").await;
+    match cli_adapter.run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,34 @@
+use minijinja::{context, Value};
+
+use crate::domain::models::config::{Auth, Model};
+use crate::domain::models::template::ModelView;
+use crate::output::unwrap_option;
+
+/// Context of `auth.rs.tmpl`, argon2 password hashing and JWT issue/verify.
+pub fn auth_context(auth: &Auth) -> Value {
+    context! {
+        token_ttl => auth.token_ttl(),
+        refresh_token_ttl => auth.refresh_token_ttl(),
+    }
+}
+
+/// Context of `auth_handlers.rs.tmpl`, the `AuthUser` extractor and the login
+/// and refresh handlers. `role` is the role field of `user`, if any.
+pub fn auth_handlers_context(auth: &Auth, user_model: &Model) -> Value {
+    let role = auth.role_field.as_ref().map(|field| {
+        let optional = user_model
+            .fields
+            .iter()
+            .any(|f| &f.name == field && unwrap_option(&f.field_type).1);
+        context! { name => field, optional }
+    });
+    context! {
+        user => ModelView::from(user_model),
+        username => auth.username_field(),
+        password => auth.password_field(),
+        role,
+    }
+}
+
+#[cfg(test)]
+mod tests;
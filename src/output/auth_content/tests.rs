@@ -0,0 +1,128 @@
+use super::{auth_context, auth_handlers_context};
+use crate::domain::models::config::Config;
+use crate::output::TemplateEngine;
+use minijinja::Value;
+
+fn config(auth: &str) -> Config {
+    toml::from_str(&format!(
+        r#"
+project_name = "accounts"
+database_url = "postgres://localhost/accounts"
+database_type = "postgres"
+framework = "Axum"
+
+[auth]
+user_model = "User"
+{auth}
+
+[[models]]
+name = "User"
+table_name = "users"
+fields = [
+    {{ name = "id", type = "i64" }},
+    {{ name = "login", type = "String" }},
+    {{ name = "email", type = "String" }},
+    {{ name = "password_hash", type = "String" }},
+    {{ name = "role", type = "String" }},
+    {{ name = "team_role", type = "Option<String>" }},
+]
+"#
+    ))
+    .unwrap()
+}
+
+fn render(config: &Config, name: &str, context: Value) -> String {
+    TemplateEngine::new(config).render(name, context).unwrap()
+}
+
+fn auth(config: &Config) -> String {
+    render(
+        config,
+        "auth.rs.tmpl",
+        auth_context(config.auth.as_ref().unwrap()),
+    )
+}
+
+fn auth_handlers(config: &Config) -> String {
+    let context = auth_handlers_context(config.auth.as_ref().unwrap(), &config.models[0]);
+    render(config, "auth_handlers.rs.tmpl", context)
+}
+
+#[test]
+fn token_lifetimes_default_to_15_minutes_and_7_days() {
+    let auth = auth(&config(""));
+    assert!(
+        auth.contains("pub const ACCESS_TOKEN_TTL: u64 = 900;"),
+        "{}",
+        auth
+    );
+    assert!(
+        auth.contains("pub const REFRESH_TOKEN_TTL: u64 = 604800;"),
+        "{}",
+        auth
+    );
+}
+
+#[test]
+fn token_lifetimes_can_be_configured() {
+    let auth = auth(&config("token_ttl = 60\nrefresh_token_ttl = 3600"));
+    assert!(
+        auth.contains("pub const ACCESS_TOKEN_TTL: u64 = 60;"),
+        "{}",
+        auth
+    );
+    assert!(
+        auth.contains("pub const REFRESH_TOKEN_TTL: u64 = 3600;"),
+        "{}",
+        auth
+    );
+}
+
+#[test]
+fn the_auth_user_is_keyed_by_the_user_primary_key() {
+    let handlers = auth_handlers(&config(""));
+    assert!(handlers.contains("pub id: i64,"), "{}", handlers);
+}
+
+#[test]
+fn login_looks_the_user_up_by_the_username_field() {
+    let handlers = auth_handlers(&config(""));
+    assert!(
+        handlers.contains("state.user_service.get_one_by_email(body.email.clone())"),
+        "{}",
+        handlers
+    );
+    assert!(
+        handlers.contains("verify_password(&body.password, &user.password_hash)"),
+        "{}",
+        handlers
+    );
+
+    let handlers = auth_handlers(&config("username_field = \"login\""));
+    assert!(
+        handlers.contains("state.user_service.get_one_by_login(body.login.clone())"),
+        "{}",
+        handlers
+    );
+    assert!(handlers.contains("pub login: String,"), "{}", handlers);
+}
+
+#[test]
+fn tokens_carry_the_role_field() {
+    for (auth, role) in [
+        ("", "None"),
+        ("role_field = \"role\"", "Some(user.role.clone())"),
+        ("role_field = \"team_role\"", "user.team_role.clone()"),
+    ] {
+        let handlers = auth_handlers(&config(auth));
+        let issue = format!("token_pair(&user.id.to_string(), {})?", role);
+        // Both login and refresh issue a new pair
+        assert_eq!(
+            handlers.matches(&issue).count(),
+            2,
+            "{}\n\n{}",
+            issue,
+            handlers
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use crate::domain::models::config::{Config, DefaultValue, Endpoint, Field, Model};
+use crate::domain::models::relation::{Relation, RelationKind};
+use toml_edit::{Array, DocumentMut, Item, Value};
+
+/// Renders the project settings, models, fields and endpoints of `config`.
+pub fn config_toml_from(config: &Config) -> String {
+    let mut content = format!(
+        "project_name = {}\ndatabase_url = {}\ndatabase_type = {} # postgres, mysql, sqlite\n\n\
+         framework = \"{:?}\" # Axum, ActixWeb\n",
+        toml_string(&config.project_name),
+        toml_string(&config.database_url),
+        toml_string(&config.database_type),
+        config.framework
+    );
+    for model in &config.models {
+        content.push_str(&model_toml(model));
+    }
+    content
+}
+
+/// `config_toml_from` under a header pointing at what to do next, for `init`.
+pub fn init_config_toml(config: &Config) -> String {
+    format!(
+        "# prkserver project config, see the Readme for every setting.\n\
+         # `prkserver validate` checks it and `prkserver new` generates the project.\n\n{}",
+        config_toml_from(config)
+    )
+}
+
+/// One `[[models]]` table, preceded by a blank line.
+fn model_toml(model: &Model) -> String {
+    let mut content = format!(
+        "\n[[models]]\nname = {}\ntable_name = {}\n",
+        toml_string(&model.name),
+        toml_string(&model.table_name)
+    );
+    if let Some(response_dto) = model.response_dto {
+        content.push_str(&format!("response_dto = {}\n", response_dto));
+    }
+    if let Some(max_page_size) = model.max_page_size {
+        content.push_str(&format!("max_page_size = {}\n", max_page_size));
+    }
+    content.push_str("fields = [\n");
+    for field in &model.fields {
+        content.push_str(&format!("    {},\n", inline_field(field)));
+    }
+    content.push_str("]\n");
+    if let Some(endpoints) = &model.endpoints {
+        content.push_str("endpoints = [\n");
+        for endpoint in endpoints {
+            content.push_str(&format!("    {},\n", inline_endpoint(endpoint)));
+        }
+        content.push_str("]\n");
+    }
+    if let Some(relations) = &model.relations {
+        content.push_str("relations = [\n");
+        for relation in relations {
+            content.push_str(&format!("    {},\n", inline_relation(relation)));
+        }
+        content.push_str("]\n");
+    }
+    content
+}
+
+/// `content` with `model` appended as a new `[[models]]` table, leaving the
+/// rest of the file as it is.
+pub fn config_toml_with_model(content: &str, model: &Model) -> String {
+    let mut content = content.to_string();
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&model_toml(model));
+    content
+}
+
+/// `content` with `endpoint` added to the `endpoints` of the model named
+/// `model_name`, keeping comments and formatting elsewhere.
+pub fn config_toml_with_endpoint(
+    content: &str,
+    model_name: &str,
+    endpoint: &Endpoint,
+) -> Result<String, String> {
+    let mut document: DocumentMut = content.parse().map_err(|e| format!("{}", e))?;
+    let model = document
+        .get_mut("models")
+        .and_then(Item::as_array_of_tables_mut)
+        .and_then(|models| {
+            models
+                .iter_mut()
+                .find(|model| model.get("name").and_then(Item::as_str) == Some(model_name))
+        })
+        .ok_or_else(|| format!("there is no model named '{}'", model_name))?;
+    let endpoints = model
+        .entry("endpoints")
+        .or_insert_with(|| Item::Value(Value::Array(Array::new())))
+        .as_array_mut()
+        .ok_or_else(|| format!("the endpoints of '{}' are not an array", model_name))?;
+
+    let mut value: Value = inline_endpoint(endpoint)
+        .parse()
+        .map_err(|e| format!("{}", e))?;
+    // One endpoint per line, as `config_toml_from` writes them, unless they already share one
+    let one_per_line = endpoints.is_empty()
+        || endpoints.iter().any(|value| {
+            value
+                .decor()
+                .prefix()
+                .and_then(|prefix| prefix.as_str())
+                .is_some_and(|prefix| prefix.contains('\n'))
+        });
+    if one_per_line {
+        value.decor_mut().set_prefix("\n    ");
+        value.decor_mut().set_suffix("");
+        endpoints.push_formatted(value);
+        endpoints.set_trailing("\n");
+        endpoints.set_trailing_comma(true);
+    } else {
+        endpoints.push(value);
+    }
+    Ok(document.to_string())
+}
+
+fn inline_relation(relation: &Relation) -> String {
+    let kind = match relation.kind {
+        RelationKind::BelongsTo => "belongs_to",
+        RelationKind::HasMany => "has_many",
+        RelationKind::ManyToMany => "many_to_many",
+    };
+    let mut entries = vec![
+        format!("kind = \"{}\"", kind),
+        format!("model = {}", toml_string(&relation.model)),
+    ];
+    for (key, value) in [
+        ("name", &relation.name),
+        ("foreign_key", &relation.foreign_key),
+        ("join_table", &relation.join_table),
+    ] {
+        if let Some(value) = value {
+            entries.push(format!("{} = {}", key, toml_string(value)));
+        }
+    }
+    if let Some(nested_route) = relation.nested_route {
+        entries.push(format!("nested_route = {}", nested_route));
+    }
+    format!("{{ {} }}", entries.join(", "))
+}
+
+fn inline_endpoint(endpoint: &Endpoint) -> String {
+    let mut entries = vec![
+        format!("method = {}", toml_string(&endpoint.method)),
+        format!("path = {}", toml_string(&endpoint.path)),
+    ];
+    let strings = |values: &[String]| {
+        values
+            .iter()
+            .map(|value| toml_string(value))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if let Some(middlewares) = &endpoint.middlewares {
+        entries.push(format!("middlewares = [{}]", strings(middlewares)));
+    }
+    if let Some(auth) = endpoint.auth {
+        entries.push(format!("auth = {}", auth));
+    }
+    if let Some(roles) = &endpoint.roles {
+        entries.push(format!("roles = [{}]", strings(roles)));
+    }
+    if let Some(owner_field) = &endpoint.owner_field {
+        entries.push(format!("owner_field = {}", toml_string(owner_field)));
+    }
+    let mut params = String::new();
+    for (key, fields) in [
+        ("path_params", &endpoint.path_params),
+        ("query_params", &endpoint.query_params),
+        ("body_params", &endpoint.body_params),
+    ] {
+        if let Some(fields) = fields {
+            params.push_str(&format!(", {} = [\n", key));
+            for field in fields {
+                params.push_str(&format!("        {},\n", inline_field(field)));
+            }
+            params.push_str("    ]");
+        }
+    }
+    format!("{{ {}{} }}", entries.join(", "), params)
+}
+
+fn inline_field(field: &Field) -> String {
+    let mut entries = vec![
+        format!("name = {}", toml_string(&field.name)),
+        format!("type = {}", toml_string(&field.field_type)),
+    ];
+    let flags = [
+        ("primary_key", field.primary_key),
+        ("unique", field.unique),
+        ("index", field.index),
+        ("hidden", field.hidden),
+        ("auto_increment", field.auto_increment),
+    ];
+    for (key, value) in flags {
+        if let Some(value) = value {
+            entries.push(format!("{} = {}", key, value));
+        }
+    }
+    if let Some(column) = &field.column {
+        entries.push(format!("column = {}", toml_string(column)));
+    }
+    if let Some(max_length) = field.max_length {
+        entries.push(format!("max_length = {}", max_length));
+    }
+    if let Some(default) = &field.default {
+        let value = match default {
+            DefaultValue::Bool(value) => value.to_string(),
+            DefaultValue::Integer(value) => value.to_string(),
+            DefaultValue::Float(value) => toml::Value::Float(*value).to_string(),
+            DefaultValue::Text(value) => toml_string(value),
+            DefaultValue::Sql { sql } => format!("{{ sql = {} }}", toml_string(sql)),
+        };
+        entries.push(format!("default = {}", value));
+    }
+    if let Some(references) = &field.references {
+        entries.push(format!(
+            "references = {{ table = {}, column = {} }}",
+            toml_string(&references.table),
+            toml_string(&references.column)
+        ));
+    }
+    format!("{{ {} }}", entries.join(", "))
+}
+
+fn toml_string(value: &str) -> String {
+    toml::Value::String(value.to_string()).to_string()
+}
+
+#[cfg(test)]
+mod tests;
@@ -1,7 +0,0 @@
-pub fn docker_ignore_content() -> String {
-    r#"
-target
-Cargo.lock
-"#
-    .to_string()
-}
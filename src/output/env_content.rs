@@ -1,3 +1,18 @@
-pub fn env_content(database_url: &str) -> String {
-    format!("DATABASE_URL={}", database_url)
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// A 64 character hex secret for `.env`. `RandomState` keys are seeded from the OS,
+/// which is enough for a development secret without pulling in an RNG crate.
+pub fn jwt_secret() -> String {
+    (0..4)
+        .map(|_| format!("{:016x}", RandomState::new().build_hasher().finish()))
+        .collect()
+}
+
+/// The `JWT_SECRET` set in the contents of an existing `.env`.
+pub fn existing_jwt_secret(env: &str) -> Option<String> {
+    env.lines()
+        .filter_map(|line| line.trim().strip_prefix("JWT_SECRET="))
+        .map(|secret| secret.trim().to_string())
+        .find(|secret| !secret.is_empty())
 }
@@ -1,7 +0,0 @@
-pub fn git_ignore_content() -> String {
-    r#"
-/target
-.env
-"#
-    .to_string()
-}
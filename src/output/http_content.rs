@@ -1,108 +1,133 @@
-use crate::domain::models::config::{Endpoint, Framework, Model};
+use convert_case::{Case, Casing};
+use serde::Serialize;
 
-pub fn generate_handler(model: &Model, endpoint: &Endpoint, framework: Framework) -> String {
-    let method = endpoint.method.to_lowercase();
-    let path = &endpoint.path;
-    let handler_name = format!(
-        "{}{}",
-        method,
-        path.replace("/", "_").replace("{", "by_").replace("}", "")
-    );
-    let service_field = format!("{}_service", model.name.to_lowercase());
-    let model_name = &model.name;
-    let model_lower = model_name.to_lowercase();
+use crate::domain::models::config::{Endpoint, Model};
+use crate::domain::models::relation::{Include, NestedRoute, RelationKind};
+use crate::domain::models::template::{
+    patch_request_fields, update_request_fields, FieldView, ModelView,
+};
+use crate::output::unwrap_option;
 
-    let mut handler_code = String::new();
-    let mut extractors = Vec::new();
-
-    // Define framework-specific variable names
-    let state_var = match framework {
-        Framework::Axum => "state",
-        Framework::ActixWeb => "state",
-    };
-    let params_var = match framework {
-        Framework::Axum => "params",
-        Framework::ActixWeb => "path",
-    };
-    let json_type = match framework {
-        Framework::Axum => "Json",
-        Framework::ActixWeb => "web::Json",
-    };
+/// An endpoint as `handler.rs.tmpl` sees it. `id` is the path placeholder
+/// addressing a single row, if any.
+#[derive(Serialize)]
+pub struct EndpointView {
+    pub handler: String,
+    pub method: String,
+    pub path: String,
+    pub params_struct: String,
+    pub path_params: Option<Vec<FieldView>>,
+    pub id: Option<String>,
+    pub query_params: bool,
+    pub body_params: bool,
+    pub auth: bool,
+    pub roles: Option<Vec<String>>,
+    pub owner: Option<OwnerView>,
+}
 
-    // State extractor
-    let state_extractor = match framework {
-        Framework::Axum => format!("State({}): State<AppState>", state_var),
-        Framework::ActixWeb => format!("{}: web::Data<AppState>", state_var),
-    };
-    extractors.push(state_extractor);
+/// The `owner_field` of an endpoint. `in_body` tells whether the request body
+/// of a PUT or PATCH can hand the row over to someone else.
+#[derive(Serialize)]
+pub struct OwnerView {
+    pub name: String,
+    pub optional: bool,
+    pub in_body: bool,
+}
 
-    // Path parameters
-    if let Some(path_params) = &endpoint.path_params {
-        handler_code.push_str("#[derive(Deserialize)]\n");
-        handler_code.push_str(&format!("pub struct {}PathParams {{\n", model_name));
-        for param in path_params {
-            handler_code.push_str(&format!("    pub {}: {},\n", param.name, param.field_type));
+impl EndpointView {
+    pub fn new(model: &Model, endpoint: &Endpoint) -> Self {
+        let method = endpoint.method.to_lowercase();
+        let owner = endpoint.owner_field.as_ref().map(|owner| {
+            let body_fields = match method.as_str() {
+                "put" => update_request_fields(model),
+                "patch" => patch_request_fields(model),
+                _ => Vec::new(),
+            };
+            OwnerView {
+                name: owner.clone(),
+                optional: model
+                    .fields
+                    .iter()
+                    .any(|f| &f.name == owner && unwrap_option(&f.field_type).1),
+                in_body: body_fields.iter().any(|f| &f.name == owner),
+            }
+        });
+        Self {
+            handler: endpoint.handler_name(),
+            method,
+            path: endpoint.path.clone(),
+            params_struct: format!(
+                "{}PathParams",
+                endpoint.handler_name().to_case(Case::Pascal)
+            ),
+            path_params: endpoint
+                .path_params
+                .as_ref()
+                .map(|params| params.iter().map(FieldView::from).collect()),
+            id: endpoint.path_placeholders().pop(),
+            query_params: endpoint.query_params.is_some(),
+            body_params: endpoint.body_params.is_some(),
+            auth: endpoint.requires_auth(),
+            roles: endpoint.roles.clone(),
+            owner,
         }
-        handler_code.push_str("}\n\n");
-        let path_extractor = match framework {
-            Framework::Axum => format!("Path({}): Path<{}PathParams>", params_var, model_name),
-            Framework::ActixWeb => format!("{}: web::Path<{}PathParams>", params_var, model_name),
-        };
-        extractors.push(path_extractor);
     }
+}
 
-    // Query parameters
-    if endpoint.query_params.is_some() {
-        extractors.push(format!("Query(query): Query<{}QueryParams>", model_name));
-    }
+/// A relation `?include=` can embed, for `relations.rs.tmpl`. `optional` is
+/// set when a `belongs_to` foreign key may be null.
+#[derive(Serialize)]
+pub struct IncludeView {
+    pub name: String,
+    pub kind: RelationKind,
+    pub key: String,
+    pub optional: bool,
+    pub target: ModelView,
+}
 
-    // Body parameters
-    if endpoint.body_params.is_some() {
-        let body_extractor = match framework {
-            Framework::Axum => format!("Json(body): Json<{}>", model_name),
-            Framework::ActixWeb => format!("body: web::Json<{}>", model_name),
-        };
-        extractors.push(body_extractor);
+impl IncludeView {
+    pub fn new(model: &Model, include: &Include) -> Self {
+        Self {
+            name: include.name.clone(),
+            kind: include.kind,
+            key: include.key.clone(),
+            optional: include.kind == RelationKind::BelongsTo
+                && model
+                    .fields
+                    .iter()
+                    .any(|f| f.name == include.key && unwrap_option(&f.field_type).1),
+            target: ModelView::from(&include.target),
+        }
     }
+}
 
-    let extractor_str = extractors.join(", ");
+/// A nested route such as `GET /users/{user_id}/todos`, for
+/// `nested_handler.rs.tmpl`. `parameter` is the parent's id in the path.
+#[derive(Serialize)]
+pub struct NestedRouteView {
+    pub endpoint: EndpointView,
+    pub parameter: String,
+    pub key: String,
+    pub target: ModelView,
+}
 
-    // Handler body with parameterized variable names and JSON type
-    let handler_body = match (method.as_str(), path.as_str()) {
-        ("get", p) if p == format!("/{}", model_lower) => {
-            format!(
-                "let items = {}.{}.get_all().await?;\nOk({}(items))",
-                state_var, service_field, json_type
-            )
-        }
-        ("post", p) if p == format!("/{}", model_lower) => {
-            format!(
-                "let item = {}.{}.create(body).await?;\nOk({}(item))",
-                state_var, service_field, json_type
-            )
+impl NestedRouteView {
+    pub fn new(model: &Model, route: &NestedRoute) -> Self {
+        let endpoint = EndpointView::new(model, &route.endpoint);
+        Self {
+            parameter: endpoint
+                .path_params
+                .iter()
+                .flatten()
+                .next()
+                .map(|param| param.name.clone())
+                .unwrap_or_default(),
+            endpoint,
+            key: route.key.clone(),
+            target: ModelView::from(&route.target),
         }
-        ("get", p) if p.contains("{") => {
-            format!(
-                "let item = {}.{}.get_by_id({}.id).await?.ok_or(AppError::NotFound(format!(\"Not found\")))?;\nOk({}(item))",
-                state_var, service_field, params_var, json_type
-            )
-        }
-        _ => "// TODO: Implement handler logic\ntodo!()".to_string(),
-    };
-
-    // Define the return type
-    let return_type = match framework {
-        Framework::Axum => format!("Result<Json<{}>, AppError>", model_name),
-        Framework::ActixWeb => format!("Result<web::Json<{}>, AppError>", model_name),
-    };
-
-    // Assemble the handler code
-    handler_code.push_str(&format!(
-        "pub async fn {}({}) -> {} {{\n\
-             {}\n\
-         }}\n",
-        handler_name, extractor_str, return_type, handler_body
-    ));
-
-    handler_code
+    }
 }
+
+#[cfg(test)]
+mod tests;
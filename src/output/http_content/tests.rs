@@ -0,0 +1,410 @@
+use super::{EndpointView, IncludeView};
+use crate::domain::models::config::{Config, Framework};
+use crate::domain::models::relation::resolve_relations;
+use crate::domain::models::template::{ModelView, Template};
+use crate::output::TemplateEngine;
+use minijinja::context;
+
+fn config() -> Config {
+    toml::from_str(
+        r#"
+project_name = "notes"
+database_url = "postgres://localhost/notes"
+database_type = "postgres"
+framework = "Axum"
+
+[[models]]
+name = "Note"
+table_name = "notes"
+response_dto = true
+fields = [
+    { name = "id", type = "i32" },
+    { name = "body", type = "String" },
+    { name = "secret", type = "String", hidden = true },
+]
+max_page_size = 25
+endpoints = [{ method = "POST", path = "/notes" }, { method = "GET", path = "/notes" }]
+"#,
+    )
+    .unwrap()
+}
+
+/// Renders `handler.rs.tmpl` for the `index`th endpoint of the first model.
+fn render_handler(config: &Config, index: usize) -> String {
+    let model = &config.models[0];
+    let endpoint = &model.endpoints.as_ref().unwrap()[index];
+    TemplateEngine::new(config)
+        .render(
+            "handler.rs.tmpl",
+            context! {
+                model => ModelView::from(model),
+                endpoint => EndpointView::new(model, endpoint),
+                includes => Vec::<IncludeView>::new(),
+            },
+        )
+        .unwrap()
+}
+
+#[test]
+fn created_items_are_returned_as_the_response_dto() {
+    let mut config = config();
+    for (framework, response) in [
+        (
+            Framework::Axum,
+            "Ok((StatusCode::CREATED, Json(NoteResponse::from(item))))",
+        ),
+        (
+            Framework::ActixWeb,
+            "Ok(HttpResponse::Created().json(NoteResponse::from(item)))",
+        ),
+    ] {
+        config.framework = framework;
+        let handler = render_handler(&config, 0);
+        assert!(handler.contains(response), "{}", handler);
+    }
+}
+
+#[test]
+fn lists_return_a_page_with_a_link_header() {
+    let mut config = config();
+    let handler = render_handler(&config, 1);
+    assert!(
+        handler.contains("-> Result<([(header::HeaderName, String); 1], Json<Page<NoteResponse, i32>>), AppError>"),
+        "{}",
+        handler
+    );
+    assert!(
+        handler.contains("OriginalUri(uri): OriginalUri"),
+        "{}",
+        handler
+    );
+    assert!(
+        handler.contains("get_all(query).await?.map(NoteResponse::from);"),
+        "{}",
+        handler
+    );
+    assert!(
+        handler.contains("Ok(([(header::LINK, link)], Json(page)))"),
+        "{}",
+        handler
+    );
+
+    config.framework = Framework::ActixWeb;
+    let handler = render_handler(&config, 1);
+    assert!(
+        handler.contains("let link = link_header(req.path(), Some(req.query_string()), &page);"),
+        "{}",
+        handler
+    );
+    assert!(
+        handler.contains("HttpResponse::Ok().insert_header((header::LINK, link)).json(page)"),
+        "{}",
+        handler
+    );
+}
+
+#[test]
+fn the_link_header_helper_is_only_generated_with_a_list_endpoint() {
+    let mut config = config();
+    assert!(render_http(&config).contains("fn link_header<T, K: std::fmt::Display>("));
+
+    config.models[0].endpoints.as_mut().unwrap().pop();
+    assert!(!render_http(&config).contains("fn link_header"));
+}
+
+#[test]
+fn the_page_size_is_capped_per_model() {
+    let config = config();
+    let context = Template::new(config.clone()).service_context(&config.models[0]);
+    let service = TemplateEngine::new(&config)
+        .render("service.rs.tmpl", context)
+        .unwrap();
+    assert!(
+        service.contains("const MAX_PAGE_SIZE: i64 = 25;"),
+        "{}",
+        service
+    );
+}
+
+/// `User` has many `Todo`s through a nested route; a `Todo` may belong to a `User`.
+fn relations_config(framework: &str) -> Config {
+    let config = toml::from_str(&format!(
+        r#"
+project_name = "todos"
+database_url = "postgres://localhost/todos"
+database_type = "postgres"
+framework = "{framework}"
+
+[[models]]
+name = "User"
+table_name = "users"
+response_dto = true
+relations = [{{ kind = "has_many", model = "Todo", nested_route = true }}]
+fields = [{{ name = "id", type = "i32" }}, {{ name = "email", type = "String" }}]
+endpoints = [{{ method = "GET", path = "/users/{{id}}", path_params = [{{ name = "id", type = "i32" }}] }}]
+
+[[models]]
+name = "Todo"
+table_name = "todos"
+relations = [{{ kind = "belongs_to", model = "User" }}]
+fields = [
+    {{ name = "id", type = "i32" }},
+    {{ name = "task", type = "String" }},
+    {{ name = "user_id", type = "Option<i32>" }},
+]
+endpoints = [{{ method = "GET", path = "/todos" }}]
+"#
+    ))
+    .unwrap();
+    resolve_relations(&config)
+}
+
+fn render_http(config: &Config) -> String {
+    let context = Template::new(config.clone()).http_context();
+    TemplateEngine::new(config)
+        .render("http.rs.tmpl", context)
+        .unwrap()
+}
+
+#[test]
+fn nested_routes_404_on_a_missing_parent_and_list_the_children() {
+    let http = render_http(&relations_config("Axum"));
+    assert!(
+        http.contains(
+            "pub async fn get_users_by_user_id_todos(State(state): State<AppState>, \
+             Path(params): Path<GetUsersByUserIdTodosPathParams>) -> Result<Json<Vec<Todo>>, AppError> {\n\
+             state.user_service.get_by_id(params.user_id.clone()).await?\n\
+             .ok_or_else(|| AppError::NotFound(\"User not found\".to_string()))?;\n\
+             let items = state.todo_service.get_by_user_id(params.user_id).await?;\n\
+             Ok(Json(items))\n}"
+        ),
+        "{}",
+        http
+    );
+
+    let http = render_http(&relations_config("ActixWeb"));
+    assert!(
+        http.contains("path: web::Path<GetUsersByUserIdTodosPathParams>) -> Result<web::Json<Vec<Todo>>, AppError> {\nlet params = path.into_inner();"),
+        "{}",
+        http
+    );
+}
+
+#[test]
+fn includes_embed_the_requested_relations() {
+    let http = render_http(&relations_config("Axum"));
+    assert!(
+        http.contains("const USER_INCLUDES: &[&str] = &[\"todos\"];"),
+        "{}",
+        http
+    );
+    assert!(
+        http.contains("pub struct UserWithRelations {\n#[serde(flatten)]\npub item: UserResponse,"),
+        "{}",
+        http
+    );
+    assert!(
+        http.contains("Some(state.todo_service.get_by_user_id(item.id.clone()).await?)"),
+        "{}",
+        http
+    );
+    assert!(
+        http.contains("loaded.push(UserWithRelations { item: UserResponse::from(item), todos });"),
+        "{}",
+        http
+    );
+    // The foreign key is nullable, so a todo without a user loads nothing
+    assert!(
+        http.contains(
+            "let user = match item.user_id.clone() {\n\
+             Some(id) if include.iter().any(|name| name == \"user\") => \
+             state.user_service.get_by_id(id).await?.map(UserResponse::from),\n\
+             _ => None,\n};"
+        ),
+        "{}",
+        http
+    );
+    assert!(http.contains("fn parse_include("), "{}", http);
+}
+
+#[test]
+fn reads_of_models_with_relations_go_through_the_loader() {
+    let http = render_http(&relations_config("Axum"));
+    assert!(
+        http.contains(
+            "Query(query): Query<UserQueryParams>) -> Result<Json<UserWithRelations>, AppError>"
+        ),
+        "{}",
+        http
+    );
+    assert!(
+        http.contains(
+            "let item = user_with_relations(&state, vec![item], &include).await?.remove(0);"
+        ),
+        "{}",
+        http
+    );
+    assert!(
+        http.contains("let items = todo_with_relations(&state, std::mem::take(&mut page.items), &include).await?;"),
+        "{}",
+        http
+    );
+}
+
+#[test]
+fn endpoints_with_auth_take_the_auth_user() {
+    let mut config = config();
+    config.models[0].endpoints.as_mut().unwrap()[0].auth = Some(true);
+    let handler = render_handler(&config, 0);
+    assert!(handler.contains(", _user: AuthUser"), "{}", handler);
+    let handler = render_handler(&config, 1);
+    assert!(!handler.contains("AuthUser"), "{}", handler);
+}
+
+/// `Note`s owned by their `author_id`, which is nullable when `optional`.
+fn owner_config(optional: bool) -> Config {
+    let author_type = if optional { "Option<i32>" } else { "i32" };
+    toml::from_str(&format!(
+        r#"
+project_name = "notes"
+database_url = "postgres://localhost/notes"
+database_type = "postgres"
+framework = "Axum"
+
+[[models]]
+name = "Note"
+table_name = "notes"
+fields = [
+    {{ name = "id", type = "i32" }},
+    {{ name = "author_id", type = "{author_type}" }},
+    {{ name = "body", type = "String" }},
+]
+endpoints = [
+    {{ method = "POST", path = "/notes", owner_field = "author_id" }},
+    {{ method = "GET", path = "/notes/{{id}}", owner_field = "author_id" }},
+    {{ method = "PATCH", path = "/notes/{{id}}", owner_field = "author_id" }},
+    {{ method = "DELETE", path = "/notes/{{id}}", owner_field = "author_id" }},
+    {{ method = "GET", path = "/notes", roles = ["admin", "editor"] }},
+]
+"#
+    ))
+    .unwrap()
+}
+
+const FORBIDDEN: &str =
+    "return Err(AppError::Forbidden(\"not the owner of this Note\".to_string()));";
+
+#[test]
+fn roles_are_required_before_anything_else() {
+    let handler = render_handler(&owner_config(false), 4);
+    assert!(handler.contains(", user: AuthUser"), "{}", handler);
+    assert!(
+        handler.contains(") -> Result<([(header::HeaderName, String); 1], Json<Page<Note, i32>>), AppError> {\nuser.require_role(&[\"admin\", \"editor\"])?;\n"),
+        "{}",
+        handler
+    );
+}
+
+#[test]
+fn created_rows_must_belong_to_the_caller() {
+    let handler = render_handler(&owner_config(false), 0);
+    assert!(
+        handler.contains(&format!(
+            "if body.author_id != user.id {{\n{}\n}}\nlet item = state.note_service.create(body)",
+            FORBIDDEN
+        )),
+        "{}",
+        handler
+    );
+}
+
+#[test]
+fn reads_check_the_owner_of_the_row() {
+    let handler = render_handler(&owner_config(false), 1);
+    assert!(
+        handler.contains(&format!(
+            "if item.author_id != user.id {{\n{}\n}}",
+            FORBIDDEN
+        )),
+        "{}",
+        handler
+    );
+}
+
+#[test]
+fn writes_check_the_owner_before_touching_the_row() {
+    for (index, write) in [
+        (2, "let item = state.note_service.patch(id, body)"),
+        (3, "if !state.note_service.delete(id).await? {"),
+    ] {
+        let handler = render_handler(&owner_config(false), index);
+        let check = format!(
+            "let id = params.id;\nlet existing = state.note_service.get_by_id(id.clone()).await?.ok_or_else(|| AppError::NotFound(\"Note not found\".to_string()))?;\nif existing.author_id != user.id {{\n{}\n}}\n",
+            FORBIDDEN
+        );
+        let checked_at = handler.find(&check).expect(&handler);
+        assert!(
+            handler.find(write).expect(&handler) > checked_at,
+            "{}",
+            handler
+        );
+    }
+}
+
+#[test]
+fn patches_may_not_hand_the_row_to_someone_else() {
+    let handler = render_handler(&owner_config(false), 2);
+    assert!(
+        handler.contains(&format!(
+            "if body.author_id.as_ref().is_some_and(|owner| *owner != user.id) {{\n{}\n}}",
+            FORBIDDEN
+        )),
+        "{}",
+        handler
+    );
+}
+
+#[test]
+fn nullable_owners_are_compared_as_options() {
+    let config = owner_config(true);
+    let handler = render_handler(&config, 0);
+    assert!(
+        handler.contains("if body.author_id != Some(user.id.clone()) {"),
+        "{}",
+        handler
+    );
+    let handler = render_handler(&config, 2);
+    assert!(
+        handler.contains("if existing.author_id != Some(user.id.clone()) {"),
+        "{}",
+        handler
+    );
+    assert!(
+        handler.contains(
+            "if body.author_id.as_ref().is_some_and(|owner| *owner != Some(user.id.clone())) {"
+        ),
+        "{}",
+        handler
+    );
+}
+
+#[test]
+fn forbidden_errors_are_403s() {
+    for (framework, response) in [
+        (
+            Framework::Axum,
+            "AppError::Forbidden(_) => (StatusCode::FORBIDDEN, self.to_string()).into_response(),",
+        ),
+        (
+            Framework::ActixWeb,
+            "AppError::Forbidden(_) => HttpResponse::Forbidden().body(self.to_string()),",
+        ),
+    ] {
+        let mut config = config();
+        config.framework = framework;
+        let error = TemplateEngine::new(&config)
+            .render("error.rs.tmpl", context! {})
+            .unwrap();
+        assert!(error.contains(response), "{}", error);
+    }
+}
@@ -0,0 +1,633 @@
+use crate::domain::models::config::{DefaultValue, Field};
+use crate::domain::models::schema::{
+    backfill_default, requires_value, SchemaChange, SchemaSnapshot,
+};
+use minijinja::{context, Value};
+use serde::Serialize;
+
+/// A column as `column.sql.tmpl` renders it, e.g. `"email" VARCHAR(255) NOT NULL UNIQUE`.
+/// `sql_type` already ends in `PRIMARY KEY` for the primary key.
+#[derive(Serialize, PartialEq)]
+pub struct ColumnView {
+    pub name: String,
+    pub sql_type: String,
+    pub primary_key: bool,
+    pub nullable: bool,
+    pub unique: bool,
+    pub default: Option<String>,
+}
+
+impl ColumnView {
+    pub fn new(field: &Field, database_type: &str) -> Self {
+        let (inner_type, nullable) = unwrap_option(&field.field_type);
+        Self {
+            name: field.column_name().to_string(),
+            sql_type: if field.is_primary_key() {
+                primary_key_type(inner_type, field, database_type)
+            } else {
+                column_type(field, database_type)
+            },
+            primary_key: field.is_primary_key(),
+            nullable,
+            unique: field.is_unique(),
+            default: field
+                .default
+                .as_ref()
+                .map(|default| default_expression(default, database_type)),
+        }
+    }
+}
+
+/// A `FOREIGN KEY` constraint on `column`, named after its table and column.
+#[derive(Serialize)]
+pub struct ForeignKeyView {
+    pub name: String,
+    pub column: String,
+    pub referenced_table: String,
+    pub referenced_column: String,
+}
+
+impl ForeignKeyView {
+    fn new(table: &str, field: &Field) -> Option<Self> {
+        field.references.as_ref().map(|references| Self {
+            name: foreign_key_name(table, field),
+            column: field.column_name().to_string(),
+            referenced_table: references.table.clone(),
+            referenced_column: references.column.clone(),
+        })
+    }
+}
+
+/// The index of an `index = true` column.
+#[derive(Serialize)]
+pub struct IndexView {
+    pub name: String,
+    pub column: String,
+}
+
+impl IndexView {
+    fn new(table: &str, field: &Field) -> Self {
+        Self {
+            name: index_name(table, field),
+            column: field.column_name().to_string(),
+        }
+    }
+}
+
+/// A table as `create_table.sql.tmpl` renders it.
+#[derive(Serialize)]
+pub struct TableView {
+    pub table_name: String,
+    pub columns: Vec<ColumnView>,
+    pub foreign_keys: Vec<ForeignKeyView>,
+    pub indexes: Vec<IndexView>,
+}
+
+impl TableView {
+    pub fn new(table_name: &str, fields: &[Field], database_type: &str) -> Self {
+        Self {
+            table_name: table_name.to_string(),
+            columns: fields
+                .iter()
+                .map(|field| ColumnView::new(field, database_type))
+                .collect(),
+            foreign_keys: fields
+                .iter()
+                .filter_map(|field| ForeignKeyView::new(table_name, field))
+                .collect(),
+            indexes: fields
+                .iter()
+                .filter(|field| field.is_indexed())
+                .map(|field| IndexView::new(table_name, field))
+                .collect(),
+        }
+    }
+}
+
+/// Context of `create_table.sql.tmpl`: the columns, foreign key constraints
+/// and indexes of the table.
+pub fn create_table_context(table_name: &str, fields: &[Field], database_type: &str) -> Value {
+    Value::from_serialize(TableView::new(table_name, fields, database_type))
+}
+
+/// One statement of an incremental migration, rendered by `migration.sql.tmpl`
+/// after its `kind`.
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StatementView {
+    CreateTable(TableView),
+    DropTable {
+        table: String,
+    },
+    RenameTable {
+        from: String,
+        to: String,
+    },
+    /// `references` is set on SQLite, which only takes inline references.
+    AddColumn {
+        table: String,
+        column: ColumnView,
+        references: Option<ForeignKeyView>,
+    },
+    DropColumn {
+        table: String,
+        column: String,
+    },
+    RenameColumn {
+        table: String,
+        from: String,
+        to: String,
+    },
+    /// MySQL's `MODIFY COLUMN`.
+    ModifyColumn {
+        table: String,
+        column: ColumnView,
+    },
+    /// Postgres' `ALTER COLUMN ... TYPE`.
+    AlterColumnType {
+        table: String,
+        column: String,
+        sql_type: String,
+    },
+    /// Postgres' `ALTER COLUMN ... SET/DROP NOT NULL`.
+    AlterColumnNullable {
+        table: String,
+        column: String,
+        nullable: bool,
+    },
+    /// Postgres' `ALTER COLUMN ... SET/DROP DEFAULT`.
+    AlterColumnDefault {
+        table: String,
+        column: String,
+        default: Option<String>,
+    },
+    /// `constraint` names the Postgres constraint; MySQL names the index after the column.
+    AddUnique {
+        table: String,
+        column: String,
+        constraint: String,
+    },
+    DropUnique {
+        table: String,
+        column: String,
+        constraint: String,
+    },
+    AddForeignKey {
+        table: String,
+        foreign_key: ForeignKeyView,
+    },
+    DropForeignKey {
+        table: String,
+        name: String,
+    },
+    CreateIndex {
+        table: String,
+        index: IndexView,
+    },
+    DropIndex {
+        table: String,
+        name: String,
+    },
+    /// Copies `columns` into the table rebuilding `from`, see `rebuild_sqlite_table`.
+    CopyRows {
+        from: String,
+        to: String,
+        columns: Vec<String>,
+    },
+}
+
+/// Context of `migration.sql.tmpl`: `changes` described as text and the
+/// `statements` applying each of them, see `schema_migration_statements`.
+pub fn schema_migration_context(
+    changes: &[SchemaChange],
+    target: &SchemaSnapshot,
+    database_type: &str,
+) -> Value {
+    context! {
+        changes => changes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        statements => schema_migration_statements(changes, target, database_type),
+    }
+}
+
+/// The statements of an incremental migration applying `changes`, grouped by
+/// change, where `target` is the schema after the migration (SQLite needs it
+/// to rebuild altered tables).
+pub fn schema_migration_statements(
+    changes: &[SchemaChange],
+    target: &SchemaSnapshot,
+    database_type: &str,
+) -> Vec<Vec<StatementView>> {
+    let db = database_type.to_lowercase();
+    let mut rebuilt_tables = Vec::new();
+    let mut deferred_rebuilds = Vec::new();
+    let mut groups = Vec::new();
+    for change in changes {
+        let statements = match change {
+            SchemaChange::CreateTable(table) => vec![StatementView::CreateTable(TableView::new(
+                &table.table_name,
+                &table.fields,
+                database_type,
+            ))],
+            SchemaChange::DropTable(table) => vec![StatementView::DropTable {
+                table: table.clone(),
+            }],
+            SchemaChange::RenameTable { from, to } => vec![StatementView::RenameTable {
+                from: from.clone(),
+                to: to.clone(),
+            }],
+            SchemaChange::AddColumn { table, field } => {
+                // Existing rows need a value for a NOT NULL column
+                let mut field = field.clone();
+                if requires_value(&field) {
+                    field.default = backfill_default(&field);
+                }
+                let foreign_key = ForeignKeyView::new(table, &field);
+                // SQLite can't add constraints to an existing table, only inline references
+                let (references, constraint) = match foreign_key {
+                    Some(foreign_key) if db == "sqlite" => (Some(foreign_key), None),
+                    foreign_key => (None, foreign_key),
+                };
+                let mut statements = vec![StatementView::AddColumn {
+                    table: table.clone(),
+                    column: ColumnView::new(&field, database_type),
+                    references,
+                }];
+                statements.extend(constraint.map(|foreign_key| StatementView::AddForeignKey {
+                    table: table.clone(),
+                    foreign_key,
+                }));
+                if field.is_indexed() {
+                    statements.push(StatementView::CreateIndex {
+                        table: table.clone(),
+                        index: IndexView::new(table, &field),
+                    });
+                }
+                statements
+            }
+            // SQLite can't drop a column used by a foreign key, so the table is
+            // rebuilt once every other change has been applied.
+            SchemaChange::DropColumn { table, field }
+                if db == "sqlite" && field.references.is_some() =>
+            {
+                deferred_rebuilds.push(table.clone());
+                continue;
+            }
+            SchemaChange::DropColumn { table, field } => {
+                let mut statements = Vec::new();
+                // MySQL refuses to drop a column with a foreign key, SQLite an indexed one
+                if field.references.is_some() && db == "mysql" {
+                    statements.push(StatementView::DropForeignKey {
+                        table: table.clone(),
+                        name: foreign_key_name(table, field),
+                    });
+                }
+                if field.is_indexed() {
+                    statements.push(StatementView::DropIndex {
+                        table: table.clone(),
+                        name: index_name(table, field),
+                    });
+                }
+                statements.push(StatementView::DropColumn {
+                    table: table.clone(),
+                    column: field.column_name().to_string(),
+                });
+                statements
+            }
+            SchemaChange::RenameColumn { table, from, to } => vec![StatementView::RenameColumn {
+                table: table.clone(),
+                from: from.clone(),
+                to: to.clone(),
+            }],
+            SchemaChange::AlterColumn { table, from, to } => {
+                // Compare under the new name, so only attribute changes count
+                let from = Field {
+                    name: to.name.clone(),
+                    column: to.column.clone(),
+                    ..from.clone()
+                };
+                let mut statements = Vec::new();
+                if ColumnView::new(&from, database_type) != ColumnView::new(to, database_type)
+                    || from.references != to.references
+                {
+                    match db.as_str() {
+                        "postgres" => statements.extend(alter_postgres_column(table, &from, to)),
+                        "mysql" => statements.extend(alter_mysql_column(table, &from, to)),
+                        // SQLite cannot alter a column in place, so the table is rebuilt
+                        // once with its final definition and the data copied across.
+                        _ if rebuilt_tables.contains(table) => continue,
+                        _ => {
+                            rebuilt_tables.push(table.clone());
+                            match target.table(table) {
+                                Some(target) => statements.extend(rebuild_sqlite_table(
+                                    &target.table_name,
+                                    &target.fields,
+                                )),
+                                None => continue,
+                            }
+                        }
+                    }
+                }
+                // A rebuilt SQLite table already has its final indexes
+                if from.is_indexed() != to.is_indexed() && !rebuilt_tables.contains(table) {
+                    statements.push(if to.is_indexed() {
+                        StatementView::CreateIndex {
+                            table: table.clone(),
+                            index: IndexView::new(table, to),
+                        }
+                    } else {
+                        StatementView::DropIndex {
+                            table: table.clone(),
+                            name: index_name(table, to),
+                        }
+                    });
+                }
+                if statements.is_empty() {
+                    continue;
+                }
+                statements
+            }
+        };
+        groups.push(statements);
+    }
+    for table in deferred_rebuilds {
+        if rebuilt_tables.contains(&table) {
+            continue;
+        }
+        if let Some(target) = target.table(&table) {
+            groups.push(rebuild_sqlite_table(&target.table_name, &target.fields));
+            rebuilt_tables.push(table);
+        }
+    }
+    groups
+}
+
+fn alter_postgres_column(table: &str, from: &Field, to: &Field) -> Vec<StatementView> {
+    let column = to.column_name().to_string();
+    let mut statements = Vec::new();
+    let new_type = column_type(to, "postgres");
+    if new_type != column_type(from, "postgres") {
+        statements.push(StatementView::AlterColumnType {
+            table: table.to_string(),
+            column: column.clone(),
+            sql_type: new_type,
+        });
+    }
+    let (_, nullable) = unwrap_option(&to.field_type);
+    if nullable != unwrap_option(&from.field_type).1 {
+        statements.push(StatementView::AlterColumnNullable {
+            table: table.to_string(),
+            column: column.clone(),
+            nullable,
+        });
+    }
+    if from.default != to.default {
+        statements.push(StatementView::AlterColumnDefault {
+            table: table.to_string(),
+            column: column.clone(),
+            default: to
+                .default
+                .as_ref()
+                .map(|default| default_expression(default, "postgres")),
+        });
+    }
+    if from.references != to.references {
+        statements.extend(replace_foreign_key(table, from, to));
+    }
+    // Matches the name Postgres gives an inline `UNIQUE` constraint
+    let constraint = format!("{}_{}_key", table, column);
+    match (from.is_unique(), to.is_unique()) {
+        (false, true) => statements.push(StatementView::AddUnique {
+            table: table.to_string(),
+            column,
+            constraint,
+        }),
+        (true, false) => statements.push(StatementView::DropUnique {
+            table: table.to_string(),
+            column,
+            constraint,
+        }),
+        _ => {}
+    }
+    statements
+}
+
+fn alter_mysql_column(table: &str, from: &Field, to: &Field) -> Vec<StatementView> {
+    let column = to.column_name().to_string();
+    // `MODIFY COLUMN ... UNIQUE` would add a second index, so uniqueness is changed separately
+    let definition = |field: &Field| ColumnView {
+        unique: false,
+        ..ColumnView::new(field, "mysql")
+    };
+    let mut statements = Vec::new();
+    if definition(from) != definition(to) {
+        statements.push(StatementView::ModifyColumn {
+            table: table.to_string(),
+            column: definition(to),
+        });
+    }
+    if from.references != to.references {
+        statements.extend(replace_foreign_key(table, from, to));
+    }
+    // MySQL names an inline `UNIQUE` index after its column
+    match (from.is_unique(), to.is_unique()) {
+        (false, true) => statements.push(StatementView::AddUnique {
+            table: table.to_string(),
+            constraint: column.clone(),
+            column,
+        }),
+        (true, false) => statements.push(StatementView::DropUnique {
+            table: table.to_string(),
+            constraint: column.clone(),
+            column,
+        }),
+        _ => {}
+    }
+    statements
+}
+
+/// Rebuilds a SQLite table with its final definition: a new table is filled
+/// from the old one, which it then replaces.
+fn rebuild_sqlite_table(table_name: &str, fields: &[Field]) -> Vec<StatementView> {
+    let temporary = format!("{}__prkserver_new", table_name);
+    let unindexed = fields
+        .iter()
+        .map(|f| Field {
+            index: None,
+            ..f.clone()
+        })
+        .collect::<Vec<_>>();
+    let mut statements = vec![
+        StatementView::CreateTable(TableView::new(&temporary, &unindexed, "sqlite")),
+        StatementView::CopyRows {
+            from: table_name.to_string(),
+            to: temporary.clone(),
+            columns: fields.iter().map(|f| f.column_name().to_string()).collect(),
+        },
+        StatementView::DropTable {
+            table: table_name.to_string(),
+        },
+        StatementView::RenameTable {
+            from: temporary,
+            to: table_name.to_string(),
+        },
+    ];
+    statements.extend(fields.iter().filter(|f| f.is_indexed()).map(|f| {
+        StatementView::CreateIndex {
+            table: table_name.to_string(),
+            index: IndexView::new(table_name, f),
+        }
+    }));
+    statements
+}
+
+/// Name of the constraint created for a column with `references`.
+fn foreign_key_name(table: &str, field: &Field) -> String {
+    format!("fk_{}_{}", table, field.column_name())
+}
+
+fn replace_foreign_key(table: &str, from: &Field, to: &Field) -> Vec<StatementView> {
+    let mut statements = Vec::new();
+    if from.references.is_some() {
+        statements.push(StatementView::DropForeignKey {
+            table: table.to_string(),
+            name: foreign_key_name(table, from),
+        });
+    }
+    statements.extend(ForeignKeyView::new(table, to).map(|foreign_key| {
+        StatementView::AddForeignKey {
+            table: table.to_string(),
+            foreign_key,
+        }
+    }));
+    statements
+}
+
+/// Name of the index created for an `index = true` column.
+fn index_name(table: &str, field: &Field) -> String {
+    format!("idx_{}_{}", table, field.column_name())
+}
+
+/// Column type for a field, honouring `max_length` on string columns.
+fn column_type(field: &Field, database_type: &str) -> String {
+    let (inner_type, _) = unwrap_option(&field.field_type);
+    match field.max_length {
+        Some(max_length) if is_string_type(inner_type) => format!("VARCHAR({})", max_length),
+        _ => sql_column_type(inner_type, database_type),
+    }
+}
+
+/// SQL for a column default; text is quoted as a string literal.
+fn default_expression(default: &DefaultValue, database_type: &str) -> String {
+    match default {
+        DefaultValue::Bool(value) if database_type.eq_ignore_ascii_case("sqlite") => {
+            (*value as i32).to_string()
+        }
+        DefaultValue::Bool(value) => value.to_string().to_uppercase(),
+        DefaultValue::Integer(value) => value.to_string(),
+        DefaultValue::Float(value) => value.to_string(),
+        DefaultValue::Text(value) => format!("'{}'", value.replace('\'', "''")),
+        // MySQL only accepts expression defaults in parentheses
+        DefaultValue::Sql { sql } if database_type.eq_ignore_ascii_case("mysql") => {
+            match sql.to_uppercase().as_str() {
+                "CURRENT_TIMESTAMP" | "NULL" => sql.clone(),
+                _ => format!("({})", sql),
+            }
+        }
+        DefaultValue::Sql { sql } => sql.clone(),
+    }
+}
+
+/// Maps a Rust type from `Field::field_type` to the column type of the given database.
+pub fn sql_column_type(field_type: &str, database_type: &str) -> String {
+    let db = database_type.to_lowercase();
+    let field_type = type_name(field_type);
+    match (field_type.as_str(), db.as_str()) {
+        ("i8" | "i16" | "u8", "sqlite") => "INTEGER",
+        ("i8" | "i16" | "u8", _) => "SMALLINT",
+        ("i32" | "u16", "mysql") => "INT",
+        ("i32" | "u16", _) => "INTEGER",
+        ("u32", "mysql") => "INT UNSIGNED",
+        ("i64" | "u32" | "u64" | "isize" | "usize", "sqlite") => "INTEGER",
+        ("u64" | "usize", "mysql") => "BIGINT UNSIGNED",
+        ("i64" | "u32" | "u64" | "isize" | "usize", _) => "BIGINT",
+        ("f32", "mysql") => "FLOAT",
+        ("f32", _) => "REAL",
+        ("f64", "postgres") => "DOUBLE PRECISION",
+        ("f64", "mysql") => "DOUBLE",
+        ("f64", _) => "REAL",
+        ("bool", "sqlite") => "INTEGER",
+        ("bool", _) => "BOOLEAN",
+        ("String" | "str" | "&str", "mysql") => "VARCHAR(255)",
+        ("Vec<u8>", "postgres") => "BYTEA",
+        ("Vec<u8>", _) => "BLOB",
+        ("Uuid", "postgres") => "UUID",
+        ("Uuid", "mysql") => "CHAR(36)",
+        ("NaiveDate", "sqlite") => "TEXT",
+        ("NaiveDate", _) => "DATE",
+        ("NaiveTime", "sqlite") => "TEXT",
+        ("NaiveTime", _) => "TIME",
+        ("NaiveDateTime", "mysql") => "DATETIME",
+        ("NaiveDateTime", "postgres") => "TIMESTAMP",
+        ("DateTime<Utc>", "mysql") => "TIMESTAMP",
+        ("DateTime<Utc>", "postgres") => "TIMESTAMPTZ",
+        ("Value" | "JsonValue", "postgres") => "JSONB",
+        ("Value" | "JsonValue", "mysql") => "JSON",
+        _ => "TEXT",
+    }
+    .to_string()
+}
+
+/// Drops module paths, e.g. `chrono::DateTime<chrono::Utc>` → `DateTime<Utc>`.
+pub fn type_name(field_type: &str) -> String {
+    field_type
+        .replace(' ', "")
+        .split_inclusive(['<', '>', ','])
+        .map(|part| part.rsplit("::").next().unwrap_or(part))
+        .collect()
+}
+
+/// Column type for the primary key, auto-incrementing when the field asks for it.
+fn primary_key_type(field_type: &str, field: &Field, database_type: &str) -> String {
+    let db = database_type.to_lowercase();
+    match (field_type, db.as_str(), field.is_auto_increment()) {
+        ("i32", "postgres", true) => "SERIAL PRIMARY KEY".to_string(),
+        ("i64", "postgres", true) => "BIGSERIAL PRIMARY KEY".to_string(),
+        ("i32", "mysql", true) => "INT AUTO_INCREMENT PRIMARY KEY".to_string(),
+        ("i64", "mysql", true) => "BIGINT AUTO_INCREMENT PRIMARY KEY".to_string(),
+        ("i32" | "i64", "sqlite", true) => "INTEGER PRIMARY KEY AUTOINCREMENT".to_string(),
+        _ => format!("{} PRIMARY KEY", column_type(field, database_type)),
+    }
+}
+
+/// Strips `Option<...>`, returning the inner type and whether the column is nullable.
+pub fn unwrap_option(field_type: &str) -> (&str, bool) {
+    let trimmed = field_type.trim();
+    match trimmed
+        .strip_prefix("Option<")
+        .and_then(|inner| inner.strip_suffix('>'))
+    {
+        Some(inner) => (inner.trim(), true),
+        None => (trimmed, false),
+    }
+}
+
+pub fn quote_identifier(name: &str, database_type: &str) -> String {
+    match database_type.to_lowercase().as_str() {
+        "mysql" => format!("`{}`", name),
+        _ => format!("\"{}\"", name),
+    }
+}
+
+pub fn is_integer_type(field_type: &str) -> bool {
+    matches!(
+        field_type.trim(),
+        "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" | "isize" | "usize"
+    )
+}
+
+pub fn is_string_type(field_type: &str) -> bool {
+    matches!(field_type.trim(), "String" | "str" | "&str")
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,153 @@
+use super::{create_table_context, sql_column_type};
+use crate::domain::models::config::{resolve_primary_key, Config, DefaultValue, Field};
+use crate::output::TemplateEngine;
+
+fn config(database_type: &str) -> Config {
+    toml::from_str(&format!(
+        r#"
+project_name = "notes"
+database_url = "{database_type}://localhost/notes"
+database_type = "{database_type}"
+framework = "Axum"
+models = []
+"#
+    ))
+    .unwrap()
+}
+
+fn field(name: &str, field_type: &str) -> Field {
+    Field {
+        name: name.to_string(),
+        field_type: field_type.to_string(),
+        ..Field::default()
+    }
+}
+
+/// `create_table.sql.tmpl` rendered for a `notes` table with `fields`, keyed
+/// by `id` unless a field says otherwise.
+fn create_table(database_type: &str, fields: &[Field]) -> String {
+    TemplateEngine::new(&config(database_type))
+        .render(
+            "create_table.sql.tmpl",
+            create_table_context("notes", &resolve_primary_key(fields), database_type),
+        )
+        .unwrap()
+}
+
+#[test]
+fn tables_are_created_with_the_column_types_of_each_database() {
+    let fields = [
+        field("id", "i32"),
+        field("body", "String"),
+        field("stars", "f64"),
+        field("done", "bool"),
+        field("created_at", "chrono::DateTime<chrono::Utc>"),
+    ];
+    for (database_type, expected) in [
+        (
+            "postgres",
+            "CREATE TABLE IF NOT EXISTS \"notes\" (\n    \
+             \"id\" SERIAL PRIMARY KEY,\n    \
+             \"body\" TEXT NOT NULL,\n    \
+             \"stars\" DOUBLE PRECISION NOT NULL,\n    \
+             \"done\" BOOLEAN NOT NULL,\n    \
+             \"created_at\" TIMESTAMPTZ NOT NULL\n);\n",
+        ),
+        (
+            "mysql",
+            "CREATE TABLE IF NOT EXISTS `notes` (\n    \
+             `id` INT AUTO_INCREMENT PRIMARY KEY,\n    \
+             `body` VARCHAR(255) NOT NULL,\n    \
+             `stars` DOUBLE NOT NULL,\n    \
+             `done` BOOLEAN NOT NULL,\n    \
+             `created_at` TIMESTAMP NOT NULL\n);\n",
+        ),
+        (
+            "sqlite",
+            "CREATE TABLE IF NOT EXISTS \"notes\" (\n    \
+             \"id\" INTEGER PRIMARY KEY AUTOINCREMENT,\n    \
+             \"body\" TEXT NOT NULL,\n    \
+             \"stars\" REAL NOT NULL,\n    \
+             \"done\" INTEGER NOT NULL,\n    \
+             \"created_at\" TEXT NOT NULL\n);\n",
+        ),
+    ] {
+        assert_eq!(create_table(database_type, &fields), expected);
+    }
+}
+
+#[test]
+fn optional_fields_give_nullable_columns() {
+    let sql = create_table(
+        "postgres",
+        &[field("id", "i64"), field("summary", "Option<String>")],
+    );
+    assert!(sql.contains("\"id\" BIGSERIAL PRIMARY KEY,"), "{}", sql);
+    assert!(sql.contains("\"summary\" TEXT\n"), "{}", sql);
+}
+
+#[test]
+fn unknown_types_fall_back_to_text() {
+    for database_type in ["postgres", "mysql", "sqlite"] {
+        assert_eq!(sql_column_type("Money", database_type), "TEXT");
+    }
+    assert_eq!(sql_column_type("uuid::Uuid", "postgres"), "UUID");
+    assert_eq!(sql_column_type("Uuid", "mysql"), "CHAR(36)");
+}
+
+#[test]
+fn field_attributes_shape_the_column_definitions() {
+    let fields = [
+        Field {
+            primary_key: Some(true),
+            max_length: Some(64),
+            ..field("slug", "String")
+        },
+        Field {
+            unique: Some(true),
+            column: Some("email_address".to_string()),
+            index: Some(true),
+            ..field("email", "String")
+        },
+        Field {
+            default: Some(DefaultValue::Text("it's new".to_string())),
+            ..field("status", "String")
+        },
+        Field {
+            default: Some(DefaultValue::Bool(true)),
+            ..field("active", "bool")
+        },
+        Field {
+            default: Some(DefaultValue::Sql {
+                sql: "CURRENT_TIMESTAMP".to_string(),
+            }),
+            ..field("created_at", "NaiveDateTime")
+        },
+    ];
+    assert_eq!(
+        create_table("sqlite", &fields),
+        "CREATE TABLE IF NOT EXISTS \"notes\" (\n    \
+         \"slug\" VARCHAR(64) PRIMARY KEY,\n    \
+         \"email_address\" TEXT NOT NULL UNIQUE,\n    \
+         \"status\" TEXT NOT NULL DEFAULT 'it''s new',\n    \
+         \"active\" INTEGER NOT NULL DEFAULT 1,\n    \
+         \"created_at\" TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP\n);\n\
+         CREATE INDEX \"idx_notes_email_address\" ON \"notes\" (\"email_address\");\n"
+    );
+    let sql = create_table("postgres", &fields);
+    assert!(
+        sql.contains("\"active\" BOOLEAN NOT NULL DEFAULT TRUE"),
+        "{}",
+        sql
+    );
+}
+
+#[test]
+fn integer_keys_only_auto_increment_unless_told_otherwise() {
+    let fields = [Field {
+        auto_increment: Some(false),
+        ..field("id", "i64")
+    }];
+    let sql = create_table("mysql", &fields);
+    assert!(sql.contains("`id` BIGINT PRIMARY KEY\n"), "{}", sql);
+}
@@ -1,19 +1,15 @@
-pub mod cargo_toml_content;
-pub mod compose_yaml_content;
-pub mod database_connection_content;
-pub mod docker_ignore_content;
-pub mod dockerfile_content;
+pub mod auth_content;
+pub mod config_toml_content;
 pub mod env_content;
-pub mod gitignore_content;
 pub mod http_content;
-pub mod main_content;
+pub mod migration_content;
+pub mod openapi_content;
+pub mod template_engine;
 
-pub use cargo_toml_content::*;
-pub use compose_yaml_content::*;
-pub use database_connection_content::*;
-pub use docker_ignore_content::*;
-pub use dockerfile_content::*;
+pub use auth_content::*;
+pub use config_toml_content::*;
 pub use env_content::*;
-pub use gitignore_content::*;
 pub use http_content::*;
-pub use main_content::*;
+pub use migration_content::*;
+pub use openapi_content::*;
+pub use template_engine::*;
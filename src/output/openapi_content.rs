@@ -0,0 +1,572 @@
+use serde_json::{json, Map, Value};
+
+use crate::domain::models::api_key::API_KEY_PATH;
+use crate::domain::models::config::{Config, DefaultValue, Endpoint, Field, Model};
+use crate::domain::models::relation::{includes, nested_routes, RelationKind};
+use crate::domain::models::template::{
+    create_request_fields, patch_request_fields, primary_key, query_param_fields,
+    update_request_fields,
+};
+use crate::output::{type_name, unwrap_option};
+
+/// `openapi.yaml`: the OpenAPI 3.1 document describing the generated API.
+pub fn openapi_yaml_content(config: &Config) -> String {
+    serde_yaml::to_string(&openapi_document(config)).expect("OpenAPI documents serialize to YAML")
+}
+
+/// Context of `openapi.yaml.tmpl` and `openapi.rs.tmpl`, which serves the
+/// document at `/api/openapi.json` and a Swagger UI at `/api/docs`.
+pub fn openapi_context(config: &Config) -> minijinja::Value {
+    minijinja::context! {
+        document => minijinja::Value::from_serialize(openapi_document(config)),
+    }
+}
+
+/// Builds the document from the resolved config: every endpoint, nested
+/// route and the routes `[auth]` and `[api_keys]` add.
+pub fn openapi_document(config: &Config) -> Value {
+    let mut builder = OpenApiBuilder {
+        config,
+        paths: Map::new(),
+        schemas: Map::new(),
+        security_schemes: Map::new(),
+    };
+    for model in &config.models {
+        for endpoint in model.endpoints.iter().flatten() {
+            let operation = builder.operation(model, endpoint);
+            builder.add_operation(&endpoint.path, &endpoint.method, operation);
+        }
+        for route in nested_routes(config, model) {
+            let operation = builder.nested_operation(model, &route.endpoint, &route.target);
+            builder.add_operation(&route.endpoint.path, "get", operation);
+        }
+    }
+    builder.auth_operations();
+    builder.api_key_operations();
+
+    let mut components = Map::new();
+    components.insert("schemas".into(), Value::Object(builder.schemas));
+    if !builder.security_schemes.is_empty() {
+        components.insert(
+            "securitySchemes".into(),
+            Value::Object(builder.security_schemes),
+        );
+    }
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": config.project_name,
+            "version": "0.1.0",
+        },
+        "servers": [{ "url": "/api" }],
+        "paths": builder.paths,
+        "components": components,
+    })
+}
+
+struct OpenApiBuilder<'a> {
+    config: &'a Config,
+    paths: Map<String, Value>,
+    schemas: Map<String, Value>,
+    security_schemes: Map<String, Value>,
+}
+
+impl OpenApiBuilder<'_> {
+    fn add_operation(&mut self, path: &str, method: &str, operation: Value) {
+        let item = self
+            .paths
+            .entry(openapi_path(path))
+            .or_insert_with(|| json!({}));
+        item[method.to_lowercase()] = operation;
+    }
+
+    fn operation(&mut self, model: &Model, endpoint: &Endpoint) -> Value {
+        let method = endpoint.method.to_lowercase();
+        let model_name = &model.name;
+        let is_list = method == "get" && endpoint.path_placeholders().is_empty();
+        let includes = includes(self.config, model);
+
+        let mut parameters = path_parameters(endpoint);
+        if is_list {
+            for field in query_param_fields(model) {
+                parameters.push(parameter("query", &field.name, field_schema(&field), false));
+            }
+            let cursor = field_schema(&primary_key(model));
+            parameters.extend([
+                parameter("query", "sort", json!({ "type": "string", "description": "Comma-separated fields, `-` for descending" }), false),
+                parameter("query", "limit", json!({ "type": "integer", "minimum": 1 }), false),
+                parameter("query", "offset", json!({ "type": "integer", "minimum": 0 }), false),
+                parameter("query", "after", cursor, false),
+            ]);
+        } else {
+            for field in endpoint.query_params.iter().flatten() {
+                parameters.push(parameter("query", &field.name, field_schema(field), false));
+            }
+        }
+        if method == "get" && !includes.is_empty() {
+            let names = includes
+                .iter()
+                .map(|include| include.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            parameters.push(parameter(
+                "query",
+                "include",
+                json!({ "type": "string", "description": format!("Comma-separated relations to load: {}", names) }),
+                false,
+            ));
+        }
+
+        let request_body = match method.as_str() {
+            "post" => Some(self.request_schema(
+                &format!("Create{}Request", model_name),
+                &create_request_fields(model),
+                true,
+            )),
+            "put" => Some(self.request_schema(
+                &format!("Update{}Request", model_name),
+                &update_request_fields(model),
+                true,
+            )),
+            "patch" => Some(self.request_schema(
+                &format!("Patch{}Request", model_name),
+                &patch_request_fields(model),
+                false,
+            )),
+            _ if endpoint.body_params.is_some() => Some(self.read_schema(model)),
+            _ => None,
+        };
+
+        let read = if includes.is_empty() {
+            self.read_schema(model)
+        } else {
+            self.with_relations_schema(model)
+        };
+        let mut responses = Map::new();
+        match (method.as_str(), is_list) {
+            ("get", true) => {
+                let page = self.page_schema(model, &read);
+                let mut response = json_response("A page of results", schema_ref(&page));
+                response["headers"] = json!({
+                    "Link": {
+                        "description": "`next`/`prev` links to neighbouring pages",
+                        "schema": { "type": "string" },
+                    }
+                });
+                responses.insert("200".into(), response);
+            }
+            ("get", false) => {
+                responses.insert("200".into(), json_response("OK", schema_ref(&read)));
+            }
+            ("post", _) => {
+                let created = self.read_schema(model);
+                responses.insert("201".into(), json_response("Created", schema_ref(&created)));
+            }
+            ("put" | "patch", _) => {
+                let updated = self.read_schema(model);
+                responses.insert("200".into(), json_response("Updated", schema_ref(&updated)));
+            }
+            ("delete", _) => {
+                responses.insert("204".into(), json!({ "description": "Deleted" }));
+            }
+            _ => {}
+        }
+        if !endpoint.path_placeholders().is_empty() {
+            responses.insert(
+                "404".into(),
+                error_response(&format!("{} not found", model_name)),
+            );
+        }
+        if request_body.is_some() || is_list {
+            responses.insert("400".into(), error_response("Invalid request"));
+        }
+
+        let security = self.security(endpoint);
+        if !security.is_empty() {
+            responses.insert(
+                "401".into(),
+                error_response("Missing or invalid credentials"),
+            );
+        }
+        if endpoint.roles.is_some() || endpoint.owner_field.is_some() {
+            responses.insert("403".into(), error_response("Not allowed for this caller"));
+        }
+
+        let mut operation = json!({
+            "tags": [model_name],
+            "operationId": endpoint.handler_name(),
+        });
+        if !parameters.is_empty() {
+            operation["parameters"] = Value::Array(parameters);
+        }
+        if let Some(body) = request_body {
+            operation["requestBody"] = json!({
+                "required": true,
+                "content": { "application/json": { "schema": schema_ref(&body) } },
+            });
+        }
+        operation["responses"] = Value::Object(responses);
+        if !security.is_empty() {
+            operation["security"] = json!([security]);
+        }
+        operation
+    }
+
+    fn nested_operation(&mut self, model: &Model, endpoint: &Endpoint, target: &Model) -> Value {
+        let read = self.read_schema(target);
+        let mut operation = json!({
+            "tags": [model.name],
+            "operationId": endpoint.handler_name(),
+            "parameters": path_parameters(endpoint),
+            "responses": {
+                "200": json_response("OK", json!({ "type": "array", "items": schema_ref(&read) })),
+                "404": error_response(&format!("{} not found", model.name)),
+            },
+        });
+        let security = self.security(endpoint);
+        if !security.is_empty() {
+            operation["responses"]["401"] = error_response("Missing or invalid credentials");
+            operation["security"] = json!([security]);
+        }
+        operation
+    }
+
+    /// One requirement holding the bearer token and every header the
+    /// endpoint's middlewares check; all of them have to be sent.
+    fn security(&mut self, endpoint: &Endpoint) -> Map<String, Value> {
+        let mut requirement = Map::new();
+        if endpoint.requires_auth() && self.config.auth.is_some() {
+            self.security_schemes.insert(
+                "bearerAuth".into(),
+                json!({ "type": "http", "scheme": "bearer", "bearerFormat": "JWT" }),
+            );
+            requirement.insert("bearerAuth".into(), json!([]));
+        }
+        for function in endpoint.middleware_functions() {
+            let Some(middleware) = self
+                .config
+                .middlewares
+                .iter()
+                .flatten()
+                .find(|m| m.function_name() == function)
+            else {
+                continue;
+            };
+            for check in middleware.header_checks() {
+                self.security_schemes.insert(
+                    check.header_key.clone(),
+                    json!({ "type": "apiKey", "in": "header", "name": check.header_key }),
+                );
+                requirement.insert(check.header_key.clone(), json!([]));
+            }
+        }
+        requirement
+    }
+
+    /// The model as handlers return it: `{Model}Response` or the model itself,
+    /// without hidden fields either way.
+    fn read_schema(&mut self, model: &Model) -> String {
+        let name = if model.response_dto.unwrap_or(false) {
+            format!("{}Response", model.name)
+        } else {
+            model.name.clone()
+        };
+        if !self.schemas.contains_key(&name) {
+            let fields = model
+                .fields
+                .iter()
+                .filter(|f| !f.is_hidden())
+                .cloned()
+                .collect::<Vec<_>>();
+            self.schemas
+                .insert(name.clone(), object_schema(&fields, true));
+        }
+        name
+    }
+
+    fn request_schema(&mut self, name: &str, fields: &[Field], required: bool) -> String {
+        self.schemas
+            .insert(name.to_string(), object_schema(fields, required));
+        name.to_string()
+    }
+
+    fn with_relations_schema(&mut self, model: &Model) -> String {
+        let read = self.read_schema(model);
+        let mut properties = Map::new();
+        for include in includes(self.config, model) {
+            let target = self.read_schema(&include.target);
+            let schema = match include.kind {
+                RelationKind::BelongsTo => schema_ref(&target),
+                _ => json!({ "type": "array", "items": schema_ref(&target) }),
+            };
+            properties.insert(include.name.clone(), schema);
+        }
+        let name = format!("{}WithRelations", model.name);
+        self.schemas.insert(
+            name.clone(),
+            json!({
+                "description": "Relations are only present when requested with `include`",
+                "allOf": [schema_ref(&read), { "type": "object", "properties": properties }],
+            }),
+        );
+        name
+    }
+
+    fn page_schema(&mut self, model: &Model, items: &str) -> String {
+        let name = format!("{}Page", items);
+        let cursor = nullable(field_schema(&primary_key(model)));
+        self.schemas.insert(
+            name.clone(),
+            json!({
+                "type": "object",
+                "required": ["items", "total", "limit", "offset", "next_cursor"],
+                "properties": {
+                    "items": { "type": "array", "items": schema_ref(items) },
+                    "total": { "type": "integer", "format": "int64" },
+                    "limit": { "type": "integer", "format": "int64" },
+                    "offset": { "type": "integer", "format": "int64" },
+                    "next_cursor": cursor,
+                },
+            }),
+        );
+        name
+    }
+
+    fn auth_operations(&mut self) {
+        let Some(auth) = &self.config.auth else {
+            return;
+        };
+        let string = json!({ "type": "string" });
+        let mut login = Map::new();
+        login.insert(auth.username_field().to_string(), string.clone());
+        login.insert("password".into(), string.clone());
+        self.schemas.insert(
+            "LoginRequest".into(),
+            json!({
+                "type": "object",
+                "required": [auth.username_field(), "password"],
+                "properties": login,
+            }),
+        );
+        self.schemas.insert(
+            "RefreshRequest".into(),
+            json!({
+                "type": "object",
+                "required": ["refresh_token"],
+                "properties": { "refresh_token": string },
+            }),
+        );
+        self.schemas.insert(
+            "TokenResponse".into(),
+            json!({
+                "type": "object",
+                "required": ["access_token", "refresh_token", "token_type", "expires_in"],
+                "properties": {
+                    "access_token": string,
+                    "refresh_token": string,
+                    "token_type": { "type": "string", "const": "Bearer" },
+                    "expires_in": { "type": "integer", "description": "Access token lifetime in seconds" },
+                },
+            }),
+        );
+        for (path, handler, request) in [
+            ("/auth/login", "login", "LoginRequest"),
+            ("/auth/refresh", "refresh", "RefreshRequest"),
+        ] {
+            let operation = json!({
+                "tags": ["auth"],
+                "operationId": handler,
+                "requestBody": {
+                    "required": true,
+                    "content": { "application/json": { "schema": schema_ref(request) } },
+                },
+                "responses": {
+                    "200": json_response("A new token pair", schema_ref("TokenResponse")),
+                    "401": error_response("Invalid credentials or token"),
+                },
+            });
+            self.add_operation(path, "post", operation);
+        }
+    }
+
+    fn api_key_operations(&mut self) {
+        let Some(api_keys) = &self.config.api_keys else {
+            return;
+        };
+        self.schemas.insert(
+            "IssueApiKeyRequest".into(),
+            json!({
+                "type": "object",
+                "required": ["name"],
+                "properties": { "name": { "type": "string" } },
+            }),
+        );
+        self.schemas.insert(
+            "IssuedApiKey".into(),
+            json!({
+                "type": "object",
+                "required": ["id", "name", "key"],
+                "properties": {
+                    "id": { "type": "integer", "format": "int64" },
+                    "name": { "type": "string" },
+                    "key": { "type": "string", "description": "Only returned once" },
+                },
+            }),
+        );
+        let issue = Endpoint {
+            method: "POST".to_string(),
+            path: API_KEY_PATH.to_string(),
+            auth: Some(true),
+            ..Endpoint::default()
+        };
+        let security = self.security(&issue);
+        let mut operation = json!({
+            "tags": ["ApiKey"],
+            "operationId": "issue_api_key",
+            "requestBody": {
+                "required": true,
+                "content": { "application/json": { "schema": schema_ref("IssueApiKeyRequest") } },
+            },
+            "responses": {
+                "201": json_response("The new key", schema_ref("IssuedApiKey")),
+                "401": error_response("Missing or invalid credentials"),
+            },
+            "security": [security],
+        });
+        if api_keys.admin_roles.is_some() {
+            operation["responses"]["403"] = error_response("Not allowed for this caller");
+        }
+        self.add_operation(API_KEY_PATH, "post", operation);
+    }
+}
+
+/// `/todos/:id` and `/todos/{id}` both become `/todos/{id}`.
+fn openapi_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(name) => format!("{{{}}}", name),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn path_parameters(endpoint: &Endpoint) -> Vec<Value> {
+    endpoint
+        .path_placeholders()
+        .iter()
+        .map(|name| {
+            let schema = endpoint
+                .path_params
+                .iter()
+                .flatten()
+                .find(|f| &f.name == name)
+                .map_or_else(|| json!({ "type": "string" }), field_schema);
+            parameter("path", name, schema, true)
+        })
+        .collect()
+}
+
+fn parameter(location: &str, name: &str, schema: Value, required: bool) -> Value {
+    json!({ "name": name, "in": location, "required": required, "schema": schema })
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+fn json_response(description: &str, schema: Value) -> Value {
+    json!({
+        "description": description,
+        "content": { "application/json": { "schema": schema } },
+    })
+}
+
+/// Errors are sent as plain-text messages.
+fn error_response(description: &str) -> Value {
+    json!({
+        "description": description,
+        "content": { "text/plain": { "schema": { "type": "string" } } },
+    })
+}
+
+/// An object with one property per field; non-`Option` fields are required
+/// unless `required` is false, as for `PATCH` bodies.
+fn object_schema(fields: &[Field], required: bool) -> Value {
+    let mut properties = Map::new();
+    let mut required_fields = Vec::new();
+    for field in fields {
+        properties.insert(field.name.clone(), field_schema(field));
+        if required && !unwrap_option(&field.field_type).1 {
+            required_fields.push(field.name.clone());
+        }
+    }
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required_fields.is_empty() {
+        schema["required"] = json!(required_fields);
+    }
+    schema
+}
+
+fn field_schema(field: &Field) -> Value {
+    let (inner, optional) = unwrap_option(&field.field_type);
+    let mut schema = type_schema(inner);
+    if let Some(max_length) = field.max_length.filter(|_| schema["type"] == "string") {
+        schema["maxLength"] = json!(max_length);
+    }
+    let default = match &field.default {
+        Some(DefaultValue::Bool(value)) => Some(json!(value)),
+        Some(DefaultValue::Integer(value)) => Some(json!(value)),
+        Some(DefaultValue::Float(value)) => Some(json!(value)),
+        Some(DefaultValue::Text(value)) => Some(json!(value)),
+        Some(DefaultValue::Sql { .. }) | None => None,
+    };
+    if let Some(default) = default {
+        schema["default"] = default;
+    }
+    if optional {
+        nullable(schema)
+    } else {
+        schema
+    }
+}
+
+/// JSON Schema of a Rust type, matching how serde serializes it.
+fn type_schema(field_type: &str) -> Value {
+    match type_name(field_type).as_str() {
+        "i8" | "i16" | "i32" => json!({ "type": "integer", "format": "int32" }),
+        "u8" | "u16" => json!({ "type": "integer", "format": "int32", "minimum": 0 }),
+        "i64" | "isize" => json!({ "type": "integer", "format": "int64" }),
+        "u32" | "u64" | "usize" => json!({ "type": "integer", "format": "int64", "minimum": 0 }),
+        "f32" => json!({ "type": "number", "format": "float" }),
+        "f64" => json!({ "type": "number", "format": "double" }),
+        "bool" => json!({ "type": "boolean" }),
+        "String" | "str" | "&str" => json!({ "type": "string" }),
+        "Uuid" => json!({ "type": "string", "format": "uuid" }),
+        "NaiveDate" => json!({ "type": "string", "format": "date" }),
+        "NaiveTime" => json!({ "type": "string", "format": "time" }),
+        "NaiveDateTime" => {
+            json!({ "type": "string", "description": "Date and time without offset" })
+        }
+        "DateTime<Utc>" => json!({ "type": "string", "format": "date-time" }),
+        "Vec<u8>" => {
+            json!({ "type": "array", "items": { "type": "integer", "minimum": 0, "maximum": 255 } })
+        }
+        // `serde_json::Value` and anything unknown can be any JSON value
+        _ => json!({}),
+    }
+}
+
+/// OpenAPI 3.1 spells nullable types as a `type` list.
+fn nullable(mut schema: Value) -> Value {
+    match schema.get("type").cloned() {
+        Some(Value::String(ty)) => {
+            schema["type"] = json!([ty, "null"]);
+            schema
+        }
+        _ => schema,
+    }
+}
+
+#[cfg(test)]
+mod tests;
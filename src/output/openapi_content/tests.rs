@@ -0,0 +1,187 @@
+use super::{openapi_context, openapi_document};
+use crate::domain::models::config::Config;
+use crate::output::TemplateEngine;
+use serde_json::{json, Value};
+
+fn config(project_name: &str) -> Config {
+    toml::from_str(&format!(
+        r#"
+project_name = "{project_name}"
+database_url = "postgres://localhost/notes"
+database_type = "postgres"
+framework = "Axum"
+
+[openapi]
+serve = true
+
+[[models]]
+name = "Note"
+table_name = "notes"
+fields = [
+    {{ name = "id", type = "i32" }},
+    {{ name = "body", type = "String" }},
+]
+endpoints = [{{ method = "GET", path = "/notes" }}]
+"#
+    ))
+    .unwrap()
+}
+
+#[test]
+fn the_swagger_ui_title_is_html_escaped() {
+    let config = config("notes & <more>");
+    let rendered = TemplateEngine::new(&config)
+        .render("openapi.rs.tmpl", openapi_context(&config))
+        .unwrap();
+    assert!(
+        rendered.contains("<title>notes &amp; &lt;more&gt;</title>"),
+        "{}",
+        rendered
+    );
+}
+
+/// Users with a hidden password hash, and their books.
+const LIBRARY: &str = r#"
+project_name = "library"
+database_url = "postgres://localhost/library"
+database_type = "postgres"
+framework = "Axum"
+
+[auth]
+user_model = "User"
+role_field = "role"
+
+[[models]]
+name = "User"
+table_name = "users"
+response_dto = true
+fields = [
+    { name = "id", type = "i32" },
+    { name = "email", type = "String", max_length = 80 },
+    { name = "password_hash", type = "String", hidden = true },
+    { name = "role", type = "String", default = "member" },
+]
+endpoints = [
+    { method = "GET", path = "/users/:id", roles = ["admin"] },
+]
+
+[[models]]
+name = "Book"
+table_name = "books"
+relations = [{ kind = "belongs_to", model = "User" }]
+fields = [
+    { name = "id", type = "i64" },
+    { name = "title", type = "String" },
+    { name = "subtitle", type = "Option<String>" },
+    { name = "user_id", type = "i32" },
+]
+endpoints = [
+    { method = "GET", path = "/books", query_params = [{ name = "title", type = "String" }] },
+    { method = "PATCH", path = "/books/{id}", auth = true, path_params = [
+        { name = "id", type = "i64" },
+    ] },
+]
+"#;
+
+fn library() -> Value {
+    openapi_document(&toml::from_str(LIBRARY).unwrap())
+}
+
+fn parameter_names(operation: &Value) -> Vec<&str> {
+    operation["parameters"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|parameter| parameter["name"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn list_endpoints_are_paginated_and_filterable() {
+    let document = library();
+    let list = &document["paths"]["/books"]["get"];
+    assert_eq!(
+        parameter_names(list),
+        ["title", "sort", "limit", "offset", "after", "include"]
+    );
+    let ok = &list["responses"]["200"];
+    assert_eq!(
+        ok["content"]["application/json"]["schema"],
+        json!({ "$ref": "#/components/schemas/BookWithRelationsPage" })
+    );
+    assert!(ok["headers"]["Link"].is_object());
+
+    let page = &document["components"]["schemas"]["BookWithRelationsPage"];
+    assert_eq!(
+        page["properties"]["next_cursor"],
+        json!({ "type": ["integer", "null"], "format": "int64" })
+    );
+    let with_relations = &document["components"]["schemas"]["BookWithRelations"];
+    assert_eq!(
+        with_relations["allOf"][1]["properties"]["user"],
+        json!({ "$ref": "#/components/schemas/UserResponse" })
+    );
+}
+
+#[test]
+fn read_schemas_leave_out_hidden_fields() {
+    let document = library();
+    let user = &document["components"]["schemas"]["UserResponse"];
+    assert_eq!(
+        user["properties"],
+        json!({
+            "id": { "type": "integer", "format": "int32" },
+            "email": { "type": "string", "maxLength": 80 },
+            "role": { "type": "string", "default": "member" },
+        })
+    );
+    assert_eq!(user["required"], json!(["id", "email", "role"]));
+}
+
+#[test]
+fn patch_bodies_require_nothing() {
+    let document = library();
+    let patch = &document["paths"]["/books/{id}"]["patch"];
+    assert_eq!(
+        patch["parameters"][0],
+        json!({
+            "name": "id",
+            "in": "path",
+            "required": true,
+            "schema": { "type": "integer", "format": "int64" },
+        })
+    );
+    let body = &document["components"]["schemas"]["PatchBookRequest"];
+    assert!(body.get("required").is_none(), "{}", body);
+    assert_eq!(
+        body["properties"]["subtitle"]["type"],
+        json!(["string", "null"])
+    );
+    let responses = patch["responses"].as_object().unwrap();
+    assert_eq!(
+        responses.keys().collect::<Vec<_>>(),
+        ["200", "404", "400", "401"]
+    );
+}
+
+#[test]
+fn protected_endpoints_require_a_bearer_token() {
+    let document = library();
+    assert_eq!(
+        document["components"]["securitySchemes"]["bearerAuth"],
+        json!({ "type": "http", "scheme": "bearer", "bearerFormat": "JWT" })
+    );
+    // `:id` paths are written the OpenAPI way
+    let get_user = &document["paths"]["/users/{id}"]["get"];
+    assert_eq!(get_user["security"], json!([{ "bearerAuth": [] }]));
+    assert!(get_user["responses"]["403"].is_object());
+    assert!(document["paths"]["/books"]["get"].get("security").is_none());
+
+    for path in ["/auth/login", "/auth/refresh"] {
+        assert!(document["paths"][path]["post"].is_object(), "{}", path);
+    }
+    assert_eq!(
+        document["components"]["schemas"]["LoginRequest"]["required"],
+        json!(["email", "password"])
+    );
+}
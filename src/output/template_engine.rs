@@ -0,0 +1,161 @@
+use crate::domain::models::config::Config;
+use crate::output::quote_identifier;
+use minijinja::{
+    context, AutoEscape, Environment, Error, ErrorKind, State, UndefinedBehavior, Value,
+};
+
+/// The built-in templates, keyed by the file name an override in `templates_dir` must use.
+pub const TEMPLATES: &[(&str, &str)] = &[
+    ("Cargo.toml.tmpl", include_str!("templates/Cargo.toml.tmpl")),
+    ("main.rs.tmpl", include_str!("templates/main.rs.tmpl")),
+    (
+        "database_connection.rs.tmpl",
+        include_str!("templates/database_connection.rs.tmpl"),
+    ),
+    ("env.tmpl", include_str!("templates/env.tmpl")),
+    ("Dockerfile.tmpl", include_str!("templates/Dockerfile.tmpl")),
+    (
+        "dockerignore.tmpl",
+        include_str!("templates/dockerignore.tmpl"),
+    ),
+    ("gitignore.tmpl", include_str!("templates/gitignore.tmpl")),
+    (
+        "compose.yaml.tmpl",
+        include_str!("templates/compose.yaml.tmpl"),
+    ),
+    ("mod.rs.tmpl", include_str!("templates/mod.rs.tmpl")),
+    ("page.rs.tmpl", include_str!("templates/page.rs.tmpl")),
+    ("error.rs.tmpl", include_str!("templates/error.rs.tmpl")),
+    ("model.rs.tmpl", include_str!("templates/model.rs.tmpl")),
+    (
+        "repository_trait.rs.tmpl",
+        include_str!("templates/repository_trait.rs.tmpl"),
+    ),
+    (
+        "repository_impl.rs.tmpl",
+        include_str!("templates/repository_impl.rs.tmpl"),
+    ),
+    ("service.rs.tmpl", include_str!("templates/service.rs.tmpl")),
+    ("http.rs.tmpl", include_str!("templates/http.rs.tmpl")),
+    ("handler.rs.tmpl", include_str!("templates/handler.rs.tmpl")),
+    (
+        "nested_handler.rs.tmpl",
+        include_str!("templates/nested_handler.rs.tmpl"),
+    ),
+    (
+        "relations.rs.tmpl",
+        include_str!("templates/relations.rs.tmpl"),
+    ),
+    (
+        "middleware.rs.tmpl",
+        include_str!("templates/middleware.rs.tmpl"),
+    ),
+    ("auth.rs.tmpl", include_str!("templates/auth.rs.tmpl")),
+    (
+        "auth_handlers.rs.tmpl",
+        include_str!("templates/auth_handlers.rs.tmpl"),
+    ),
+    (
+        "api_keys.rs.tmpl",
+        include_str!("templates/api_keys.rs.tmpl"),
+    ),
+    (
+        "api_key_handlers.rs.tmpl",
+        include_str!("templates/api_key_handlers.rs.tmpl"),
+    ),
+    ("openapi.rs.tmpl", include_str!("templates/openapi.rs.tmpl")),
+    (
+        "openapi.yaml.tmpl",
+        include_str!("templates/openapi.yaml.tmpl"),
+    ),
+    ("column.sql.tmpl", include_str!("templates/column.sql.tmpl")),
+    (
+        "create_table.sql.tmpl",
+        include_str!("templates/create_table.sql.tmpl"),
+    ),
+    (
+        "migration.sql.tmpl",
+        include_str!("templates/migration.sql.tmpl"),
+    ),
+];
+
+/// Renders generated files from the built-in templates or their overrides.
+/// Every template sees the whole config as `config`, next to its own variables.
+pub struct TemplateEngine {
+    env: Environment<'static>,
+    config: Value,
+}
+
+impl TemplateEngine {
+    pub fn new(config: &Config) -> Self {
+        let mut env = Environment::new();
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+        env.set_keep_trailing_newline(true);
+        env.set_auto_escape_callback(|_| AutoEscape::None);
+        // A misspelt variable in an override should fail rather than render as nothing
+        env.set_undefined_behavior(UndefinedBehavior::Strict);
+        // `"a \"b\""`: a Rust string literal, e.g. for SQL with quoted identifiers
+        env.add_filter("rust_string", |value: String| format!("{:?}", value));
+        // `r#"..."#` with enough `#`s that the value can't end it early
+        env.add_filter("rust_raw_string", |value: String| {
+            let mut hashes = "#".to_string();
+            while value.contains(&format!("\"{}", hashes)) {
+                hashes.push('#');
+            }
+            format!("r{}\"{}\"{}", hashes, value, hashes)
+        });
+        env.add_filter("to_yaml", |value: Value| {
+            serde_yaml::to_string(&value)
+                .map(|yaml| yaml.trim_end().to_string())
+                .map_err(|e| Error::new(ErrorKind::InvalidOperation, e.to_string()))
+        });
+        env.add_filter("to_json", |value: Value| {
+            serde_json::to_string_pretty(&value)
+                .map_err(|e| Error::new(ErrorKind::InvalidOperation, e.to_string()))
+        });
+        // The name quoted for `config.database_type`
+        env.add_filter("quote_identifier", |state: &State, name: String| {
+            let database_type = state
+                .lookup("config")
+                .and_then(|config| config.get_attr("database_type").ok())
+                .map(|database_type| database_type.to_string())
+                .unwrap_or_default();
+            quote_identifier(&name, &database_type)
+        });
+        for (name, source) in TEMPLATES {
+            env.add_template(name, source)
+                .expect("built-in templates are valid");
+        }
+        Self {
+            env,
+            config: Value::from_serialize(config),
+        }
+    }
+
+    /// Replaces the built-in template `name` with `source`.
+    pub fn override_template(&mut self, name: &str, source: String) -> Result<(), String> {
+        if !TEMPLATES.iter().any(|(known, _)| *known == name) {
+            let known: Vec<_> = TEMPLATES.iter().map(|(known, _)| *known).collect();
+            return Err(format!(
+                "unknown template '{}', expected one of: {}",
+                name,
+                known.join(", ")
+            ));
+        }
+        self.env
+            .add_template_owned(name.to_string(), source)
+            .map_err(|e| format!("{:#}", e))
+    }
+
+    /// Renders the template `name` with `context` on top of `config`.
+    pub fn render(&self, name: &str, context: Value) -> Result<String, String> {
+        self.env
+            .get_template(name)
+            .and_then(|template| template.render(context! { config => self.config, ..context }))
+            .map_err(|e| format!("{:#}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests;
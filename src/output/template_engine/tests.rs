@@ -0,0 +1,60 @@
+use super::TemplateEngine;
+use crate::domain::models::config::Config;
+use crate::domain::models::template::Template;
+use minijinja::context;
+
+fn config(database_type: &str) -> Config {
+    toml::from_str(&format!(
+        r#"
+project_name = "shop"
+database_url = "{database_type}://localhost/shop"
+database_type = "{database_type}"
+framework = "Axum"
+
+[[models]]
+name = "Order"
+table_name = "orders"
+fields = [
+    {{ name = "id", type = "i32" }},
+    {{ name = "note", type = "Option<String>", column = "order_note" }},
+]
+"#
+    ))
+    .unwrap()
+}
+
+fn render_override(config: &Config, name: &str, source: &str, context: minijinja::Value) -> String {
+    let mut engine = TemplateEngine::new(config);
+    engine.override_template(name, source.to_string()).unwrap();
+    engine.render(name, context).unwrap()
+}
+
+#[test]
+fn identifiers_are_quoted_for_the_configured_database() {
+    let source = r#"{{ "orders" | quote_identifier }}"#;
+    for (database_type, quoted) in [("postgres", "\"orders\""), ("mysql", "`orders`")] {
+        let rendered = render_override(&config(database_type), "mod.rs.tmpl", source, context! {});
+        assert_eq!(rendered, quoted);
+    }
+}
+
+#[test]
+fn strings_become_rust_literals() {
+    let source = r##"{{ 'a "b"' | rust_string }} {{ 'a "#b"' | rust_raw_string }}"##;
+    let rendered = render_override(&config("postgres"), "mod.rs.tmpl", source, context! {});
+    assert_eq!(rendered, r####""a \"b\"" r##"a "#b""##"####);
+}
+
+#[test]
+fn overrides_can_build_a_model_from_its_fields() {
+    let config = config("postgres");
+    let source = "{% for field in model.fields %}\
+                  {{ model.name }}.{{ field.name }}: {{ field.inner_type }} {{ field.column }} {{ \"null\" if field.optional else \"not null\" }}\n\
+                  {% endfor %}";
+    let context = Template::new(config.clone()).model_context(&config.models[0]);
+    let rendered = render_override(&config, "model.rs.tmpl", source, context);
+    assert_eq!(
+        rendered,
+        "Order.id: i32 id not null\nOrder.note: String order_note null\n"
+    );
+}